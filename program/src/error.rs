@@ -23,6 +23,56 @@ pub enum AuctionError {
     ActiveAuction,
     #[error("No Bidders Error: There are no bidders participating in this auction.")]
     NoBidderFound,
+    #[error("Seed Error: The revealed seed does not match the committed seed hash.")]
+    InvalidSeed,
+    #[error("Draw Error: The raffle winner has already been drawn.")]
+    AlreadyDrawn,
+    #[error("Draw Error: The raffle winner has not been drawn yet.")]
+    NotDrawn,
+    #[error("Ticket Error: The raffle account has no room left for another ticket range.")]
+    TicketLedgerFull,
+    #[error("Price Limit Error: The purchase price exceeds the buyer's maximum total price.")]
+    PriceLimitExceeded,
+    #[error("Supply Error: Not enough items are left for this purchase.")]
+    InsufficientSupply,
+    #[error("Metadata Error: The token metadata account could not be decoded.")]
+    InvalidMetadata,
+    #[error("Collection Error: The NFT is not a verified member of the required collection.")]
+    CollectionMismatch,
+    #[error("Order Error: The provided orders do not match the open orders of the call auction.")]
+    OrderSetMismatch,
+    #[error("Excessive Ask Price Error: The ask amount is too high. Please lower your ask.")]
+    ExcessiveAskPrice,
+    #[error("Basis Points Error: The basis points value exceeds 10000.")]
+    InvalidBasisPoints,
+    #[error("Admin Error: The signer is not the admin of the config.")]
+    NotAdmin,
+    #[error("Payee Error: Up to 5 payees of an English auction may split the proceeds, with shares adding up to 10000 basis points.")]
+    InvalidPayees,
+    #[error("Price Feed Error: The price of the bid's mint is missing or stale.")]
+    StalePrice,
+    #[error("Bond Error: A bond must be posted before bidding.")]
+    BondRequired,
+    #[error("Bond Error: The bond can't be refunded yet.")]
+    BondLocked,
+    #[error("House Error: The signer is not the authority of the auction house.")]
+    NotHouseAuthority,
+    #[error("House Error: The auction doesn't comply with the policy of its auction house.")]
+    HousePolicyViolation,
+    #[error("Pause Error: The program is paused, only cancellations, refunds and settlements are accepted.")]
+    Paused,
+    #[error("Gate Error: The bidder doesn't hold the token gating the auction.")]
+    NotGateHolder,
+    #[error("Allowlist Error: The signer is not on the allowlist.")]
+    NotAllowlisted,
+    #[error("Denylist Error: The signer is on the denylist.")]
+    Denied,
+    #[error("Duration Error: The auction duration is zero or outside the allowed bounds.")]
+    InvalidDuration,
+    #[error("Order Book Error: The call auction account has no room left for another order.")]
+    OrderBookFull,
+    #[error("Slot Hash Error: The hash of the draw slot is not recorded or no longer in the slot hashes sysvar.")]
+    SlotHashUnavailable,
 }
 
 impl From<AuctionError> for ProgramError {
@@ -30,4 +80,4 @@ impl From<AuctionError> for ProgramError {
         msg!("Error: {:?}", e);
         ProgramError::Custom(e as u32)
     }
-}
\ No newline at end of file
+}
@@ -1,43 +1,139 @@
 use crate::error::AuctionError::InvalidInstruction;
+use crate::state::AuctionKind;
 use solana_program::program_error::ProgramError;
+use solana_program::pubkey::Pubkey;
 
 pub enum AuctionInstruction {
-    /// Starts the auction by creating and populating an escrow account and transferring ownership of the given temp NFT account to the PDA
+    /// Starts the auction by creating and populating an escrow account and transferring ownership of the given temp account to the PDA
+    /// English and barter auctions escrow the NFT, a reverse auction escrows the FT budget the exhibitor is willing to pay
     ///
     ///
     /// Accounts expected:
     ///
-    /// 0. `[signer]` The account of the person starting the auction
-    /// 1. `[writable]` Temporary NFT account that should be created prior to this instruction and owned by the exhibitor
-    /// 2. `[]` The exhibitor's NFT account for the token they will receive should the trade go through
-    /// 3. `[writable]` The escrow account, it will hold all necessary info about the auction.
-    /// 4. `[]` The rent sysvar
-    /// 4. `[]` The clock sysvar
-    /// 5. `[]` The token program
+    /// 0. `[signer]` The account of the person starting the auction, writable for English auctions to pay for the vault
+    /// 1. `[writable]` The exhibitor's NFT account, or FT account holding the budget of a reverse auction
+    /// 2. `[writable]` Temporary account that should be created prior to this instruction and owned by the exhibitor
+    /// 3. `[]` The exhibitor's FT account to receive the proceeds, or to get the unspent budget of a reverse auction back to,
+    ///    or the account receiving the lamports of a lamport auction
+    /// 4. `[writable]` The escrow account, it will hold all necessary info about the auction.
+    /// 5. `[]` The rent sysvar
+    /// 6. `[]` The clock sysvar
+    /// 7. `[]` The token program
+    /// 8. `[]` The config account
+    /// 9. `[]` The denylist page of the exhibitor, the PDA derived from `b"denylist"` and the first byte of their wallet
+    /// 10. `[]` The collection mint the NFT must be a verified member of, only for reverse auctions
+    /// 11. `[writable]` The vault account holding the bids, the PDA derived from `b"vault"` and the escrow account,
+    ///     only for English auctions that aren't reference priced
+    /// 12. `[]` The system program, only for English auctions that aren't reference priced
+    /// 13. `[]` The mint of the FT bids are made in, only for English auctions paid in FT that aren't reference priced
+    /// 14. `[]` For each payee, the payee's FT account to receive their share of the proceeds, or their wallet in a lamport auction
+    /// 15. `[]` The auction house to exhibit in, only when `house` is set
+    /// 16. `[signer, writable]` The rent payer, only when `rent_payer` is set and isn't the exhibitor
+    ///
+    /// Bids of English auctions that aren't reference priced are deposited into the vault, a token account owned by itself
+    /// or a lamport account owned by the program, instead of temporary accounts of their own.
+    /// The rent of the escrow and temporary accounts is returned to the rent payer when the auction is closed or cancelled,
+    /// while the exhibitor pays for and gets back the vault.
+    ///
+    /// The exhibitor's FT account of a reference priced auction is only checked at close, where it must be owned by the exhibitor
+    /// and hold the mint the winning bid was made in.
+    ///
+    /// An auction exhibited in a house must last within the house's duration bounds and be paid in one of its payment mints,
+    /// and inherits its minimum bid increment and anti-snipe policy.
+    /// Houses restricting payment mints don't take barter or reference priced auctions, whose bids can come in any mint.
+    ///
+    /// Only holders of an NFT of the gate collection, or of at least `gate_amount` of the gate mint,
+    /// may bid in a token-gated auction.
+    ///
+    /// Allowlists are Merkle trees whose leaves are the keccak hashes of the allowed wallets,
+    /// every pair of nodes being hashed in sorted order.
+    ///
+    /// An English auction ending without bids can be relisted by anyone with `CrankRelist` up to `auto_relist_cycles` times,
+    /// its price dropping by `auto_relist_price_drop_bps` every time.
     Exhibit {
-        /// Initial NFT price
+        /// Initial NFT price, or the budget of a reverse auction
         initial_price: u64,
-        /// Auction duration
+        /// Auction duration, within the bounds of the config
         seconds: u64,
+        /// Whether the auction sells or buys the NFT
+        kind: AuctionKind,
+        /// Whether bids are paid in lamports with `BidLamports`, only for English auctions
+        pays_in_lamports: bool,
+        /// Whether the initial price is in reference units and bids are made in any mint with a price feed,
+        /// only for English auctions paid in FT without payees
+        prices_in_reference: bool,
+        /// Lamports every bidder bonds with `PostBond` before their first bid, only for English auctions
+        bond_amount: u64,
+        /// Share of the proceeds of every payee, in basis points, empty when the exhibitor gets everything
+        payee_shares_bps: Vec<u16>,
+        /// Index of the payee getting the rounding dust
+        dust_payee_index: u8,
+        /// Wallet pubkey that paid the rent of the escrow and temporary accounts, the exhibitor when `None`,
+        /// must sign the instruction
+        rent_payer: Option<Pubkey>,
+        /// Auction house the auction is exhibited in, `None` for auctions outside any house
+        house: Option<Pubkey>,
+        /// Collection or mint gating the bids, `None` when anyone may bid, only for English and reverse auctions
+        gate: Option<Pubkey>,
+        /// Amount of the gate mint bidders must hold, zero when the gate is a collection, only present when `gate` is set
+        gate_amount: u64,
+        /// Merkle root of the wallets allowed to bid, `None` when anyone may bid
+        bidder_allowlist_root: Option<[u8; 32]>,
+        /// Merkle proof the exhibitor is on the exhibitor allowlist of the house, empty when the house has none
+        proof: Vec<[u8; 32]>,
+        /// Number of automatic relistings of the auction should it end without bids, only for English auctions
+        auto_relist_cycles: u8,
+        /// Price drop of every automatic relisting, in basis points of the price
+        auto_relist_price_drop_bps: u16,
     },
 
-    /// Bid on the auction and transfer ownership of the given temp FT account to the PDA
+    /// Bid on the auction and transfer ownership of the given temp account to the PDA
+    /// In a reverse auction the bidder escrows the NFT they ask to sell and the lowest ask wins
+    /// Barter auctions only take offers made with `MakeOffer`
+    /// English auctions paid in FT keep the outbid highest bid in escrow as the runner-up, refunding the previous runner-up
+    /// Bids placed within the anti-snipe window of an auction exhibited in a house extend the auction
     ///
     ///
     /// Accounts expected:
     ///
     /// 0. `[signer]`  The account of the person bidding on the auction
-    /// 1. `[writable]` The current highest bidder's temporary FT account
-    /// 2. `[writable]` The current highest bidder's FT account to get back to when the other person become the highest bidder
-    /// 3. `[writable]` The bidder's temporary FT account for depositing FT in escrow
-    /// 4. `[writable]` The bidder's FT account to get back to when the other person become the highest bidder
-    /// 5. `[writable]` The escrow account, it will hold all necessary info about the auction.
-    /// 6. `[]` The clock sysvar
-    /// 7. `[]` The token program
-    /// 8. `[]` The PDA account
+    /// 1. `[writable]` The current highest bidder's account
+    /// 2. `[writable]` The current highest bidder's temporary account, or the vault of the auction
+    /// 3. `[writable]` The current highest bidder's account to get back to when the other person become the highest bidder
+    /// 4. `[writable]` The bidder's temporary account for depositing FT, or the NFT of a reverse auction, in escrow,
+    ///    or the vault of the auction
+    /// 5. `[writable]` The bidder's account to get back to when the other person become the highest bidder
+    /// 6. `[writable]` The escrow account, it will hold all necessary info about the auction.
+    /// 7. `[]` The clock sysvar
+    /// 8. `[]` The token program
+    /// 9. `[]` The PDA account
+    /// 10. `[]` The metadata account of the NFT, only for reverse auctions
+    /// 11. `[]` The config account
+    /// 12. `[]` The denylist page of the bidder, the PDA derived from `b"denylist"` and the first byte of their wallet
+    /// 13. `[]` The price feed account of the bid's mint, only for reference priced auctions
+    /// 14. `[]` The bidder's bond account, only for bonded auctions
+    /// 15. Only for token-gated auctions:
+    ///    0. `[]` The bidder's token account holding the gate
+    ///    1. `[]` The metadata account of the held NFT, only when the gate is a collection
+    /// 16. `[writable]` The rent payer of the current highest bidder's temporary account, only when the current highest bid
+    ///     is refunded and the rent payer isn't the current highest bidder
+    /// 17. Only when the auction has a runner-up:
+    ///    0. `[writable]` The runner-up's account
+    ///    1. `[writable]` The runner-up's temporary account, or the vault of the auction
+    ///    2. `[writable]` The runner-up's account to get the bid back to
+    ///    3. `[writable]` The rent payer of the runner-up's temporary account, only when it isn't the runner-up
+    /// 18. `[signer, writable]` The rent payer of the bidder's temporary account, only when `rent_payer` is set,
+    ///     isn't the bidder and the bid isn't deposited into the vault of the auction
     Bid {
-        /// Bidding price
+        /// Bidding price, or the asking price of a reverse auction
         price: u64,
+        /// Wallet pubkey of the referrer paid a share of the sale should the bid win, can't be the bidder's own wallet
+        referrer: Option<Pubkey>,
+        /// Wallet pubkey that paid the rent of the bidder's temporary account, the bidder when `None`,
+        /// must sign the instruction, ignored for bids deposited into the vault of the auction
+        rent_payer: Option<Pubkey>,
+        /// Merkle proof the bidder is on the bidder allowlist of the auction, empty when the auction has none
+        proof: Vec<[u8; 32]>,
     },
 
     /// Cancels a auction
@@ -47,49 +143,1117 @@ pub enum AuctionInstruction {
     /// Accounts expected:
     ///
     /// 0. `[signer]` The account of the person started the auction
-    /// 1. `[writable]` The PDA's temporary NFT account
-    /// 2. `[writable]` The exhibitor's NFT account to get the token back to
+    /// 1. `[writable]` The PDA's temporary account
+    /// 2. `[writable]` The exhibitor's account to get the token back to
     /// 3. `[writable]` The escrow account holding the escrow info
     /// 4. `[]` The token program
     /// 5. `[]` The PDA account
+    /// 6. `[writable]` The vault account, only for English auctions that aren't reference priced
+    /// 7. `[writable]` The exhibitor's FT account to get FT sent to the vault outside of bids to, only when the
+    ///    vault holds any
+    /// 8. `[writable]` The rent payer of the auction, only when it isn't the exhibitor
     Cancel {},
 
     /// Closes a auction
     /// Only the successful bidder can close the auction
+    /// The verified creators of the sold NFT are paid their royalty before the exhibitor
     ///
     ///
     /// Accounts expected:
     ///
     /// 0. `[signer]` The account of the successful bidder
-    /// 1. `[]` The account ot the person started the auction to close the escrow
-    /// 2. `[writable]` The temporary NFT account to send to successful bidder
-    /// 3. `[writable]` The FT account to sent FT to the exhibitor'
-    /// 4. `[writable]` The temporary FT account that holds the successful bidder's FT
-    /// 5. `[writable]` The NFT account that will receive NFT
+    /// 1. `[writable]` The account ot the person started the auction to close the escrow
+    /// 2. `[writable]` The temporary account holding the NFT, or the FT budget of a reverse auction
+    /// 3. `[writable]` The FT account to sent FT to the exhibitor, or the unspent budget of a reverse auction,
+    ///    any exhibitor's FT account of the winning bid's mint for reference priced auctions
+    /// 4. `[writable]` The temporary account that holds the successful bidder's FT, or NFT of a reverse auction,
+    ///    or the vault of the auction
+    /// 5. `[writable]` The NFT account that will receive NFT, or the FT account receiving the ask of a reverse auction
     /// 6. `[writable]` The escrow account holding the escrow info
     /// 7. `[]` The clock sysvar
     /// 8. `[]` The token program
     /// 9. `[]` The PDA account
+    /// 10. `[writable]` The treasury's FT account that will receive the protocol fee, or the treasury itself in a lamport auction,
+    ///     the treasury of the house for auctions exhibited in a house, only when the fee isn't zero
+    /// 11. `[]` The metadata account of the sold NFT, the metadata PDA of its mint even when it was never created,
+    ///     in which case no royalty is paid
+    /// 12. `[writable]` The exhibitor's NFT account that will receive the NFT, only for reverse auctions
+    /// 13. `[writable]` For each verified creator of the sold NFT, in metadata order, the creator's FT account receiving the royalty
+    /// 14. `[writable]` The referrer's FT account receiving the referral share, only when the winning bid was referred
+    /// 15. `[writable]` For each payee of the auction, the payee's FT account receiving their share of the proceeds
+    /// 16. Only when the auction has a runner-up:
+    ///    0. `[writable]` The runner-up's account
+    ///    1. `[writable]` The runner-up's temporary account, or the vault of the auction
+    ///    2. `[writable]` The runner-up's account to get the bid back to
+    ///    3. `[writable]` The rent payer of the runner-up's temporary account, only when it isn't the runner-up
+    /// 17. `[writable]` The rent payer of the successful bidder's temporary account, only when it isn't the successful bidder
+    /// 18. `[writable]` The rent payer of the auction, only when it isn't the exhibitor
+    ///
+    /// The fee is the one in effect when the auction was exhibited, that of its house for auctions exhibited in a house.
+    ///
+    /// The creators, the referrer and the payees of a lamport auction are paid to their wallets directly.
+    /// A runner-up offered a second chance accepts it by closing the auction before the offer expires.
     Close {},
+
+    /// Starts a raffle by escrowing the NFT and an empty FT account collecting the ticket proceeds
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the person starting the raffle
+    /// 1. `[writable]` The exhibitor's NFT account holding the NFT
+    /// 2. `[writable]` Temporary NFT account that should be created prior to this instruction and owned by the exhibitor
+    /// 3. `[]` The exhibitor's FT account to receive the proceeds
+    /// 4. `[writable]` Empty temporary FT account that should be created prior to this instruction and owned by the exhibitor
+    /// 5. `[writable]` The raffle account, it will hold the raffle info followed by the ticket ranges
+    /// 6. `[]` The rent sysvar
+    /// 7. `[]` The clock sysvar
+    /// 8. `[]` The token program
+    /// 9. `[]` The config account
+    ExhibitRaffle {
+        /// Price of a single ticket, can't be zero
+        ticket_price: u64,
+        /// Ticket sale duration, within the bounds of the config
+        seconds: u64,
+        /// SHA-256 hash of the secret seed revealed at `Draw`
+        seed_hash: [u8; 32],
+    },
+
+    /// Buys raffle tickets by transferring FT into the raffle's proceeds account
+    /// The exhibitor can't buy tickets of their own raffle
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the ticket buyer
+    /// 1. `[writable]` The buyer's FT account to pay from
+    /// 2. `[writable]` The raffle's temporary FT account collecting the proceeds
+    /// 3. `[writable]` The raffle account
+    /// 4. `[]` The clock sysvar
+    /// 5. `[]` The token program
+    /// 6. `[]` The config account
+    BuyTickets {
+        /// Number of tickets to buy
+        count: u64,
+    },
+
+    /// Reveals the exhibitor's seed and draws the winning ticket once the draw slot was recorded with `RecordDrawSlot`
+    /// The winning ticket is derived from the seed and the hash of the draw slot, which the slot hashes sysvar only keeps
+    /// for the most recent 512 slots, and the seed must be revealed within `RAFFLE_REVEAL_PERIOD_SEC` of the end of the sale
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[writable]` The raffle account
+    /// 1. `[]` The clock sysvar
+    /// 2. `[]` The slot hashes sysvar
+    Draw {
+        /// Secret seed whose hash was committed at `ExhibitRaffle`
+        seed: [u8; 32],
+    },
+
+    /// Settles a drawn raffle, sending the NFT to the winner and all proceeds to the exhibitor
+    /// Either the winner or the exhibitor can settle the raffle
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the winner or the exhibitor
+    /// 1. `[writable]` The account of the person started the raffle to close the escrow
+    /// 2. `[writable]` The temporary NFT account to send to the winner
+    /// 3. `[writable]` The FT account to send FT to the exhibitor
+    /// 4. `[writable]` The temporary FT account holding the proceeds
+    /// 5. `[writable]` The winner's NFT account that will receive the NFT
+    /// 6. `[writable]` The raffle account
+    /// 7. `[]` The token program
+    /// 8. `[]` The PDA account
+    /// 9. `[writable]` The treasury's FT account that will receive the protocol fee in effect when the raffle was exhibited,
+    ///    only when the fee isn't zero
+    SettleRaffle {},
+
+    /// Cancels a raffle
+    /// Once any ticket is sold, the raffle can only be cancelled after the seed wasn't revealed in time
+    /// The NFT is returned right away, and the raffle is closed once every ticket is refunded
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the person started the raffle
+    /// 1. `[writable]` The PDA's temporary NFT account, ignored once the NFT was returned
+    /// 2. `[writable]` The exhibitor's NFT account to get the token back to
+    /// 3. `[writable]` The raffle's temporary FT account collecting the proceeds
+    /// 4. `[writable]` The raffle account
+    /// 5. `[]` The token program
+    /// 6. `[]` The PDA account
+    /// 7. `[]` The clock sysvar, only when tickets were sold
+    /// 8. `[writable]` The exhibitor's FT account, only when the proceeds account holds FT nobody is refunded
+    CancelRaffle {},
+
+    /// Starts a gradual Dutch auction selling many items of a series one purchase at a time
+    /// The items are either escrowed in a temporary token account or minted by the PDA on purchase
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the person starting the auction
+    /// 1. `[writable]` Temporary token account holding the series items, created prior to this instruction and owned by the exhibitor,
+    ///    or the series mint whose mint authority is the exhibitor when minting on purchase
+    /// 2. `[]` The exhibitor's FT account to receive the proceeds
+    /// 3. `[writable]` The GDA account, it will hold all necessary info about the auction.
+    /// 4. `[]` The rent sysvar
+    /// 5. `[]` The clock sysvar
+    /// 6. `[]` The token program
+    /// 7. `[]` The config account
+    ExhibitGda {
+        /// Price of the first item at the start of the sale
+        initial_price: u64,
+        /// Price multiplier applied for every item sold, in `FIXED_POINT_ONE` units
+        price_scale: u64,
+        /// Price multiplier applied for every elapsed second, in `FIXED_POINT_ONE` units
+        decay_rate: u64,
+        /// Number of items the PDA may mint, ignored when the items are escrowed
+        max_supply: u64,
+        /// Whether the items are minted on purchase instead of escrowed
+        mints_on_purchase: bool,
+    },
+
+    /// Buys items from a gradual Dutch auction at the current curve price
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the buyer
+    /// 1. `[writable]` The buyer's FT account to pay from
+    /// 2. `[writable]` The exhibitor's FT account to receive the payment
+    /// 3. `[writable]` The buyer's token account that will receive the items
+    /// 4. `[writable]` The PDA's temporary token account holding the items, or the series mint
+    /// 5. `[writable]` The GDA account
+    /// 6. `[]` The clock sysvar
+    /// 7. `[]` The token program
+    /// 8. `[]` The PDA account
+    /// 9. `[]` The config account
+    /// 10. `[writable]` The treasury's FT account that will receive the protocol fee in effect when the auction was exhibited,
+    ///     only when the fee isn't zero
+    PurchaseGda {
+        /// Number of items to buy
+        quantity: u64,
+        /// Maximum total price the buyer accepts to pay
+        max_total_price: u64,
+    },
+
+    /// Ends a gradual Dutch auction, returning the unsold items or the mint authority to the exhibitor
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the person started the auction
+    /// 1. `[writable]` The PDA's temporary token account holding the items, or the series mint
+    /// 2. `[writable]` The GDA account
+    /// 3. `[]` The token program
+    /// 4. `[]` The PDA account
+    /// 5. `[writable]` The exhibitor's token account to get the unsold items back to, only when the items are escrowed
+    CloseGda {},
+
+    /// Opens a periodic call auction for a collection, cleared at the end of every epoch window
+    /// The order book is kept in the call auction account, sized for the number of orders it can hold
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the person opening the call auction
+    /// 1. `[]` The collection mint every ask must be a verified member of
+    /// 2. `[]` The FT mint bids are paid in
+    /// 3. `[writable]` The call auction account, it will hold all necessary info about the call auction followed by the order book
+    /// 4. `[]` The rent sysvar
+    /// 5. `[]` The clock sysvar
+    OpenCallAuction {
+        /// Epoch window duration
+        epoch_duration: u64,
+    },
+
+    /// Posts an ask to the current epoch by escrowing an NFT of the collection
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the seller
+    /// 1. `[writable]` The seller's NFT account holding the NFT
+    /// 2. `[writable]` Temporary NFT account that should be created prior to this instruction and owned by the seller
+    /// 3. `[]` The seller's FT account to receive the proceeds
+    /// 4. `[]` The metadata account of the NFT
+    /// 5. `[writable]` The call auction account
+    /// 6. `[writable]` The order account, it will hold all necessary info about the ask.
+    /// 7. `[]` The rent sysvar
+    /// 8. `[]` The clock sysvar
+    /// 9. `[]` The token program
+    /// 10. `[]` The config account
+    PostAsk {
+        /// Lowest price the seller accepts
+        price: u64,
+    },
+
+    /// Posts a bid to the current epoch by escrowing FT
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the buyer
+    /// 1. `[writable]` The buyer's FT account to pay from and to get the unspent FT back to
+    /// 2. `[writable]` Temporary FT account that should be created prior to this instruction and owned by the buyer
+    /// 3. `[writable]` The call auction account
+    /// 4. `[writable]` The order account, it will hold all necessary info about the bid.
+    /// 5. `[]` The rent sysvar
+    /// 6. `[]` The clock sysvar
+    /// 7. `[]` The token program
+    /// 8. `[]` The config account
+    PostBid {
+        /// Highest price the buyer accepts
+        price: u64,
+    },
+
+    /// Cancels an open order while its epoch window is open, or once the window ended `SETTLEMENT_GRACE_PERIOD_SEC`
+    /// ago without being cleared. A filled order can be cancelled once it went unsettled for the same period
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the person who posted the order
+    /// 1. `[writable]` The PDA's temporary account holding the escrowed tokens
+    /// 2. `[writable]` The account to get the escrowed tokens back to
+    /// 3. `[writable]` The call auction account
+    /// 4. `[writable]` The order account
+    /// 5. `[]` The clock sysvar
+    /// 6. `[]` The token program
+    /// 7. `[]` The PDA account
+    CancelOrder {},
+
+    /// Clears the ended epoch window, matching asks with bids at a single uniform price
+    /// The fills are computed from the order book alone and settled pair by pair with `SettleFill`,
+    /// unfilled orders roll over to the next epoch
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[writable]` The call auction account
+    /// 1. `[]` The clock sysvar
+    Clear {},
+
+    /// Makes an offer of NFTs, optionally with FT added, to a barter auction and escrows every offered item
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the bidder
+    /// 1. `[]` The bidder's NFT account that will receive the exhibited NFT should the offer be accepted
+    /// 2. `[]` The escrow account of the barter auction
+    /// 3. `[writable]` The offer account, it will hold all necessary info about the offer.
+    /// 4. `[]` The rent sysvar
+    /// 5. `[]` The clock sysvar
+    /// 6. `[]` The token program
+    /// 7. `[]` The config account
+    /// 8. Only when FT is added to the offer:
+    ///    0. `[writable]` The bidder's FT account to pay from and to get the FT back to
+    ///    1. `[writable]` Temporary FT account that should be created prior to this instruction and owned by the bidder
+    /// 9. For each offered NFT:
+    ///    0. `[writable]` The bidder's NFT account holding the NFT and to get it back to
+    ///    1. `[writable]` Temporary NFT account that should be created prior to this instruction and owned by the bidder
+    MakeOffer {
+        /// Amount of FT added to the offer
+        ft_amount: u64,
+        /// Number of NFTs in the offer
+        nft_count: u8,
+    },
+
+    /// Accepts an offer once the barter auction has ended, swapping the exhibited NFT for the offered items
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the person started the auction
+    /// 1. `[writable]` The PDA's temporary NFT account
+    /// 2. `[writable]` The bidder's NFT account that will receive the exhibited NFT
+    /// 3. `[writable]` The escrow account holding the escrow info
+    /// 4. `[writable]` The accepted offer account
+    /// 5. `[writable]` The bidder's account to return the rent to
+    /// 6. `[]` The clock sysvar
+    /// 7. `[]` The token program
+    /// 8. `[]` The PDA account
+    /// 9. Only when FT is added to the offer:
+    ///    0. `[writable]` The PDA's temporary FT account
+    ///    1. `[writable]` The exhibitor's FT account to receive the FT
+    ///    2. `[writable]` The treasury's FT account that will receive the protocol fee in effect when the auction was exhibited,
+    ///       the treasury of the house for auctions exhibited in a house, only when the fee isn't zero
+    /// 10. For each offered NFT:
+    ///    0. `[writable]` The PDA's temporary NFT account
+    ///    1. `[writable]` The exhibitor's NFT account that will receive the NFT
+    /// 11. `[writable]` The rent payer of the auction, only when it isn't the exhibitor
+    AcceptOffer {},
+
+    /// Withdraws an offer that was not accepted and returns the escrowed items
+    /// Offers are locked from the end of the auction until the exhibitor picks one or the selection period runs out
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the bidder
+    /// 1. `[]` The escrow account of the barter auction, closed once the auction is settled or cancelled
+    /// 2. `[writable]` The offer account
+    /// 3. `[]` The clock sysvar
+    /// 4. `[]` The token program
+    /// 5. `[]` The PDA account
+    /// 6. Only when FT is added to the offer:
+    ///    0. `[writable]` The PDA's temporary FT account
+    ///    1. `[writable]` The bidder's FT account to get the FT back to
+    /// 7. For each offered NFT:
+    ///    0. `[writable]` The PDA's temporary NFT account
+    ///    1. `[writable]` The bidder's NFT account to get the NFT back to
+    WithdrawOffer {},
+
+    /// Creates the program config, the signer becomes its admin and price authority
+    /// Only the upgrade authority of the program can create the config
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the admin, paying for the config account
+    /// 1. `[]` The treasury's account owning the FT accounts the protocol fee is paid to
+    /// 2. `[writable]` The config account, the PDA derived from `b"config"`
+    /// 3. `[]` The rent sysvar
+    /// 4. `[]` The system program
+    /// 5. `[]` The program data account of the program, whose upgrade authority must be the admin
+    InitConfig {
+        /// Protocol fee, in basis points, at most `MAX_FEE_BPS`
+        fee_bps: u16,
+        /// Referral share, in basis points
+        referral_bps: u16,
+        /// Penalty for cancelling an auction after bids, in basis points
+        cancel_penalty_bps: u16,
+        /// Penalty for retracting a bid, in basis points
+        retraction_penalty_bps: u16,
+    },
+
+    /// Updates the protocol fee, the referral share, the cancellation and retraction penalties and the treasury
+    /// the fee is paid to
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the admin
+    /// 1. `[writable]` The config account
+    /// 2. `[]` The treasury's account owning the FT accounts the protocol fee is paid to
+    UpdateFee {
+        /// Protocol fee, in basis points, at most `MAX_FEE_BPS`
+        fee_bps: u16,
+        /// Referral share, in basis points
+        referral_bps: u16,
+        /// Penalty for cancelling an auction after bids, in basis points
+        cancel_penalty_bps: u16,
+        /// Penalty for retracting a bid, in basis points
+        retraction_penalty_bps: u16,
+    },
+
+    /// Hands the config over to a new admin
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the current admin
+    /// 1. `[signer]` The account of the new admin
+    /// 2. `[writable]` The config account
+    SetAdmin {},
+
+    /// Bid on a lamport auction, the bid is transferred to the auction's vault and the previous highest bid refunded from it
+    /// Bids placed within the anti-snipe window of an auction exhibited in a house extend the auction
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the person bidding on the auction
+    /// 1. `[writable]` The current highest bidder's account to get the previous highest bid back to
+    /// 2. `[writable]` The escrow account, it will hold all necessary info about the auction.
+    /// 3. `[writable]` The vault account
+    /// 4. `[]` The clock sysvar
+    /// 5. `[]` The system program
+    /// 6. `[]` The config account
+    /// 7. `[]` The denylist page of the bidder, the PDA derived from `b"denylist"` and the first byte of their wallet
+    /// 8. `[]` The bidder's bond account, only for bonded auctions
+    /// 9. Only for token-gated auctions:
+    ///    0. `[]` The bidder's token account holding the gate
+    ///    1. `[]` The metadata account of the held NFT, only when the gate is a collection
+    BidLamports {
+        /// Bidding price, in lamports
+        price: u64,
+        /// Wallet pubkey of the referrer paid a share of the sale should the bid win, can't be the bidder's own wallet
+        referrer: Option<Pubkey>,
+        /// Merkle proof the bidder is on the bidder allowlist of the auction, empty when the auction has none
+        proof: Vec<[u8; 32]>,
+    },
+
+    /// Sets the price of a mint in reference units, creating its price feed on first use
+    /// Only mints with a price feed can be bid with in reference priced auctions
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the price authority, paying for a new price feed account
+    /// 1. `[]` The mint the price is quoted for
+    /// 2. `[writable]` The price feed account, the PDA derived from `b"price_feed"` and the mint
+    /// 3. `[]` The config account
+    /// 4. `[]` The rent sysvar
+    /// 5. `[]` The clock sysvar
+    /// 6. `[]` The system program
+    UpdatePrice {
+        /// Reference units per base unit of the mint, in `FIXED_POINT_ONE` units, can't be zero
+        price: u64,
+    },
+
+    /// Hands the price feeds over to a new price authority
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the admin
+    /// 1. `[]` The account of the new price authority
+    /// 2. `[writable]` The config account
+    SetPriceAuthority {},
+
+    /// Posts the bond a bonded auction requires before the bidder's first bid
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the bidder, paying the bond and the bond account's rent
+    /// 1. `[]` The escrow account of the auction
+    /// 2. `[writable]` The bond account, the PDA derived from `b"bond"`, the escrow account and the bidder
+    /// 3. `[]` The rent sysvar
+    /// 4. `[]` The clock sysvar
+    /// 5. `[]` The system program
+    /// 6. `[]` The config account
+    PostBond {},
+
+    /// Refunds a bond once the auction has ended
+    /// The winner's bond is only refunded once the auction is settled
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the bidder
+    /// 1. `[]` The escrow account of the auction, closed once the auction is settled or cancelled
+    /// 2. `[writable]` The bond account
+    /// 3. `[]` The clock sysvar
+    RefundBond {},
+
+    /// Takes the bond of a winner who didn't settle within the grace period, returning the NFT to the exhibitor
+    /// and the winning bid to the winner
+    /// The bond of a runner-up offered a second chance can't be taken
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the person started the auction
+    /// 1. `[writable]` The PDA's temporary NFT account
+    /// 2. `[writable]` The exhibitor's NFT account to get the NFT back to
+    /// 3. `[writable]` The escrow account holding the escrow info
+    /// 4. `[writable]` The winner's bond account
+    /// 5. `[writable]` The temporary account holding the winning bid, or the vault of the auction
+    /// 6. `[writable]` The winner's account to get the winning bid back to
+    /// 7. `[writable]` The winner's account to return the rent to
+    /// 8. `[]` The clock sysvar
+    /// 9. `[]` The token program
+    /// 10. `[]` The PDA account
+    /// 11. Only when the auction has a runner-up:
+    ///    0. `[writable]` The runner-up's account
+    ///    1. `[writable]` The runner-up's temporary account, or the vault of the auction
+    ///    2. `[writable]` The runner-up's account to get the bid back to
+    ///    3. `[writable]` The rent payer of the runner-up's temporary account, only when it isn't the runner-up
+    /// 12. `[writable]` The rent payer of the winner's temporary account, only when it isn't the winner, or for
+    ///     bids held in the vault, the exhibitor's FT account to get FT sent to the vault outside of bids to, only
+    ///     when the vault holds any
+    /// 13. `[writable]` The rent payer of the auction, only when it isn't the exhibitor
+    ForfeitBond {},
+
+    /// Cancels an active English auction after bids, refunding the highest bid and paying the highest bidder
+    /// the cancellation penalty of the config in effect when the auction was exhibited out of the exhibitor's funds
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the person started the auction
+    /// 1. `[writable]` The PDA's temporary NFT account
+    /// 2. `[writable]` The exhibitor's NFT account to get the NFT back to
+    /// 3. `[writable]` The escrow account holding the escrow info
+    /// 4. `[writable]` The temporary account holding the highest bid, or the vault of the auction
+    /// 5. `[writable]` The highest bidder's account to get the highest bid and the penalty to
+    /// 6. `[writable]` The highest bidder's account to return the rent to
+    /// 7. `[writable]` The exhibitor's FT account paying the penalty, or the system program for a lamport
+    ///    auction
+    /// 8. `[]` The clock sysvar
+    /// 9. `[]` The token program
+    /// 10. `[]` The PDA account
+    /// 11. Only when the auction has a runner-up:
+    ///    0. `[writable]` The runner-up's account
+    ///    1. `[writable]` The runner-up's temporary account, or the vault of the auction
+    ///    2. `[writable]` The runner-up's account to get the bid back to
+    ///    3. `[writable]` The rent payer of the runner-up's temporary account, only when it isn't the runner-up
+    /// 12. `[writable]` The rent payer of the highest bidder's temporary account, only when it isn't the highest bidder,
+    ///     or for bids held in the vault, the exhibitor's FT account to get FT sent to the vault outside of bids to,
+    ///     only when the vault holds any
+    /// 13. `[writable]` The rent payer of the auction, only when it isn't the exhibitor
+    CancelWithPenalty {},
+
+    /// Offers the NFT of an English auction paid in FT to the runner-up at their bid, once the winner didn't settle
+    /// within the grace period or was disqualified by the admin
+    /// The winning bid is refunded, the winner's bond taken, and the runner-up becomes the highest bidder who can close
+    /// the auction until the offer expires
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the person started the auction
+    /// 1. `[writable]` The escrow account holding the escrow info
+    /// 2. `[writable]` The temporary account holding the winning bid, or the vault of the auction
+    /// 3. `[writable]` The winner's account to get the winning bid back to
+    /// 4. `[writable]` The winner's account to return the rent to
+    /// 5. `[]` The clock sysvar
+    /// 6. `[]` The token program
+    /// 7. `[]` The PDA account
+    /// 8. Only to disqualify the winner within the grace period:
+    ///    0. `[signer]` The account of the admin
+    ///    1. `[]` The config account
+    /// 9. `[writable]` The winner's bond account, only for bonded auctions
+    /// 10. `[writable]` The rent payer of the winner's temporary account, only when it isn't the winner
+    OfferSecondChance {},
+
+    /// Returns the NFT to the exhibitor and the bid to the runner-up once a second chance offer expired unaccepted
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the person started the auction
+    /// 1. `[writable]` The PDA's temporary NFT account
+    /// 2. `[writable]` The exhibitor's NFT account to get the NFT back to
+    /// 3. `[writable]` The escrow account holding the escrow info
+    /// 4. `[writable]` The temporary account holding the runner-up's bid, or the vault of the auction
+    /// 5. `[writable]` The runner-up's account to get the bid back to
+    /// 6. `[writable]` The runner-up's account to return the rent to
+    /// 7. `[]` The clock sysvar
+    /// 8. `[]` The token program
+    /// 9. `[]` The PDA account
+    /// 10. `[writable]` The rent payer of the runner-up's temporary account, only when it isn't the runner-up, or for
+    ///     bids held in the vault, the exhibitor's FT account to get FT sent to the vault outside of bids to, only
+    ///     when the vault holds any
+    /// 11. `[writable]` The rent payer of the auction, only when it isn't the exhibitor
+    ExpireSecondChance {},
+
+    /// Retracts the highest bid of an English auction paid in FT before its final hour, restoring the runner-up's bid
+    /// or the initial price, and paying the exhibitor the retraction penalty of the config out of the retracted bid
+    /// Bids can't be retracted while the penalty is zero
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the highest bidder
+    /// 1. `[writable]` The highest bidder's temporary account, or the vault of the auction
+    /// 2. `[writable]` The highest bidder's account to get the bid back to
+    /// 3. `[writable]` The escrow account holding the escrow info
+    /// 4. `[writable]` The exhibitor's FT account to receive the penalty, any exhibitor's FT account of the bid's mint
+    ///    for reference priced auctions
+    /// 5. `[]` The clock sysvar
+    /// 6. `[]` The config account
+    /// 7. `[]` The token program
+    /// 8. `[]` The PDA account
+    /// 9. `[writable]` The rent payer of the highest bidder's temporary account, only when it isn't the highest bidder
+    RetractBid {},
+
+    /// Creates an auction house whose policy applies to the auctions exhibited in it
+    /// Only the admin of the config can create houses, since their fee replaces the protocol fee
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the admin
+    /// 1. `[]` The config account
+    /// 2. `[]` The account of the house authority allowed to update the house
+    /// 3. `[]` The treasury's account owning the FT accounts the fee of the house is paid to
+    /// 4. `[writable]` The house account, created prior to this instruction and owned by the program, it will hold the policy of the house
+    /// 5. `[]` The rent sysvar
+    /// 6. `[]` For each payment mint, the mint auctions of the house may be paid in, the native mint for lamport auctions
+    CreateHouse {
+        /// Fee of the house, in basis points, at most `MAX_FEE_BPS`
+        fee_bps: u16,
+        /// Minimum raise over the highest bid of English auctions, in basis points
+        min_increment_bps: u16,
+        /// Shortest auction duration, in seconds
+        min_duration_sec: u64,
+        /// Longest auction duration, in seconds, zero when unbounded
+        max_duration_sec: u64,
+        /// Bids placed this many seconds before the end extend the auction, zero to disable
+        anti_snipe_window_sec: u32,
+        /// Seconds left in the auction after a bid placed within the anti-snipe window
+        anti_snipe_extension_sec: u32,
+        /// Number of payment mints, zero when auctions may be paid in any mint
+        mint_count: u8,
+        /// Merkle root of the wallets allowed to exhibit in the house, zeroed when anyone may exhibit
+        exhibitor_allowlist_root: [u8; 32],
+    },
+
+    /// Replaces the policy of an auction house
+    /// Auctions already exhibited keep the fee, increment and anti-snipe policy they inherited
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the house authority
+    /// 1. `[]` The config account
+    /// 2. `[writable]` The house account
+    /// 3. `[]` The treasury's account owning the FT accounts the fee of the house is paid to
+    /// 4. `[]` For each payment mint, the mint auctions of the house may be paid in, the native mint for lamport auctions
+    UpdateHouse {
+        /// Fee of the house, in basis points, at most `MAX_FEE_BPS`
+        fee_bps: u16,
+        /// Minimum raise over the highest bid of English auctions, in basis points
+        min_increment_bps: u16,
+        /// Shortest auction duration, in seconds
+        min_duration_sec: u64,
+        /// Longest auction duration, in seconds, zero when unbounded
+        max_duration_sec: u64,
+        /// Bids placed this many seconds before the end extend the auction, zero to disable
+        anti_snipe_window_sec: u32,
+        /// Seconds left in the auction after a bid placed within the anti-snipe window
+        anti_snipe_extension_sec: u32,
+        /// Number of payment mints, zero when auctions may be paid in any mint
+        mint_count: u8,
+        /// Merkle root of the wallets allowed to exhibit in the house, zeroed when anyone may exhibit
+        exhibitor_allowlist_root: [u8; 32],
+    },
+
+    /// Pauses or resumes the program
+    /// While paused, no auction, raffle or GDA can be exhibited or updated, and no bid, bond, ticket, purchase, order or
+    /// offer can be placed, but cancellations, refunds, withdrawals and settlements, accepting an offer included, keep
+    /// working so escrowed funds are never trapped
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the admin
+    /// 1. `[writable]` The config account
+    SetPaused {
+        /// Whether the program is paused
+        paused: bool,
+    },
+
+    /// Adds a wallet to the denylist, creating its denylist page on first use and growing it by a wallet every time
+    /// Denied wallets can't exhibit or bid
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the admin, paying for a new denylist page and the rent of its growth
+    /// 1. `[]` The config account
+    /// 2. `[writable]` The denylist page of the wallet, the PDA derived from `b"denylist"` and the first byte of the wallet
+    /// 3. `[]` The rent sysvar
+    /// 4. `[]` The system program
+    AddToDenylist {
+        /// Wallet pubkey to deny
+        wallet: Pubkey,
+    },
+
+    /// Removes a wallet from the denylist
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the admin
+    /// 1. `[]` The config account
+    /// 2. `[writable]` The denylist page of the wallet
+    RemoveFromDenylist {
+        /// Wallet pubkey to allow again
+        wallet: Pubkey,
+    },
+
+    /// Sets the shortest and longest duration of the auctions
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the admin
+    /// 1. `[writable]` The config account
+    SetDurationBounds {
+        /// Shortest auction duration, in seconds
+        min_duration_sec: u64,
+        /// Longest auction duration, in seconds, zero when unbounded
+        max_duration_sec: u64,
+    },
+
+    /// Changes the starting price, the end time and the proceeds account of an English or reverse auction nobody has
+    /// bid on yet
+    /// The budget of a reverse auction is escrowed and can't be changed
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the person started the auction
+    /// 1. `[writable]` The escrow account holding the escrow info
+    /// 2. `[]` The clock sysvar
+    /// 3. `[]` The config account
+    /// 4. `[]` The exhibitor's current FT account to receive the proceeds, or the account receiving the lamports
+    ///    of a lamport auction
+    /// 5. `[]` The exhibitor's new FT account to receive the proceeds, of the same mint as the current one,
+    ///    or the new account receiving the lamports of a lamport auction
+    /// 6. `[]` The auction house, only for auctions exhibited in a house
+    UpdateAuction {
+        /// New initial price
+        initial_price: u64,
+        /// New auction duration from now, within the bounds of the config and the house
+        seconds: u64,
+    },
+
+    /// Restarts an English or reverse auction that ended without bids in place, keeping the escrowed NFT or budget
+    /// The budget of a reverse auction is escrowed and can't be changed
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the person started the auction
+    /// 1. `[writable]` The escrow account holding the escrow info
+    /// 2. `[]` The clock sysvar
+    /// 3. `[]` The config account
+    /// 4. `[]` The denylist page of the exhibitor, the PDA derived from `b"denylist"` and the first byte of their wallet
+    /// 5. `[]` The auction house, only for auctions exhibited in a house
+    Relist {
+        /// New initial price
+        initial_price: u64,
+        /// New auction duration from now, within the bounds of the config and the house
+        seconds: u64,
+    },
+
+    /// Relists an English auction that ended without bids according to its auto-relist policy
+    /// Anyone can crank the relisting, which restarts the auction for its duration at a dropped price
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[writable]` The escrow account holding the escrow info
+    /// 1. `[]` The clock sysvar
+    /// 2. `[]` The config account
+    /// 3. `[]` The auction house, only for auctions exhibited in a house
+    ///
+    /// The auction must still last within the bounds of the config and the house.
+    CrankRelist {},
+
+    /// Refunds the tickets of a buyer once the exhibitor missed the deadline to reveal the seed
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the ticket buyer
+    /// 1. `[writable]` The buyer's FT account to refund to
+    /// 2. `[writable]` The raffle's temporary FT account collecting the proceeds
+    /// 3. `[writable]` The raffle account
+    /// 4. `[]` The clock sysvar
+    /// 5. `[]` The token program
+    /// 6. `[]` The PDA account
+    RefundTickets {},
+
+    /// Settles a bid filled by `Clear` with the ask it was matched with, the buyer naming the NFT account
+    /// Sends the NFT to the buyer, the clearing price to the seller and the unspent FT back to the buyer
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account of the buyer
+    /// 1. `[writable]` The buyer's NFT account of the mint of the ask that will receive the NFT
+    /// 2. `[writable]` The bid's order account
+    /// 3. `[writable]` The PDA's temporary FT account of the bid
+    /// 4. `[writable]` The buyer's FT account to get the unspent FT back to
+    /// 5. `[writable]` The ask's order account
+    /// 6. `[writable]` The PDA's temporary NFT account of the ask
+    /// 7. `[writable]` The seller's FT account to receive the proceeds
+    /// 8. `[writable]` The seller's account to return the rent to
+    /// 9. `[writable]` The call auction account
+    /// 10. `[]` The token program
+    /// 11. `[]` The PDA account
+    /// 12. `[]` The config account
+    /// 13. `[writable]` The treasury's FT account that will receive the protocol fee, only when the fee isn't zero
+    SettleFill {},
+
+    /// Migrates the escrow account of an auction exhibited before the auction kinds were introduced to the current layout
+    /// The auction stays an English auction paid in FT whose bids are escrowed in temporary accounts of their own,
+    /// and the rent of the grown escrow account is returned to the exhibitor when the auction is closed or cancelled
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer, writable]` The account paying for the rent of the grown escrow account
+    /// 1. `[writable]` The escrow account holding the escrow info
+    /// 2. `[]` The rent sysvar
+    /// 3. `[]` The system program
+    MigrateAuction {},
+
+    /// Refunds the runner-up's bid of an English auction the winner didn't settle and the exhibitor didn't offer
+    /// to the runner-up within the grace period and the second chance window after the end of the auction
+    /// Anyone can refund the runner-up
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[writable]` The escrow account holding the escrow info
+    /// 1. `[]` The clock sysvar
+    /// 2. `[]` The token program
+    /// 3. `[]` The PDA account
+    /// 4. `[writable]` The runner-up's account
+    /// 5. `[writable]` The runner-up's temporary account, or the vault of the auction
+    /// 6. `[writable]` The runner-up's account to get the bid back to
+    /// 7. `[writable]` The rent payer of the runner-up's temporary account, only when it isn't the runner-up
+    RefundRunnerUp {},
+
+    /// Records the slot whose hash the raffle is drawn from, once the ticket sale has ended
+    /// The slot can only be recorded once, and `Draw` must follow while its hash is still in the slot hashes sysvar
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The account of the person that started the raffle
+    /// 1. `[writable]` The raffle account
+    /// 2. `[]` The clock sysvar
+    RecordDrawSlot {},
 }
 
 impl AuctionInstruction {
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
         let (instruction_type, rest) = input.split_first().ok_or(InvalidInstruction)?;
         Ok(match instruction_type {
-            0 => Self::Exhibit {
-                initial_price: Self::unpack64(rest, 0)?,
+            0 => {
+                // Older clients stop early, the fields they don't send default to an English auction without options.
+                let payee_count = Self::unpack_trailing(rest, 27, Self::unpack8)? as usize;
+                let rent_payer = Self::unpack_trailing(
+                    rest,
+                    29 + payee_count * 2,
+                    Self::unpack_optional_pubkey,
+                )?;
+                let house_start = 30 + payee_count * 2 + rent_payer.map_or(0, |_| 32);
+                let house = Self::unpack_trailing(rest, house_start, Self::unpack_optional_pubkey)?;
+                let gate_start = house_start + 1 + house.map_or(0, |_| 32);
+                let gate = Self::unpack_trailing(rest, gate_start, Self::unpack_optional_pubkey)?;
+                let root_start = gate_start + 1 + gate.map_or(0, |_| 40);
+                let bidder_allowlist_root =
+                    Self::unpack_trailing(rest, root_start, Self::unpack_optional_bytes32)?;
+                let proof_start = root_start + 1 + bidder_allowlist_root.map_or(0, |_| 32);
+                let proof = Self::unpack_trailing(rest, proof_start, Self::unpack_proof)?;
+                let auto_relist_start = proof_start + 1 + proof.len() * 32;
+                Self::Exhibit {
+                    initial_price: Self::unpack64(rest, 0)?,
+                    seconds: Self::unpack64(rest, 8)?,
+                    kind: Self::unpack_trailing(rest, 16, |input, start| {
+                        AuctionKind::from_u8(Self::unpack8(input, start)?)
+                            .ok_or_else(|| InvalidInstruction.into())
+                    })?,
+                    pays_in_lamports: Self::unpack_trailing(rest, 17, Self::unpack_bool)?,
+                    prices_in_reference: Self::unpack_trailing(rest, 18, Self::unpack_bool)?,
+                    bond_amount: Self::unpack_trailing(rest, 19, Self::unpack64)?,
+                    payee_shares_bps: (0..payee_count)
+                        .map(|index| Self::unpack16(rest, 29 + index * 2))
+                        .collect::<Result<_, _>>()?,
+                    dust_payee_index: Self::unpack_trailing(rest, 28, Self::unpack8)?,
+                    rent_payer,
+                    house,
+                    gate,
+                    gate_amount: match gate {
+                        Some(_) => Self::unpack64(rest, gate_start + 33)?,
+                        None => 0,
+                    },
+                    bidder_allowlist_root,
+                    auto_relist_cycles: Self::unpack_trailing(
+                        rest,
+                        auto_relist_start,
+                        Self::unpack8,
+                    )?,
+                    auto_relist_price_drop_bps: Self::unpack_trailing(
+                        rest,
+                        auto_relist_start + 1,
+                        Self::unpack16,
+                    )?,
+                    proof,
+                }
+            }
+            1 => {
+                // Older clients only send the price, the fields they don't send default to none.
+                let referrer = Self::unpack_trailing(rest, 8, Self::unpack_optional_pubkey)?;
+                let rent_payer_start = 9 + referrer.map_or(0, |_| 32);
+                let rent_payer =
+                    Self::unpack_trailing(rest, rent_payer_start, Self::unpack_optional_pubkey)?;
+                Self::Bid {
+                    price: Self::unpack64(rest, 0)?,
+                    referrer,
+                    rent_payer,
+                    proof: Self::unpack_trailing(
+                        rest,
+                        rent_payer_start + 1 + rent_payer.map_or(0, |_| 32),
+                        Self::unpack_proof,
+                    )?,
+                }
+            }
+            2 => Self::Cancel {},
+            3 => Self::Close {},
+            4 => Self::ExhibitRaffle {
+                ticket_price: Self::unpack64(rest, 0)?,
                 seconds: Self::unpack64(rest, 8)?,
+                seed_hash: Self::unpack_bytes32(rest, 16)?,
             },
-            1 => Self::Bid {
+            5 => Self::BuyTickets {
+                count: Self::unpack64(rest, 0)?,
+            },
+            6 => Self::Draw {
+                seed: Self::unpack_bytes32(rest, 0)?,
+            },
+            7 => Self::SettleRaffle {},
+            8 => Self::CancelRaffle {},
+            9 => Self::ExhibitGda {
+                initial_price: Self::unpack64(rest, 0)?,
+                price_scale: Self::unpack64(rest, 8)?,
+                decay_rate: Self::unpack64(rest, 16)?,
+                max_supply: Self::unpack64(rest, 24)?,
+                mints_on_purchase: Self::unpack_bool(rest, 32)?,
+            },
+            10 => Self::PurchaseGda {
+                quantity: Self::unpack64(rest, 0)?,
+                max_total_price: Self::unpack64(rest, 8)?,
+            },
+            11 => Self::CloseGda {},
+            12 => Self::OpenCallAuction {
+                epoch_duration: Self::unpack64(rest, 0)?,
+            },
+            13 => Self::PostAsk {
                 price: Self::unpack64(rest, 0)?,
             },
-            2 => Self::Cancel {},
-            3 => Self::Close {},
+            14 => Self::PostBid {
+                price: Self::unpack64(rest, 0)?,
+            },
+            15 => Self::CancelOrder {},
+            16 => Self::Clear {},
+            17 => Self::MakeOffer {
+                ft_amount: Self::unpack64(rest, 0)?,
+                nft_count: *rest.get(8).ok_or(InvalidInstruction)?,
+            },
+            18 => Self::AcceptOffer {},
+            19 => Self::WithdrawOffer {},
+            20 => Self::InitConfig {
+                fee_bps: Self::unpack16(rest, 0)?,
+                referral_bps: Self::unpack16(rest, 2)?,
+                cancel_penalty_bps: Self::unpack16(rest, 4)?,
+                retraction_penalty_bps: Self::unpack16(rest, 6)?,
+            },
+            21 => Self::UpdateFee {
+                fee_bps: Self::unpack16(rest, 0)?,
+                referral_bps: Self::unpack16(rest, 2)?,
+                cancel_penalty_bps: Self::unpack16(rest, 4)?,
+                retraction_penalty_bps: Self::unpack16(rest, 6)?,
+            },
+            22 => Self::SetAdmin {},
+            23 => {
+                let referrer = Self::unpack_optional_pubkey(rest, 8)?;
+                Self::BidLamports {
+                    price: Self::unpack64(rest, 0)?,
+                    referrer,
+                    proof: Self::unpack_proof(rest, 9 + referrer.map_or(0, |_| 32))?,
+                }
+            }
+            24 => Self::UpdatePrice {
+                price: Self::unpack64(rest, 0)?,
+            },
+            25 => Self::SetPriceAuthority {},
+            26 => Self::PostBond {},
+            27 => Self::RefundBond {},
+            28 => Self::ForfeitBond {},
+            29 => Self::CancelWithPenalty {},
+            30 => Self::OfferSecondChance {},
+            31 => Self::ExpireSecondChance {},
+            32 => Self::RetractBid {},
+            33 => Self::CreateHouse {
+                fee_bps: Self::unpack16(rest, 0)?,
+                min_increment_bps: Self::unpack16(rest, 2)?,
+                min_duration_sec: Self::unpack64(rest, 4)?,
+                max_duration_sec: Self::unpack64(rest, 12)?,
+                anti_snipe_window_sec: Self::unpack32(rest, 20)?,
+                anti_snipe_extension_sec: Self::unpack32(rest, 24)?,
+                mint_count: *rest.get(28).ok_or(InvalidInstruction)?,
+                exhibitor_allowlist_root: Self::unpack_bytes32(rest, 29)?,
+            },
+            34 => Self::UpdateHouse {
+                fee_bps: Self::unpack16(rest, 0)?,
+                min_increment_bps: Self::unpack16(rest, 2)?,
+                min_duration_sec: Self::unpack64(rest, 4)?,
+                max_duration_sec: Self::unpack64(rest, 12)?,
+                anti_snipe_window_sec: Self::unpack32(rest, 20)?,
+                anti_snipe_extension_sec: Self::unpack32(rest, 24)?,
+                mint_count: *rest.get(28).ok_or(InvalidInstruction)?,
+                exhibitor_allowlist_root: Self::unpack_bytes32(rest, 29)?,
+            },
+            35 => Self::SetPaused {
+                paused: Self::unpack_bool(rest, 0)?,
+            },
+            36 => Self::AddToDenylist {
+                wallet: Pubkey::new_from_array(Self::unpack_bytes32(rest, 0)?),
+            },
+            37 => Self::RemoveFromDenylist {
+                wallet: Pubkey::new_from_array(Self::unpack_bytes32(rest, 0)?),
+            },
+            38 => Self::SetDurationBounds {
+                min_duration_sec: Self::unpack64(rest, 0)?,
+                max_duration_sec: Self::unpack64(rest, 8)?,
+            },
+            39 => Self::UpdateAuction {
+                initial_price: Self::unpack64(rest, 0)?,
+                seconds: Self::unpack64(rest, 8)?,
+            },
+            40 => Self::Relist {
+                initial_price: Self::unpack64(rest, 0)?,
+                seconds: Self::unpack64(rest, 8)?,
+            },
+            41 => Self::CrankRelist {},
+            42 => Self::RefundTickets {},
+            43 => Self::SettleFill {},
+            44 => Self::MigrateAuction {},
+            45 => Self::RefundRunnerUp {},
+            46 => Self::RecordDrawSlot {},
             _ => return Err(InvalidInstruction.into()),
         })
     }
 
+    /// Unpacks a trailing field with `unpack`, defaulting it when the input ends before it.
+    fn unpack_trailing<T: Default>(
+        input: &[u8],
+        start: usize,
+        unpack: impl Fn(&[u8], usize) -> Result<T, ProgramError>,
+    ) -> Result<T, ProgramError> {
+        if input.len() <= start {
+            return Ok(T::default());
+        }
+        unpack(input, start)
+    }
+
+    fn unpack8(input: &[u8], start: usize) -> Result<u8, ProgramError> {
+        Ok(*input.get(start).ok_or(InvalidInstruction)?)
+    }
+
+    fn unpack16(input: &[u8], start: usize) -> Result<u16, ProgramError> {
+        let v = input
+            .get(start..start + 2)
+            .and_then(|slice| slice.try_into().ok())
+            .map(u16::from_le_bytes)
+            .ok_or(InvalidInstruction)?;
+        Ok(v)
+    }
+
+    fn unpack32(input: &[u8], start: usize) -> Result<u32, ProgramError> {
+        let v = input
+            .get(start..start + 4)
+            .and_then(|slice| slice.try_into().ok())
+            .map(u32::from_le_bytes)
+            .ok_or(InvalidInstruction)?;
+        Ok(v)
+    }
+
     fn unpack64(input: &[u8], start: usize) -> Result<u64, ProgramError> {
         let v = input
             .get(start..start + 8)
@@ -98,4 +1262,49 @@ impl AuctionInstruction {
             .ok_or(InvalidInstruction)?;
         Ok(v)
     }
+
+    fn unpack_optional_pubkey(input: &[u8], start: usize) -> Result<Option<Pubkey>, ProgramError> {
+        if Self::unpack_bool(input, start)? {
+            Ok(Some(Pubkey::new_from_array(Self::unpack_bytes32(
+                input,
+                start + 1,
+            )?)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn unpack_optional_bytes32(
+        input: &[u8],
+        start: usize,
+    ) -> Result<Option<[u8; 32]>, ProgramError> {
+        if Self::unpack_bool(input, start)? {
+            Ok(Some(Self::unpack_bytes32(input, start + 1)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Reads a Merkle proof, its node count followed by the nodes.
+    fn unpack_proof(input: &[u8], start: usize) -> Result<Vec<[u8; 32]>, ProgramError> {
+        (0..*input.get(start).ok_or(InvalidInstruction)? as usize)
+            .map(|index| Self::unpack_bytes32(input, start + 1 + index * 32))
+            .collect()
+    }
+
+    fn unpack_bool(input: &[u8], start: usize) -> Result<bool, ProgramError> {
+        match input.get(start) {
+            Some(0) => Ok(false),
+            Some(1) => Ok(true),
+            _ => Err(InvalidInstruction.into()),
+        }
+    }
+
+    fn unpack_bytes32(input: &[u8], start: usize) -> Result<[u8; 32], ProgramError> {
+        let v = input
+            .get(start..start + 32)
+            .and_then(|slice| slice.try_into().ok())
+            .ok_or(InvalidInstruction)?;
+        Ok(v)
+    }
 }
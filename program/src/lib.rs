@@ -1,5 +1,6 @@
 pub mod error;
 pub mod instruction;
+pub mod metadata;
 pub mod processor;
 pub mod state;
 
@@ -0,0 +1,317 @@
+use solana_program::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey, pubkey::Pubkey,
+};
+
+use crate::error::AuctionError;
+
+/// Metaplex Token Metadata program
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey =
+    pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+/// `Key::MetadataV1` discriminator of the Token Metadata program
+const METADATA_V1_KEY: u8 = 4;
+
+/// Size of a serialized `Creator`: address, verified flag and share
+const CREATOR_LEN: usize = 34;
+
+/// The parts of a Token Metadata account this program relies on
+pub struct Metadata {
+    /// Mint the metadata describes
+    pub mint: Pubkey,
+    /// Royalty owed to the creators on every sale, in basis points
+    pub seller_fee_basis_points: u16,
+    /// Creators sharing the royalty
+    pub creators: Vec<Creator>,
+    /// Collection the NFT claims to belong to
+    pub collection: Option<Collection>,
+}
+
+pub struct Creator {
+    pub address: Pubkey,
+    /// Whether the creator signed the metadata
+    pub verified: bool,
+    /// Percentage of the royalty paid to the creator
+    pub share: u8,
+}
+
+pub struct Collection {
+    /// Whether the collection authority signed off the membership
+    pub verified: bool,
+    /// Mint of the collection NFT
+    pub key: Pubkey,
+}
+
+impl Metadata {
+    /// Reads the metadata account of `mint`, checking it is the canonical metadata PDA.
+    pub fn from_account_info(
+        metadata_account: &AccountInfo,
+        mint: &Pubkey,
+    ) -> Result<Metadata, ProgramError> {
+        if *metadata_account.owner != TOKEN_METADATA_PROGRAM_ID {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if Metadata::find_address(mint) != *metadata_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Metadata::unpack(&metadata_account.try_borrow_data()?)
+    }
+
+    /// Reads the metadata account of `mint` like `from_account_info`, but treats a canonical metadata PDA
+    /// that was never created by the Token Metadata program as metadata without royalty or creators.
+    pub fn from_account_info_or_default(
+        metadata_account: &AccountInfo,
+        mint: &Pubkey,
+    ) -> Result<Metadata, ProgramError> {
+        if *metadata_account.owner == TOKEN_METADATA_PROGRAM_ID {
+            return Metadata::from_account_info(metadata_account, mint);
+        }
+        if Metadata::find_address(mint) != *metadata_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Metadata {
+            mint: *mint,
+            seller_fee_basis_points: 0,
+            creators: Vec::new(),
+            collection: None,
+        })
+    }
+
+    /// Address of the canonical metadata PDA of `mint`.
+    fn find_address(mint: &Pubkey) -> Pubkey {
+        let (metadata_pubkey, _bump_seed) = Pubkey::find_program_address(
+            &[
+                b"metadata",
+                TOKEN_METADATA_PROGRAM_ID.as_ref(),
+                mint.as_ref(),
+            ],
+            &TOKEN_METADATA_PROGRAM_ID,
+        );
+        metadata_pubkey
+    }
+
+    /// Whether the metadata carries a verified membership of `collection`.
+    pub fn is_verified_member_of(&self, collection: &Pubkey) -> bool {
+        matches!(&self.collection, Some(c) if c.verified && c.key == *collection)
+    }
+
+    /// Decodes the borsh layout of `Metadata` up to the `collection` field.
+    fn unpack(src: &[u8]) -> Result<Metadata, ProgramError> {
+        let mut reader = Reader { src };
+        if reader.read_u8()? != METADATA_V1_KEY {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let _update_authority = reader.read_pubkey()?;
+        let mint = reader.read_pubkey()?;
+        let _name = reader.read_bytes_with_len()?;
+        let _symbol = reader.read_bytes_with_len()?;
+        let _uri = reader.read_bytes_with_len()?;
+        let seller_fee_basis_points = reader.read_u16()?;
+        let mut creators = Vec::new();
+        if reader.read_option()? {
+            let creator_count = reader.read_u32()? as usize;
+            for creator in reader
+                .read_bytes(creator_count * CREATOR_LEN)?
+                .chunks_exact(CREATOR_LEN)
+            {
+                creators.push(Creator {
+                    address: Pubkey::try_from(&creator[..32])
+                        .map_err(|_| AuctionError::InvalidMetadata)?,
+                    verified: creator[32] == 1,
+                    share: creator[33],
+                });
+            }
+        }
+        let _primary_sale_happened = reader.read_u8()?;
+        let _is_mutable = reader.read_u8()?;
+        // Accounts created by older program versions end here, the padding reads as `None`.
+        let mut collection = None;
+        if reader.read_option_or_none()? {
+            reader.read_u8()?;
+        }
+        if reader.read_option_or_none()? {
+            reader.read_u8()?;
+        }
+        if reader.read_option_or_none()? {
+            collection = Some(Collection {
+                verified: reader.read_u8()? == 1,
+                key: reader.read_pubkey()?,
+            });
+        }
+
+        Ok(Metadata {
+            mint,
+            seller_fee_basis_points,
+            creators,
+            collection,
+        })
+    }
+}
+
+struct Reader<'a> {
+    src: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], ProgramError> {
+        if self.src.len() < len {
+            return Err(AuctionError::InvalidMetadata.into());
+        }
+        let (bytes, rest) = self.src.split_at(len);
+        self.src = rest;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, ProgramError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16, ProgramError> {
+        let bytes = self.read_bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn read_u32(&mut self) -> Result<u32, ProgramError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn read_pubkey(&mut self) -> Result<Pubkey, ProgramError> {
+        let bytes = self.read_bytes(32)?;
+        Pubkey::try_from(bytes).map_err(|_| AuctionError::InvalidMetadata.into())
+    }
+
+    fn read_bytes_with_len(&mut self) -> Result<&'a [u8], ProgramError> {
+        let len = self.read_u32()? as usize;
+        self.read_bytes(len)
+    }
+
+    fn read_option(&mut self) -> Result<bool, ProgramError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(AuctionError::InvalidMetadata.into()),
+        }
+    }
+
+    fn read_option_or_none(&mut self) -> Result<bool, ProgramError> {
+        if self.src.is_empty() {
+            return Ok(false);
+        }
+        self.read_option()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn creator(address: Pubkey, verified: bool, share: u8) -> Vec<u8> {
+        let mut bytes = address.to_bytes().to_vec();
+        bytes.extend([verified as u8, share]);
+        bytes
+    }
+
+    /// Borsh layout of a metadata account created by older program versions, ending with `is_mutable`.
+    fn legacy_metadata(mint: &Pubkey, creators: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = vec![METADATA_V1_KEY];
+        data.extend(Pubkey::new_unique().to_bytes());
+        data.extend(mint.to_bytes());
+        for field in [&b"name"[..], b"SYM", b"https://example.com"] {
+            data.extend((field.len() as u32).to_le_bytes());
+            data.extend(field);
+        }
+        data.extend(500u16.to_le_bytes());
+        data.push(1);
+        data.extend((creators.len() as u32).to_le_bytes());
+        data.extend(creators.concat());
+        data.extend([1, 1]);
+        data
+    }
+
+    #[test]
+    fn unpacks_collection_of_current_layout() {
+        let mint = Pubkey::new_unique();
+        let collection = Pubkey::new_unique();
+        let mut data = legacy_metadata(
+            &mint,
+            &[
+                creator(Pubkey::new_unique(), true, 60),
+                creator(Pubkey::new_unique(), false, 40),
+            ],
+        );
+        // Edition nonce and token standard, then the collection.
+        data.extend([1, 255, 1, 0, 1, 1]);
+        data.extend(collection.to_bytes());
+        data.resize(data.len() + 32, 0);
+
+        let metadata = Metadata::unpack(&data).unwrap();
+        assert_eq!(metadata.creators.len(), 2);
+        assert!(!metadata.creators[1].verified);
+        assert!(metadata.is_verified_member_of(&collection));
+        assert!(!metadata.is_verified_member_of(&mint));
+
+        // An unverified collection isn't a membership.
+        let verified_offset = data.len() - 32 - 33;
+        data[verified_offset] = 0;
+        let metadata = Metadata::unpack(&data).unwrap();
+        assert!(!metadata.is_verified_member_of(&collection));
+    }
+
+    #[test]
+    fn rejects_malformed_metadata() {
+        let mint = Pubkey::new_unique();
+        let data = legacy_metadata(&mint, &[creator(Pubkey::new_unique(), true, 100)]);
+
+        let mut wrong_key = data.clone();
+        wrong_key[0] = METADATA_V1_KEY + 1;
+        assert_eq!(
+            Metadata::unpack(&wrong_key).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+
+        let truncated = &data[..data.len() - 3];
+        assert_eq!(
+            Metadata::unpack(truncated).err(),
+            Some(AuctionError::InvalidMetadata.into())
+        );
+
+        let mut invalid_option = data.clone();
+        invalid_option.push(2);
+        assert_eq!(
+            Metadata::unpack(&invalid_option).err(),
+            Some(AuctionError::InvalidMetadata.into())
+        );
+    }
+
+    #[test]
+    fn unpacks_legacy_layout() {
+        let mint = Pubkey::new_unique();
+        let creator_pubkey = Pubkey::new_unique();
+        let mut data = legacy_metadata(&mint, &[creator(creator_pubkey, true, 100)]);
+
+        for padding in [0, 64] {
+            data.resize(data.len() + padding, 0);
+            let metadata = Metadata::unpack(&data).unwrap();
+            assert_eq!(metadata.mint, mint);
+            assert_eq!(metadata.seller_fee_basis_points, 500);
+            assert_eq!(metadata.creators.len(), 1);
+            assert_eq!(metadata.creators[0].address, creator_pubkey);
+            assert!(metadata.creators[0].verified);
+            assert_eq!(metadata.creators[0].share, 100);
+            assert!(metadata.collection.is_none());
+        }
+    }
+
+    #[test]
+    fn unpacks_metadata_without_creators() {
+        let mint = Pubkey::new_unique();
+        let mut data = legacy_metadata(&mint, &[]);
+        let creators_offset = data.len() - 2 - 4 - 1;
+        data.drain(creators_offset + 1..creators_offset + 5);
+        data[creators_offset] = 0;
+
+        let metadata = Metadata::unpack(&data).unwrap();
+        assert_eq!(metadata.mint, mint);
+        assert!(metadata.creators.is_empty());
+    }
+}
@@ -1,21 +1,88 @@
 use crate::error::AuctionError;
 use crate::instruction::AuctionInstruction;
-use crate::state::Auction;
+use crate::metadata::Metadata;
+use crate::state::{
+    Auction, AuctionHouse, AuctionKind, Bond, BookEntry, CallAuction, CallOrder, Config,
+    DenylistPage, GradualDutchAuction, Offer, OrderSide, PriceFeed, Raffle, TicketRange,
+    BASIS_POINTS, FIXED_POINT_ONE, LEGACY_AUCTION_LEN, MAX_FEE_BPS, MAX_HOUSE_MINTS,
+    MAX_OFFERED_NFTS, MAX_PAYEES, MAX_PRICE_AGE_SEC, OFFER_SELECTION_PERIOD_SEC,
+    RAFFLE_REVEAL_PERIOD_SEC, RETRACTION_CUTOFF_SEC, SECOND_CHANCE_WINDOW_SEC,
+    SETTLEMENT_GRACE_PERIOD_SEC,
+};
+use arrayref::{array_ref, array_refs};
 use solana_program::account_info::{next_account_info, AccountInfo};
+use solana_program::bpf_loader_upgradeable;
 use solana_program::clock::Clock;
 use solana_program::entrypoint::ProgramResult;
+use solana_program::hash::{hash, hashv};
+use solana_program::keccak;
 use solana_program::msg;
 use solana_program::program::{invoke, invoke_signed};
 use solana_program::program_error::ProgramError;
 use solana_program::program_pack::{IsInitialized, Pack};
 use solana_program::pubkey::Pubkey;
 use solana_program::rent::Rent;
-use solana_program::sysvar::Sysvar;
+use solana_program::system_instruction;
+use solana_program::sysvar::{slot_hashes, Sysvar};
 use spl_token::state::Account as TokenAccount;
-use std::ops::Add;
+use std::cmp::Reverse;
 
 pub struct Processor;
 
+/// Escrowed funds the proceeds of a sale are paid out of
+enum Proceeds<'a, 'b, 'c> {
+    /// FT in a temporary account owned by the PDA
+    Token {
+        token_program: &'a AccountInfo<'b>,
+        temp_account: &'a AccountInfo<'b>,
+        pda: Pubkey,
+        pda_account: &'a AccountInfo<'b>,
+        signers_seeds: &'c [&'c [&'c [u8]]],
+    },
+    /// Lamports in the vault of a lamport auction, owned by the program
+    Lamports { vault_account: &'a AccountInfo<'b> },
+}
+
+impl<'a, 'b> Proceeds<'a, 'b, '_> {
+    /// Checks the account receiving a payout belongs to `owner`:
+    /// an FT account it owns, or the wallet itself for lamports.
+    fn check_recipient(&self, receiving_account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+        let receiving_owner = match self {
+            Proceeds::Token { .. } => {
+                TokenAccount::unpack(&receiving_account.try_borrow_data()?)?.owner
+            }
+            Proceeds::Lamports { .. } => *receiving_account.key,
+        };
+        if receiving_owner != *owner {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    fn transfer(&self, destination_account: &'a AccountInfo<'b>, amount: u64) -> ProgramResult {
+        match self {
+            Proceeds::Token {
+                token_program,
+                temp_account,
+                pda,
+                pda_account,
+                signers_seeds,
+            } => Processor::transfer_from_escrow(
+                token_program,
+                temp_account,
+                destination_account,
+                *pda,
+                pda_account,
+                signers_seeds,
+                amount,
+            ),
+            Proceeds::Lamports { vault_account } => {
+                Processor::transfer_lamports(vault_account, destination_account, amount)
+            }
+        }
+    }
+}
+
 impl Processor {
     pub fn process(
         program_id: &Pubkey,
@@ -27,13 +94,51 @@ impl Processor {
             AuctionInstruction::Exhibit {
                 initial_price,
                 seconds,
+                kind,
+                pays_in_lamports,
+                prices_in_reference,
+                bond_amount,
+                payee_shares_bps,
+                dust_payee_index,
+                rent_payer,
+                house,
+                gate,
+                gate_amount,
+                bidder_allowlist_root,
+                proof,
+                auto_relist_cycles,
+                auto_relist_price_drop_bps,
             } => {
                 msg!("Initializing Auction...");
-                Self::process_exhibit(accounts, initial_price, seconds, program_id)
+                Self::process_exhibit(
+                    accounts,
+                    initial_price,
+                    seconds,
+                    kind,
+                    pays_in_lamports,
+                    prices_in_reference,
+                    bond_amount,
+                    &payee_shares_bps,
+                    dust_payee_index,
+                    rent_payer,
+                    house,
+                    gate,
+                    gate_amount,
+                    bidder_allowlist_root,
+                    &proof,
+                    auto_relist_cycles,
+                    auto_relist_price_drop_bps,
+                    program_id,
+                )
             }
-            AuctionInstruction::Bid { price } => {
+            AuctionInstruction::Bid {
+                price,
+                referrer,
+                rent_payer,
+                proof,
+            } => {
                 msg!("Placing a Bid in the Auction...");
-                Self::process_bid(accounts, price, program_id)
+                Self::process_bid(accounts, price, referrer, rent_payer, &proof, program_id)
             }
             AuctionInstruction::Cancel {} => {
                 msg!("Cancelling the Auction ...");
@@ -43,13 +148,306 @@ impl Processor {
                 msg!("Closing the Auction ...");
                 Self::process_close(accounts, program_id)
             }
+            AuctionInstruction::ExhibitRaffle {
+                ticket_price,
+                seconds,
+                seed_hash,
+            } => {
+                msg!("Initializing Raffle...");
+                Self::process_exhibit_raffle(accounts, ticket_price, seconds, seed_hash, program_id)
+            }
+            AuctionInstruction::BuyTickets { count } => {
+                msg!("Buying Raffle Tickets...");
+                Self::process_buy_tickets(accounts, count, program_id)
+            }
+            AuctionInstruction::Draw { seed } => {
+                msg!("Drawing the Raffle Winner...");
+                Self::process_draw(accounts, seed)
+            }
+            AuctionInstruction::SettleRaffle {} => {
+                msg!("Settling the Raffle...");
+                Self::process_settle_raffle(accounts, program_id)
+            }
+            AuctionInstruction::CancelRaffle {} => {
+                msg!("Cancelling the Raffle...");
+                Self::process_cancel_raffle(accounts, program_id)
+            }
+            AuctionInstruction::ExhibitGda {
+                initial_price,
+                price_scale,
+                decay_rate,
+                max_supply,
+                mints_on_purchase,
+            } => {
+                msg!("Initializing Gradual Dutch Auction...");
+                Self::process_exhibit_gda(
+                    accounts,
+                    initial_price,
+                    price_scale,
+                    decay_rate,
+                    max_supply,
+                    mints_on_purchase,
+                    program_id,
+                )
+            }
+            AuctionInstruction::PurchaseGda {
+                quantity,
+                max_total_price,
+            } => {
+                msg!("Purchasing from the Gradual Dutch Auction...");
+                Self::process_purchase_gda(accounts, quantity, max_total_price, program_id)
+            }
+            AuctionInstruction::CloseGda {} => {
+                msg!("Closing the Gradual Dutch Auction...");
+                Self::process_close_gda(accounts, program_id)
+            }
+            AuctionInstruction::OpenCallAuction { epoch_duration } => {
+                msg!("Opening the Call Auction...");
+                Self::process_open_call_auction(accounts, epoch_duration)
+            }
+            AuctionInstruction::PostAsk { price } => {
+                msg!("Posting an Ask to the Call Auction...");
+                Self::process_post_ask(accounts, price, program_id)
+            }
+            AuctionInstruction::PostBid { price } => {
+                msg!("Posting a Bid to the Call Auction...");
+                Self::process_post_bid(accounts, price, program_id)
+            }
+            AuctionInstruction::CancelOrder {} => {
+                msg!("Cancelling the Order...");
+                Self::process_cancel_order(accounts, program_id)
+            }
+            AuctionInstruction::Clear {} => {
+                msg!("Clearing the Call Auction...");
+                Self::process_clear(accounts, program_id)
+            }
+            AuctionInstruction::MakeOffer {
+                ft_amount,
+                nft_count,
+            } => {
+                msg!("Making an Offer in the Auction...");
+                Self::process_make_offer(accounts, ft_amount, nft_count, program_id)
+            }
+            AuctionInstruction::AcceptOffer {} => {
+                msg!("Accepting the Offer...");
+                Self::process_accept_offer(accounts, program_id)
+            }
+            AuctionInstruction::WithdrawOffer {} => {
+                msg!("Withdrawing the Offer...");
+                Self::process_withdraw_offer(accounts, program_id)
+            }
+            AuctionInstruction::InitConfig {
+                fee_bps,
+                referral_bps,
+                cancel_penalty_bps,
+                retraction_penalty_bps,
+            } => {
+                msg!("Initializing the Config...");
+                Self::process_init_config(
+                    accounts,
+                    fee_bps,
+                    referral_bps,
+                    cancel_penalty_bps,
+                    retraction_penalty_bps,
+                    program_id,
+                )
+            }
+            AuctionInstruction::UpdateFee {
+                fee_bps,
+                referral_bps,
+                cancel_penalty_bps,
+                retraction_penalty_bps,
+            } => {
+                msg!("Updating the Protocol Fee...");
+                Self::process_update_fee(
+                    accounts,
+                    fee_bps,
+                    referral_bps,
+                    cancel_penalty_bps,
+                    retraction_penalty_bps,
+                    program_id,
+                )
+            }
+            AuctionInstruction::SetAdmin {} => {
+                msg!("Setting the Admin...");
+                Self::process_set_admin(accounts, program_id)
+            }
+            AuctionInstruction::BidLamports {
+                price,
+                referrer,
+                proof,
+            } => {
+                msg!("Placing a Lamport Bid in the Auction...");
+                Self::process_bid_lamports(accounts, price, referrer, &proof, program_id)
+            }
+            AuctionInstruction::UpdatePrice { price } => {
+                msg!("Updating the Price Feed...");
+                Self::process_update_price(accounts, price, program_id)
+            }
+            AuctionInstruction::SetPriceAuthority {} => {
+                msg!("Setting the Price Authority...");
+                Self::process_set_price_authority(accounts, program_id)
+            }
+            AuctionInstruction::PostBond {} => {
+                msg!("Posting a Bond...");
+                Self::process_post_bond(accounts, program_id)
+            }
+            AuctionInstruction::RefundBond {} => {
+                msg!("Refunding the Bond...");
+                Self::process_refund_bond(accounts)
+            }
+            AuctionInstruction::ForfeitBond {} => {
+                msg!("Forfeiting the Winner's Bond...");
+                Self::process_forfeit_bond(accounts, program_id)
+            }
+            AuctionInstruction::CancelWithPenalty {} => {
+                msg!("Cancelling the Auction with a Penalty...");
+                Self::process_cancel_with_penalty(accounts, program_id)
+            }
+            AuctionInstruction::OfferSecondChance {} => {
+                msg!("Offering a Second Chance to the Runner-up...");
+                Self::process_offer_second_chance(accounts, program_id)
+            }
+            AuctionInstruction::ExpireSecondChance {} => {
+                msg!("Expiring the Second Chance Offer...");
+                Self::process_expire_second_chance(accounts, program_id)
+            }
+            AuctionInstruction::RetractBid {} => {
+                msg!("Retracting the Bid...");
+                Self::process_retract_bid(accounts, program_id)
+            }
+            AuctionInstruction::CreateHouse {
+                fee_bps,
+                min_increment_bps,
+                min_duration_sec,
+                max_duration_sec,
+                anti_snipe_window_sec,
+                anti_snipe_extension_sec,
+                mint_count,
+                exhibitor_allowlist_root,
+            } => {
+                msg!("Creating the Auction House...");
+                Self::process_create_house(
+                    accounts,
+                    fee_bps,
+                    min_increment_bps,
+                    min_duration_sec,
+                    max_duration_sec,
+                    anti_snipe_window_sec,
+                    anti_snipe_extension_sec,
+                    mint_count,
+                    exhibitor_allowlist_root,
+                    program_id,
+                )
+            }
+            AuctionInstruction::UpdateHouse {
+                fee_bps,
+                min_increment_bps,
+                min_duration_sec,
+                max_duration_sec,
+                anti_snipe_window_sec,
+                anti_snipe_extension_sec,
+                mint_count,
+                exhibitor_allowlist_root,
+            } => {
+                msg!("Updating the Auction House...");
+                Self::process_update_house(
+                    accounts,
+                    fee_bps,
+                    min_increment_bps,
+                    min_duration_sec,
+                    max_duration_sec,
+                    anti_snipe_window_sec,
+                    anti_snipe_extension_sec,
+                    mint_count,
+                    exhibitor_allowlist_root,
+                    program_id,
+                )
+            }
+            AuctionInstruction::SetPaused { paused } => {
+                msg!("Setting the Pause Flag...");
+                Self::process_set_paused(accounts, paused, program_id)
+            }
+            AuctionInstruction::AddToDenylist { wallet } => {
+                msg!("Adding a Wallet to the Denylist...");
+                Self::process_add_to_denylist(accounts, wallet, program_id)
+            }
+            AuctionInstruction::RemoveFromDenylist { wallet } => {
+                msg!("Removing a Wallet from the Denylist...");
+                Self::process_remove_from_denylist(accounts, wallet, program_id)
+            }
+            AuctionInstruction::SetDurationBounds {
+                min_duration_sec,
+                max_duration_sec,
+            } => {
+                msg!("Setting the Duration Bounds...");
+                Self::process_set_duration_bounds(
+                    accounts,
+                    min_duration_sec,
+                    max_duration_sec,
+                    program_id,
+                )
+            }
+            AuctionInstruction::UpdateAuction {
+                initial_price,
+                seconds,
+            } => {
+                msg!("Updating the Auction...");
+                Self::process_update_auction(accounts, initial_price, seconds, program_id)
+            }
+            AuctionInstruction::Relist {
+                initial_price,
+                seconds,
+            } => {
+                msg!("Relisting the Auction...");
+                Self::process_relist(accounts, initial_price, seconds, program_id)
+            }
+            AuctionInstruction::CrankRelist {} => {
+                msg!("Relisting the Auction Automatically...");
+                Self::process_crank_relist(accounts, program_id)
+            }
+            AuctionInstruction::RefundTickets {} => {
+                msg!("Refunding the Raffle Tickets...");
+                Self::process_refund_tickets(accounts, program_id)
+            }
+            AuctionInstruction::SettleFill {} => {
+                msg!("Settling the Fill...");
+                Self::process_settle_fill(accounts, program_id)
+            }
+            AuctionInstruction::MigrateAuction {} => {
+                msg!("Migrating the Auction...");
+                Self::process_migrate_auction(accounts, program_id)
+            }
+            AuctionInstruction::RefundRunnerUp {} => {
+                msg!("Refunding the Runner-up...");
+                Self::process_refund_runner_up(accounts, program_id)
+            }
+            AuctionInstruction::RecordDrawSlot {} => {
+                msg!("Recording the Draw Slot...");
+                Self::process_record_draw_slot(accounts)
+            }
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn process_exhibit(
         accounts: &[AccountInfo],
         initial_price: u64,
         auction_duration_sec: u64,
+        kind: AuctionKind,
+        pays_in_lamports: bool,
+        prices_in_reference: bool,
+        bond_amount: u64,
+        payee_shares_bps: &[u16],
+        dust_payee_index: u8,
+        rent_payer: Option<Pubkey>,
+        house: Option<Pubkey>,
+        gate: Option<Pubkey>,
+        gate_amount: u64,
+        bidder_allowlist_root: Option<[u8; 32]>,
+        proof: &[[u8; 32]],
+        auto_relist_cycles: u8,
+        auto_relist_price_drop_bps: u16,
         program_id: &Pubkey,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
@@ -78,181 +476,225 @@ impl Processor {
 
         let sys_var_clock_account = next_account_info(account_info_iter)?;
         let clock = &Clock::from_account_info(sys_var_clock_account)?;
-
-        auction_info.is_initialized = true;
-        auction_info.exhibitor_pubkey = *exhibitor_account.key;
-        auction_info.exhibiting_nft_temp_pubkey = *exhibitor_nft_temp_account.key;
-        auction_info.exhibitor_ft_receiving_pubkey = *exhibitor_ft_receiving_account.key;
-        auction_info.price = initial_price;
-        auction_info.end_at = clock.unix_timestamp.add(auction_duration_sec as i64);
-        Auction::pack(auction_info, &mut escrow_account.try_borrow_mut_data()?)?;
-
-        let (pda, _bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
         let token_program = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+        Self::check_not_paused(&config_info)?;
+        let denylist_page_account = next_account_info(account_info_iter)?;
+        Self::check_not_denied(exhibitor_account.key, denylist_page_account, program_id)?;
 
-        let exhibit_ix = spl_token::instruction::transfer(
-            token_program.key,
-            exhibitor_nft_account.key,
-            exhibitor_nft_temp_account.key,
-            exhibitor_account.key,
-            &[], // authority_pubkey is default signer when the signer_pubkeys is empty.
-            1,
-        )?;
-        msg!("Transferring the NFT to the Escrow Account...");
-        invoke(
-            &exhibit_ix,
-            &[
-                exhibitor_nft_account.clone(),
-                exhibitor_nft_temp_account.clone(),
-                exhibitor_account.clone(),
-                token_program.clone(),
-            ],
-        )?;
+        Self::check_duration(&config_info, auction_duration_sec)?;
+        let end_at = Self::end_at(clock, auction_duration_sec)?;
 
-        let owner_change_ix = spl_token::instruction::set_authority(
-            token_program.key,
-            exhibitor_nft_temp_account.key,
-            Some(&pda),
-            spl_token::instruction::AuthorityType::AccountOwner,
-            exhibitor_account.key,
-            &[], // owner_pubkey is default signer when the signer_pubkeys is empty.
-        )?;
-        msg!("Changing ownership of the token account...");
-        invoke(
-            &owner_change_ix,
-            &[
-                exhibitor_nft_temp_account.clone(),
-                exhibitor_account.clone(),
-                token_program.clone(),
-            ],
-        )?;
-        Ok(())
-    }
+        if kind == AuctionKind::Reverse && initial_price == 0 {
+            msg!("A reverse auction needs a budget to buy with");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if auto_relist_cycles > 0 && kind != AuctionKind::English {
+            msg!("Only English auctions are relisted automatically");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if auto_relist_price_drop_bps as u64 > BASIS_POINTS {
+            return Err(AuctionError::InvalidBasisPoints.into());
+        }
+        auction_info.duration_sec = auction_duration_sec;
+        auction_info.auto_relist_cycles = auto_relist_cycles;
+        auction_info.auto_relist_price_drop_bps = auto_relist_price_drop_bps;
 
-    fn process_bid(accounts: &[AccountInfo], price: u64, program_id: &Pubkey) -> ProgramResult {
-        let account_info_iter = &mut accounts.iter();
-        let bidder_account = next_account_info(account_info_iter)?;
+        // A reverse auction escrows the budget and names the collection it buys from.
+        let (escrow_amount, collection_pubkey) = match kind {
+            AuctionKind::English | AuctionKind::Barter => (1, Pubkey::default()),
+            AuctionKind::Reverse => {
+                let collection_mint_account = next_account_info(account_info_iter)?;
+                (initial_price, *collection_mint_account.key)
+            }
+        };
 
-        if !bidder_account.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+        if pays_in_lamports && kind != AuctionKind::English {
+            msg!("Only English auctions take lamport bids");
+            return Err(AuctionError::InvalidInstruction.into());
         }
-        let highest_bidder_account = next_account_info(account_info_iter)?;
-        let highest_bidder_ft_temp_account = next_account_info(account_info_iter)?;
-        let highest_bidder_ft_returning_account = next_account_info(account_info_iter)?;
+        auction_info.pays_in_lamports = pays_in_lamports;
 
-        let bidder_ft_temp_account = next_account_info(account_info_iter)?;
-        let bidder_ft_account = next_account_info(account_info_iter)?;
+        if prices_in_reference
+            && (kind != AuctionKind::English || pays_in_lamports || !payee_shares_bps.is_empty())
+        {
+            msg!("Only English auctions paid in FT without payees can be reference priced");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        auction_info.prices_in_reference = prices_in_reference;
+        auction_info.bids_in_vault = kind == AuctionKind::English && !prices_in_reference;
 
-        let escrow_account = next_account_info(account_info_iter)?;
-        let mut auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+        let (pda, _bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
 
-        let sys_var_clock_account = next_account_info(account_info_iter)?;
-        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+        // Bids made in a single mint, or in lamports, are all deposited into the vault of the auction.
+        if kind == AuctionKind::English && !prices_in_reference {
+            let vault_account = next_account_info(account_info_iter)?;
+            let system_program = next_account_info(account_info_iter)?;
+            let (vault_pubkey, vault_bump_seed) =
+                Pubkey::find_program_address(&[b"vault", escrow_account.key.as_ref()], program_id);
+            if vault_pubkey != *vault_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let vault_signers_seeds: &[&[&[u8]]] = &[&[
+                &b"vault"[..],
+                escrow_account.key.as_ref(),
+                &[vault_bump_seed],
+            ]];
 
-        if auction_info.end_at <= clock.unix_timestamp {
-            return Err(AuctionError::InactiveAuction.into());
-        }
+            // The exhibitor pays the vault's rent up front, so bids of any size can be held in it.
+            let (vault_len, vault_owner) = if pays_in_lamports {
+                (0, program_id)
+            } else {
+                (TokenAccount::LEN, token_program.key)
+            };
+            msg!("Creating the Vault Account...");
+            Self::create_pda_account(
+                exhibitor_account,
+                vault_account,
+                system_program,
+                rent.minimum_balance(vault_len),
+                vault_len,
+                vault_owner,
+                vault_signers_seeds,
+            )?;
 
-        if auction_info.price >= price {
-            return Err(AuctionError::InsufficientBidPrice.into());
+            if !pays_in_lamports {
+                let ft_mint_account = next_account_info(account_info_iter)?;
+                let exhibitor_ft_receiving_account_data =
+                    TokenAccount::unpack(&exhibitor_ft_receiving_account.try_borrow_data()?)?;
+                if exhibitor_ft_receiving_account_data.mint != *ft_mint_account.key {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                let init_vault_ix = spl_token::instruction::initialize_account3(
+                    token_program.key,
+                    vault_account.key,
+                    ft_mint_account.key,
+                    vault_account.key,
+                )?;
+                msg!("Initializing the Vault Account...");
+                invoke(
+                    &init_vault_ix,
+                    &[
+                        vault_account.clone(),
+                        ft_mint_account.clone(),
+                        token_program.clone(),
+                    ],
+                )?;
+            }
         }
 
-        if auction_info.highest_bidder_ft_temp_pubkey != *highest_bidder_ft_temp_account.key {
+        if bond_amount > 0 && kind != AuctionKind::English {
+            msg!("Only English auctions take bonds");
             return Err(AuctionError::InvalidInstruction.into());
         }
-        if auction_info.highest_bidder_ft_returning_pubkey
-            != *highest_bidder_ft_returning_account.key
-        {
+        auction_info.bond_amount = bond_amount;
+
+        if gate.is_some() && kind == AuctionKind::Barter {
+            msg!("Barter auctions take offers from anyone");
             return Err(AuctionError::InvalidInstruction.into());
         }
-        if auction_info.highest_bidder_pubkey != *highest_bidder_account.key {
-            return Err(AuctionError::InvalidInstruction.into());
+        auction_info.gate_pubkey = gate.unwrap_or_default();
+        auction_info.gate_amount = gate_amount;
+        auction_info.bidder_allowlist_root = bidder_allowlist_root.unwrap_or_default();
+
+        // Only the proceeds of an English auction can be split, and the shares must cover them exactly.
+        if !payee_shares_bps.is_empty()
+            && (kind != AuctionKind::English
+                || payee_shares_bps.len() > MAX_PAYEES
+                || dust_payee_index as usize >= payee_shares_bps.len()
+                || payee_shares_bps
+                    .iter()
+                    .map(|share_bps| *share_bps as u64)
+                    .sum::<u64>()
+                    != BASIS_POINTS)
+        {
+            return Err(AuctionError::InvalidPayees.into());
         }
-        if auction_info.highest_bidder_pubkey == *bidder_account.key {
-            return Err(AuctionError::AlreadyBid.into());
+        for (index, share_bps) in payee_shares_bps.iter().enumerate() {
+            let payee_receiving_account = next_account_info(account_info_iter)?;
+            auction_info.payee_receiving_pubkeys[index] = *payee_receiving_account.key;
+            auction_info.payee_shares_bps[index] = *share_bps;
         }
-        let token_program = next_account_info(account_info_iter)?;
-        let pda_account = next_account_info(account_info_iter)?;
-        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
-
-        let transfer_to_escrow_ix = spl_token::instruction::transfer(
-            token_program.key,
-            bidder_ft_account.key,
-            bidder_ft_temp_account.key,
-            bidder_account.key,
-            &[], 
-            price,
-        )?;
-        msg!("Transferring FT to the Escrow Account from the bidder...");
-        invoke(
-            &transfer_to_escrow_ix,
-            &[
-                bidder_ft_account.clone(),
-                bidder_ft_temp_account.clone(),
-                bidder_account.clone(),
-                token_program.clone(),
-            ],
-        )?;
-
-        let owner_change_ix = spl_token::instruction::set_authority(
-            token_program.key,
-            bidder_ft_temp_account.key,
-            Some(&pda),
-            spl_token::instruction::AuthorityType::AccountOwner,
-            bidder_account.key,
-            &[], // owner_pubkey is default signer when the signer_pubkeys is empty.
-        )?;
-        msg!("Changing ownership of the token account...");
-        invoke(
-            &owner_change_ix,
-            &[
-                bidder_ft_temp_account.clone(),
-                bidder_account.clone(),
-                token_program.clone(),
-            ],
-        )?;
+        auction_info.payee_count = payee_shares_bps.len() as u8;
+        auction_info.dust_payee_index = dust_payee_index;
 
-        if auction_info.highest_bidder_pubkey != Pubkey::default(){
-            let transfer_to_previous_bidder_ix = spl_token::instruction::transfer(
-                token_program.key,
-                highest_bidder_ft_temp_account.key,
-                highest_bidder_ft_returning_account.key,
-                &pda,
-                &[], // authority_pubkey is default signer when the signer_pubkeys is empty.
-                auction_info.price,
+        if let Some(house_pubkey) = house {
+            let house_account = next_account_info(account_info_iter)?;
+            if house_pubkey != *house_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let house_info = Self::unpack_house(house_account, program_id)?;
+            Self::check_allowlist(
+                &house_info.exhibitor_allowlist_root,
+                exhibitor_account.key,
+                proof,
             )?;
-            msg!("Transferring FT to the previous highest bidder from the escrow account...");
-            let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
-            invoke_signed(
-                &transfer_to_previous_bidder_ix,
-                &[
-                    highest_bidder_ft_temp_account.clone(),
-                    highest_bidder_ft_returning_account.clone(),
-                    pda_account.clone(),
-                    token_program.clone(),
-                ],
-                signers_seeds,
-            );
+            Self::check_house_duration(&house_info, auction_duration_sec)?;
 
-            Self::close_temporary_ft(
-                token_program,
-                highest_bidder_ft_temp_account,
-                highest_bidder_account,
-                pda,
-                pda_account,
-                signers_seeds,
-            )?;
+            // Bids of barter and reference priced auctions can come in any mint.
+            let payment_mint = if pays_in_lamports {
+                Some(spl_token::native_mint::id())
+            } else if kind == AuctionKind::Barter || prices_in_reference {
+                None
+            } else {
+                let payment_account = match kind {
+                    AuctionKind::Reverse => exhibitor_nft_account,
+                    _ => exhibitor_ft_receiving_account,
+                };
+                Some(TokenAccount::unpack(&payment_account.try_borrow_data()?)?.mint)
+            };
+            if !payment_mint.map_or(house_info.payment_mint_count == 0, |mint| {
+                house_info.allows_mint(&mint)
+            }) {
+                msg!("The house doesn't take auctions paid in this mint");
+                return Err(AuctionError::HousePolicyViolation.into());
+            }
+
+            auction_info.house_pubkey = house_pubkey;
+            auction_info.fee_bps = house_info.fee_bps;
+            auction_info.treasury_pubkey = house_info.treasury_pubkey;
+            auction_info.min_increment_bps = house_info.min_increment_bps;
+            auction_info.anti_snipe_window_sec = house_info.anti_snipe_window_sec;
+            auction_info.anti_snipe_extension_sec = house_info.anti_snipe_extension_sec;
         }
 
-        auction_info.price = price;
-        auction_info.highest_bidder_pubkey = *bidder_account.key;
-        auction_info.highest_bidder_ft_temp_pubkey = *bidder_ft_temp_account.key;
-        auction_info.highest_bidder_ft_returning_pubkey = *bidder_ft_account.key;
+        auction_info.is_initialized = true;
+        auction_info.exhibitor_pubkey = *exhibitor_account.key;
+        auction_info.exhibiting_nft_temp_pubkey = *exhibitor_nft_temp_account.key;
+        auction_info.exhibitor_ft_receiving_pubkey = *exhibitor_ft_receiving_account.key;
+        auction_info.price = initial_price;
+        auction_info.initial_price = initial_price;
+        auction_info.end_at = end_at;
+        auction_info.kind = kind;
+        auction_info.collection_pubkey = collection_pubkey;
+        auction_info.rent_payer_pubkey =
+            Self::check_rent_payer(rent_payer, exhibitor_account, account_info_iter)?;
+        // The fee of the house replaces the protocol fee.
+        if auction_info.house_pubkey == Pubkey::default() {
+            auction_info.fee_bps = config_info.fee_bps;
+            auction_info.treasury_pubkey = config_info.treasury_pubkey;
+        }
+        auction_info.cancel_penalty_bps = config_info.cancel_penalty_bps;
         Auction::pack(auction_info, &mut escrow_account.try_borrow_mut_data()?)?;
-        Ok(())
+
+        msg!("Transferring the exhibited token to the Escrow Account...");
+        Self::escrow_tokens(
+            token_program,
+            exhibitor_nft_account,
+            exhibitor_nft_temp_account,
+            exhibitor_account,
+            pda,
+            escrow_amount,
+        )
     }
 
-    fn process_cancel(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    fn process_update_auction(
+        accounts: &[AccountInfo],
+        initial_price: u64,
+        auction_duration_sec: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let exhibitor_account = next_account_info(account_info_iter)?;
 
@@ -260,27 +702,591 @@ impl Processor {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        let exhibiting_nft_temp_account = next_account_info(account_info_iter)?;
-        let exhibiting_nft_returning_account = next_account_info(account_info_iter)?;
         let escrow_account = next_account_info(account_info_iter)?;
-        let auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+        let mut auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+        Self::check_not_paused(&config_info)?;
+        let exhibitor_ft_receiving_account = next_account_info(account_info_iter)?;
+        let new_exhibitor_ft_receiving_account = next_account_info(account_info_iter)?;
 
         if auction_info.exhibitor_pubkey != *exhibitor_account.key {
             return Err(ProgramError::InvalidAccountData);
         }
-        if auction_info.exhibiting_nft_temp_pubkey != *exhibiting_nft_temp_account.key {
+        if auction_info.exhibitor_ft_receiving_pubkey != *exhibitor_ft_receiving_account.key {
             return Err(ProgramError::InvalidAccountData);
         }
-
+        if auction_info.end_at <= clock.unix_timestamp {
+            return Err(AuctionError::InactiveAuction.into());
+        }
+        // Offers made to a barter auction aren't recorded in the escrow account, so there is no telling whether any came.
+        if auction_info.kind == AuctionKind::Barter {
+            msg!("Barter auctions can't be updated");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
         if auction_info.highest_bidder_pubkey != Pubkey::default() {
             return Err(AuctionError::AlreadyBid.into());
         }
+        if auction_info.kind == AuctionKind::Reverse && initial_price != auction_info.price {
+            msg!("The budget of a reverse auction can't be changed");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
 
-        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        Self::check_duration(&config_info, auction_duration_sec)?;
+        if auction_info.house_pubkey != Pubkey::default() {
+            let house_account = next_account_info(account_info_iter)?;
+            if auction_info.house_pubkey != *house_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let house_info = Self::unpack_house(house_account, program_id)?;
+            Self::check_house_duration(&house_info, auction_duration_sec)?;
+        }
+        let end_at = Self::end_at(clock, auction_duration_sec)?;
+
+        // The proceeds keep being paid in the mint the auction was exhibited with.
+        if !auction_info.pays_in_lamports && !auction_info.prices_in_reference {
+            let exhibitor_ft_receiving_account_data =
+                TokenAccount::unpack(&exhibitor_ft_receiving_account.try_borrow_data()?)?;
+            let new_exhibitor_ft_receiving_account_data =
+                TokenAccount::unpack(&new_exhibitor_ft_receiving_account.try_borrow_data()?)?;
+            if exhibitor_ft_receiving_account_data.mint
+                != new_exhibitor_ft_receiving_account_data.mint
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+
+        msg!(
+            "Initial price changed from {} to {}",
+            auction_info.price,
+            initial_price
+        );
+        msg!(
+            "End time changed from {} to {}",
+            auction_info.end_at,
+            end_at
+        );
+        msg!(
+            "Proceeds account changed from {} to {}",
+            auction_info.exhibitor_ft_receiving_pubkey,
+            new_exhibitor_ft_receiving_account.key
+        );
+        auction_info.price = initial_price;
+        auction_info.initial_price = initial_price;
+        auction_info.end_at = end_at;
+        auction_info.duration_sec = auction_duration_sec;
+        auction_info.exhibitor_ft_receiving_pubkey = *new_exhibitor_ft_receiving_account.key;
+        Auction::pack(auction_info, &mut escrow_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    fn process_relist(
+        accounts: &[AccountInfo],
+        initial_price: u64,
+        auction_duration_sec: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let exhibitor_account = next_account_info(account_info_iter)?;
+
+        if !exhibitor_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let escrow_account = next_account_info(account_info_iter)?;
+        let mut auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+        Self::check_not_paused(&config_info)?;
+        let denylist_page_account = next_account_info(account_info_iter)?;
+        Self::check_not_denied(exhibitor_account.key, denylist_page_account, program_id)?;
+
+        if auction_info.exhibitor_pubkey != *exhibitor_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Self::check_unsold(&auction_info, clock)?;
+        if auction_info.kind == AuctionKind::Reverse && initial_price != auction_info.price {
+            msg!("The budget of a reverse auction can't be changed");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+
+        Self::check_duration(&config_info, auction_duration_sec)?;
+        if auction_info.house_pubkey != Pubkey::default() {
+            let house_account = next_account_info(account_info_iter)?;
+            if auction_info.house_pubkey != *house_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let house_info = Self::unpack_house(house_account, program_id)?;
+            Self::check_house_duration(&house_info, auction_duration_sec)?;
+        }
+
+        auction_info.price = initial_price;
+        auction_info.initial_price = initial_price;
+        auction_info.end_at = Self::end_at(clock, auction_duration_sec)?;
+        auction_info.duration_sec = auction_duration_sec;
+        msg!(
+            "Relisted at {} until {}",
+            auction_info.price,
+            auction_info.end_at
+        );
+        Auction::pack(auction_info, &mut escrow_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    fn process_crank_relist(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let escrow_account = next_account_info(account_info_iter)?;
+        let mut auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+        Self::check_not_paused(&config_info)?;
+
+        if escrow_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::check_unsold(&auction_info, clock)?;
+        if auction_info.auto_relist_cycles == 0 {
+            msg!("The auction has no relisting left");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+
+        // The bounds may have changed since the auction was exhibited.
+        Self::check_duration(&config_info, auction_info.duration_sec)?;
+        if auction_info.house_pubkey != Pubkey::default() {
+            let house_account = next_account_info(account_info_iter)?;
+            if auction_info.house_pubkey != *house_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let house_info = Self::unpack_house(house_account, program_id)?;
+            Self::check_house_duration(&house_info, auction_info.duration_sec)?;
+        }
+
+        let price_drop = (auction_info.price as u128
+            * auction_info.auto_relist_price_drop_bps as u128
+            / BASIS_POINTS as u128) as u64;
+        auction_info.price -= price_drop;
+        auction_info.initial_price = auction_info.price;
+        auction_info.end_at = Self::end_at(clock, auction_info.duration_sec)?;
+        auction_info.auto_relist_cycles -= 1;
+        msg!(
+            "Relisted at {} until {}, {} relistings left",
+            auction_info.price,
+            auction_info.end_at,
+            auction_info.auto_relist_cycles
+        );
+        Auction::pack(auction_info, &mut escrow_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    /// Checks an English or reverse auction ended without bids, so it can be relisted.
+    fn check_unsold(auction_info: &Auction, clock: &Clock) -> ProgramResult {
+        if auction_info.kind == AuctionKind::Barter {
+            msg!("Barter auctions can't be relisted");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if auction_info.end_at > clock.unix_timestamp {
+            return Err(AuctionError::ActiveAuction.into());
+        }
+        if auction_info.highest_bidder_pubkey != Pubkey::default() {
+            return Err(AuctionError::AlreadyBid.into());
+        }
+        Ok(())
+    }
+
+    fn process_bid(
+        accounts: &[AccountInfo],
+        price: u64,
+        referrer: Option<Pubkey>,
+        rent_payer: Option<Pubkey>,
+        proof: &[[u8; 32]],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let bidder_account = next_account_info(account_info_iter)?;
+
+        if !bidder_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let highest_bidder_account = next_account_info(account_info_iter)?;
+        let highest_bidder_ft_temp_account = next_account_info(account_info_iter)?;
+        let highest_bidder_ft_returning_account = next_account_info(account_info_iter)?;
+
+        let bidder_ft_temp_account = next_account_info(account_info_iter)?;
+        let bidder_ft_account = next_account_info(account_info_iter)?;
+
+        let escrow_account = next_account_info(account_info_iter)?;
+        let mut auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if auction_info.end_at <= clock.unix_timestamp {
+            return Err(AuctionError::InactiveAuction.into());
+        }
+
+        match auction_info.kind {
+            AuctionKind::English
+                if !auction_info.prices_in_reference && !auction_info.outbids(price) =>
+            {
+                return Err(AuctionError::InsufficientBidPrice.into());
+            }
+            AuctionKind::Reverse if auction_info.price <= price => {
+                return Err(AuctionError::ExcessiveAskPrice.into());
+            }
+            AuctionKind::Barter => {
+                msg!("Barter auctions only take offers");
+                return Err(AuctionError::InvalidInstruction.into());
+            }
+            _ => {}
+        }
+        if auction_info.pays_in_lamports {
+            msg!("Lamport auctions only take lamport bids");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+
+        if auction_info.highest_bidder_ft_temp_pubkey != *highest_bidder_ft_temp_account.key {
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if auction_info.highest_bidder_ft_returning_pubkey
+            != *highest_bidder_ft_returning_account.key
+        {
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if auction_info.highest_bidder_pubkey != *highest_bidder_account.key {
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if auction_info.highest_bidder_pubkey == *bidder_account.key {
+            return Err(AuctionError::AlreadyBid.into());
+        }
+        if auction_info.holds_ft_in_vault() {
+            let (vault_pubkey, _vault_bump_seed) =
+                Pubkey::find_program_address(&[b"vault", escrow_account.key.as_ref()], program_id);
+            if vault_pubkey != *bidder_ft_temp_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        } else if auction_info.runner_up_ft_temp_pubkey == *bidder_ft_temp_account.key {
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+
+        // English bidders escrow the FT they bid, reverse auction bidders escrow the NFT they ask to sell.
+        let (escrow_amount, previous_escrow_amount) = match auction_info.kind {
+            AuctionKind::English => (price, auction_info.price),
+            AuctionKind::Barter => return Err(AuctionError::InvalidInstruction.into()),
+            AuctionKind::Reverse => {
+                let metadata_account = next_account_info(account_info_iter)?;
+                let bidder_nft_account_data =
+                    TokenAccount::unpack(&bidder_ft_account.try_borrow_data()?)?;
+                let metadata =
+                    Metadata::from_account_info(metadata_account, &bidder_nft_account_data.mint)?;
+                if !metadata.is_verified_member_of(&auction_info.collection_pubkey) {
+                    return Err(AuctionError::CollectionMismatch.into());
+                }
+                (1, 1)
+            }
+        };
+
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+        Self::check_not_paused(&config_info)?;
+        let denylist_page_account = next_account_info(account_info_iter)?;
+        Self::check_not_denied(bidder_account.key, denylist_page_account, program_id)?;
+
+        // The referral share is fixed when the bid is placed, so later config changes don't affect it.
+        let referral_bps = Self::referral_bps(referrer, bidder_account, &config_info)?;
+
+        // Reference priced bids are compared by their value in reference units.
+        let bid_value = if auction_info.prices_in_reference {
+            let price_feed_account = next_account_info(account_info_iter)?;
+            let bidder_ft_account_data =
+                TokenAccount::unpack(&bidder_ft_account.try_borrow_data()?)?;
+            let price_feed_info = Self::unpack_price_feed(
+                price_feed_account,
+                &bidder_ft_account_data.mint,
+                clock,
+                program_id,
+            )?;
+            let bid_value = u64::try_from(
+                price as u128 * price_feed_info.price as u128 / FIXED_POINT_ONE as u128,
+            )
+            .map_err(|_| AuctionError::AmountOverflow)?;
+            if !auction_info.outbids(bid_value) {
+                msg!("The bid is worth {} reference units", bid_value);
+                return Err(AuctionError::InsufficientBidPrice.into());
+            }
+            bid_value
+        } else {
+            price
+        };
+        Self::check_bond(
+            &auction_info,
+            escrow_account,
+            bidder_account,
+            account_info_iter,
+            program_id,
+        )?;
+        Self::check_gate(&auction_info, bidder_account, account_info_iter)?;
+        Self::check_allowlist(
+            &auction_info.bidder_allowlist_root,
+            bidder_account.key,
+            proof,
+        )?;
+
+        if auction_info.holds_ft_in_vault() {
+            msg!("Transferring the bid to the Vault from the bidder...");
+            Self::deposit_tokens(
+                token_program,
+                bidder_ft_account,
+                bidder_ft_temp_account,
+                bidder_account,
+                escrow_amount,
+            )?;
+        } else {
+            msg!("Transferring the bid to the Escrow Account from the bidder...");
+            Self::escrow_tokens(
+                token_program,
+                bidder_ft_account,
+                bidder_ft_temp_account,
+                bidder_account,
+                pda,
+                escrow_amount,
+            )?;
+        }
+
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+        let vault_bump_seed = Self::vault_bump_seed(escrow_account.key, program_id);
+        let vault_signers_seeds: &[&[&[u8]]] = &[&[
+            &b"vault"[..],
+            escrow_account.key.as_ref(),
+            &[vault_bump_seed],
+        ]];
+        if auction_info.highest_bidder_pubkey != Pubkey::default() {
+            if auction_info.kind == AuctionKind::English {
+                // The outbid highest bid stays in escrow as the runner-up, for a second chance offer.
+                Self::refund_runner_up(
+                    &auction_info,
+                    account_info_iter,
+                    token_program,
+                    pda,
+                    pda_account,
+                    signers_seeds,
+                    vault_signers_seeds,
+                )?;
+                auction_info.runner_up_pubkey = auction_info.highest_bidder_pubkey;
+                auction_info.runner_up_ft_temp_pubkey = auction_info.highest_bidder_ft_temp_pubkey;
+                auction_info.runner_up_ft_returning_pubkey =
+                    auction_info.highest_bidder_ft_returning_pubkey;
+                auction_info.runner_up_price = auction_info.price;
+                auction_info.runner_up_referrer_pubkey =
+                    auction_info.highest_bidder_referrer_pubkey;
+                auction_info.runner_up_referral_bps = auction_info.referral_bps;
+                auction_info.runner_up_rent_payer_pubkey =
+                    auction_info.highest_bidder_rent_payer_pubkey;
+            } else {
+                msg!("Returning the previous highest bid from the escrow account...");
+                Self::transfer_from_escrow(
+                    token_program,
+                    highest_bidder_ft_temp_account,
+                    highest_bidder_ft_returning_account,
+                    pda,
+                    pda_account,
+                    signers_seeds,
+                    previous_escrow_amount,
+                )?;
+
+                let rent_payer_account = Self::rent_payer_account(
+                    &auction_info.highest_bidder_rent_payer_pubkey,
+                    highest_bidder_account,
+                    account_info_iter,
+                )?;
+                Self::close_temporary_ft(
+                    token_program,
+                    highest_bidder_ft_temp_account,
+                    rent_payer_account,
+                    pda,
+                    pda_account,
+                    signers_seeds,
+                )?;
+            }
+        }
+
+        auction_info.price = bid_value;
+        auction_info.highest_bidder_pubkey = *bidder_account.key;
+        auction_info.highest_bidder_ft_temp_pubkey = *bidder_ft_temp_account.key;
+        auction_info.highest_bidder_ft_returning_pubkey = *bidder_ft_account.key;
+        auction_info.highest_bidder_referrer_pubkey = referrer.unwrap_or_default();
+        auction_info.referral_bps = referral_bps;
+        auction_info.highest_bidder_rent_payer_pubkey = if auction_info.holds_ft_in_vault() {
+            *bidder_account.key
+        } else {
+            Self::check_rent_payer(rent_payer, bidder_account, account_info_iter)?
+        };
+        auction_info.extend_for_late_bid(clock.unix_timestamp)?;
+        Auction::pack(auction_info, &mut escrow_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    fn process_bid_lamports(
+        accounts: &[AccountInfo],
+        price: u64,
+        referrer: Option<Pubkey>,
+        proof: &[[u8; 32]],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let bidder_account = next_account_info(account_info_iter)?;
+
+        if !bidder_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        let highest_bidder_account = next_account_info(account_info_iter)?;
+
+        let escrow_account = next_account_info(account_info_iter)?;
+        let mut auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+        let vault_account = next_account_info(account_info_iter)?;
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+        let system_program = next_account_info(account_info_iter)?;
+
+        if auction_info.end_at <= clock.unix_timestamp {
+            return Err(AuctionError::InactiveAuction.into());
+        }
+        if !auction_info.pays_in_lamports {
+            msg!("Only lamport auctions take lamport bids");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if !auction_info.outbids(price) {
+            return Err(AuctionError::InsufficientBidPrice.into());
+        }
+        if auction_info.highest_bidder_pubkey != *highest_bidder_account.key {
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if auction_info.highest_bidder_pubkey == *bidder_account.key {
+            return Err(AuctionError::AlreadyBid.into());
+        }
+        let (vault_pubkey, _vault_bump_seed) =
+            Pubkey::find_program_address(&[b"vault", escrow_account.key.as_ref()], program_id);
+        if vault_pubkey != *vault_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+        Self::check_not_paused(&config_info)?;
+        let denylist_page_account = next_account_info(account_info_iter)?;
+        Self::check_not_denied(bidder_account.key, denylist_page_account, program_id)?;
+
+        // The referral share is fixed when the bid is placed, so later config changes don't affect it.
+        let referral_bps = Self::referral_bps(referrer, bidder_account, &config_info)?;
+        Self::check_bond(
+            &auction_info,
+            escrow_account,
+            bidder_account,
+            account_info_iter,
+            program_id,
+        )?;
+        Self::check_gate(&auction_info, bidder_account, account_info_iter)?;
+        Self::check_allowlist(
+            &auction_info.bidder_allowlist_root,
+            bidder_account.key,
+            proof,
+        )?;
+
+        let transfer_to_vault_ix =
+            system_instruction::transfer(bidder_account.key, vault_account.key, price);
+        msg!("Transferring the bid to the Vault from the bidder...");
+        invoke(
+            &transfer_to_vault_ix,
+            &[
+                bidder_account.clone(),
+                vault_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+
+        if auction_info.highest_bidder_pubkey != Pubkey::default() {
+            msg!("Returning the previous highest bid from the vault...");
+            Self::transfer_lamports(vault_account, highest_bidder_account, auction_info.price)?;
+        }
+
+        auction_info.price = price;
+        auction_info.highest_bidder_pubkey = *bidder_account.key;
+        auction_info.highest_bidder_ft_temp_pubkey = *vault_account.key;
+        auction_info.highest_bidder_ft_returning_pubkey = *bidder_account.key;
+        auction_info.highest_bidder_referrer_pubkey = referrer.unwrap_or_default();
+        auction_info.referral_bps = referral_bps;
+        auction_info.highest_bidder_rent_payer_pubkey = *bidder_account.key;
+        auction_info.extend_for_late_bid(clock.unix_timestamp)?;
+        Auction::pack(auction_info, &mut escrow_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    fn process_cancel(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let exhibitor_account = next_account_info(account_info_iter)?;
+
+        if !exhibitor_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let exhibiting_nft_temp_account = next_account_info(account_info_iter)?;
+        let exhibiting_nft_returning_account = next_account_info(account_info_iter)?;
+        let escrow_account = next_account_info(account_info_iter)?;
+        let auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+
+        if auction_info.exhibitor_pubkey != *exhibitor_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.exhibiting_nft_temp_pubkey != *exhibiting_nft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if auction_info.highest_bidder_pubkey != Pubkey::default() {
+            return Err(AuctionError::AlreadyBid.into());
+        }
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
         let token_program = next_account_info(account_info_iter)?;
         let pda_account = next_account_info(account_info_iter)?;
         let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
 
+        if auction_info.pays_in_lamports || auction_info.holds_ft_in_vault() {
+            let vault_account = next_account_info(account_info_iter)?;
+            let (vault_pubkey, vault_bump_seed) =
+                Pubkey::find_program_address(&[b"vault", escrow_account.key.as_ref()], program_id);
+            if vault_pubkey != *vault_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if auction_info.pays_in_lamports {
+                msg!("Closing the Vault Account...");
+                Self::close_state_account(vault_account, exhibitor_account)?;
+            } else {
+                let vault_signers_seeds: &[&[&[u8]]] = &[&[
+                    &b"vault"[..],
+                    escrow_account.key.as_ref(),
+                    &[vault_bump_seed],
+                ]];
+                Self::close_vault(
+                    &auction_info,
+                    account_info_iter,
+                    token_program,
+                    vault_account,
+                    exhibitor_account,
+                    None,
+                    vault_signers_seeds,
+                )?;
+            }
+        }
+
         let exhibiting_nft_temp_account_data =
             TokenAccount::unpack(&exhibiting_nft_temp_account.try_borrow_data()?)?;
         let transfer_nft_to_exhibitor_ix = spl_token::instruction::transfer(
@@ -288,7 +1294,7 @@ impl Processor {
             exhibiting_nft_temp_account.key,
             exhibiting_nft_returning_account.key,
             &pda,
-            &[], 
+            &[],
             exhibiting_nft_temp_account_data.amount,
         )?;
         msg!("Transferring NFT to the Exhibitor...");
@@ -303,10 +1309,15 @@ impl Processor {
             signers_seeds,
         )?;
 
+        let rent_payer_account = Self::rent_payer_account(
+            &auction_info.rent_payer_pubkey,
+            exhibitor_account,
+            account_info_iter,
+        )?;
         Self::close_escrow(
             token_program,
             exhibiting_nft_temp_account,
-            exhibitor_account,
+            rent_payer_account,
             pda,
             pda_account,
             escrow_account,
@@ -346,7 +1357,15 @@ impl Processor {
         if auction_info.exhibiting_nft_temp_pubkey != *exhibiting_nft_temp_account.key {
             return Err(ProgramError::InvalidAccountData);
         }
-        if auction_info.exhibitor_ft_receiving_pubkey != *exhibitor_ft_receiving_account.key {
+        if auction_info.prices_in_reference {
+            // The winner pays in the mint they bid with, so any exhibitor's account of it receives the proceeds.
+            let exhibitor_ft_receiving_account_data =
+                TokenAccount::unpack(&exhibitor_ft_receiving_account.try_borrow_data()?)?;
+            if exhibitor_ft_receiving_account_data.owner != auction_info.exhibitor_pubkey {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        } else if auction_info.exhibitor_ft_receiving_pubkey != *exhibitor_ft_receiving_account.key
+        {
             return Err(ProgramError::InvalidAccountData);
         }
         if auction_info.highest_bidder_ft_temp_pubkey != *highest_bidder_ft_temp_account.key {
@@ -356,70 +1375,211 @@ impl Processor {
         if auction_info.highest_bidder_pubkey != *highest_bidder_account.key {
             return Err(ProgramError::InvalidAccountData);
         }
+        if auction_info.second_chance_until != 0
+            && auction_info.second_chance_until < clock.unix_timestamp
+        {
+            msg!("The second chance offer expired");
+            return Err(AuctionError::InactiveAuction.into());
+        }
 
         let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
         let token_program = next_account_info(account_info_iter)?;
         let pda_account = next_account_info(account_info_iter)?;
         let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
-
-        let exhibiting_nft_temp_account_data =
-            TokenAccount::unpack(&exhibiting_nft_temp_account.try_borrow_data()?)?;
-
-        let transfer_nft_to_highest_bidder_ix = spl_token::instruction::transfer(
-            token_program.key,
-            exhibiting_nft_temp_account.key,
-            &highest_bidder_nft_receiving_account.key,
-            &pda,
-            &[], 
-            exhibiting_nft_temp_account_data.amount,
-        )?;
-        msg!("Transferring NFT to the Highest Bidder...");
-        invoke_signed(
-            &transfer_nft_to_highest_bidder_ix,
-            &[
-                exhibiting_nft_temp_account.clone(),
-                highest_bidder_nft_receiving_account.clone(),
-                pda_account.clone(),
-                token_program.clone(),
-            ],
+        let vault_bump_seed = Self::vault_bump_seed(escrow_account.key, program_id);
+        let vault_signers_seeds: &[&[&[u8]]] = &[&[
+            &b"vault"[..],
+            escrow_account.key.as_ref(),
+            &[vault_bump_seed],
+        ]];
+        let (bid_authority, bid_authority_account, bid_signers_seeds) = Self::bid_authority(
+            &auction_info,
+            highest_bidder_ft_temp_account,
+            pda,
+            pda_account,
             signers_seeds,
-        )?;
+            vault_signers_seeds,
+        );
+        let treasury_ft_account =
+            Self::next_treasury_account(account_info_iter, auction_info.fee_bps)?;
+        let metadata_account = next_account_info(account_info_iter)?;
 
-        let highest_bidder_ft_temp_account_data =
-            TokenAccount::unpack(&highest_bidder_ft_temp_account.try_borrow_data()?)?;
-        let transfer_ft_to_exhibitor_ix = spl_token::instruction::transfer(
-            token_program.key,
-            highest_bidder_ft_temp_account.key,
-            &exhibitor_ft_receiving_account.key,
-            &pda,
-            &[], 
-            highest_bidder_ft_temp_account_data.amount,
-        )?;
-        msg!("Transferring FT to the Exhibitor...");
-        invoke_signed(
-            &transfer_ft_to_exhibitor_ix,
-            &[
-                highest_bidder_ft_temp_account.clone(),
-                exhibitor_ft_receiving_account.clone(),
-                pda_account.clone(),
-                token_program.clone(),
-            ],
-            signers_seeds,
-        )?;
+        let exhibiting_nft_temp_account_data =
+            TokenAccount::unpack(&exhibiting_nft_temp_account.try_borrow_data()?)?;
 
-        Self::close_temporary_ft(
+        match auction_info.kind {
+            AuctionKind::English => {
+                msg!("Transferring NFT to the Highest Bidder...");
+                Self::transfer_from_escrow(
+                    token_program,
+                    exhibiting_nft_temp_account,
+                    highest_bidder_nft_receiving_account,
+                    pda,
+                    pda_account,
+                    signers_seeds,
+                    exhibiting_nft_temp_account_data.amount,
+                )?;
+
+                // The highest bid is held in the vault of the auction, or a temporary FT account of a
+                // reference priced auction.
+                let proceeds = if auction_info.pays_in_lamports {
+                    let vault_account = highest_bidder_ft_temp_account;
+                    Proceeds::Lamports { vault_account }
+                } else {
+                    Proceeds::Token {
+                        token_program,
+                        temp_account: highest_bidder_ft_temp_account,
+                        pda: bid_authority,
+                        pda_account: bid_authority_account,
+                        signers_seeds: bid_signers_seeds,
+                    }
+                };
+                let amount = Self::escrowed_amount(
+                    &auction_info,
+                    highest_bidder_ft_temp_account,
+                    auction_info.price,
+                )?;
+
+                let metadata = Metadata::from_account_info_or_default(
+                    metadata_account,
+                    &exhibiting_nft_temp_account_data.mint,
+                )?;
+                let royalties =
+                    Self::pay_royalties(&proceeds, &metadata, account_info_iter, amount)?;
+
+                let amount = amount - royalties;
+                let fee = Self::pay_protocol_fee(
+                    &proceeds,
+                    treasury_ft_account,
+                    auction_info.fee_bps,
+                    &auction_info.treasury_pubkey,
+                    amount,
+                )?;
+                let referral =
+                    Self::pay_referral(&proceeds, &auction_info, account_info_iter, amount)?;
+                Self::pay_payees(
+                    &proceeds,
+                    &auction_info,
+                    exhibitor_ft_receiving_account,
+                    account_info_iter,
+                    amount - fee - referral,
+                )?;
+            }
+            AuctionKind::Reverse => {
+                // The winning seller escrowed the NFT and is paid their ask out of the exhibitor's budget.
+                let highest_bidder_ft_receiving_account = highest_bidder_nft_receiving_account;
+                let exhibitor_nft_receiving_account = next_account_info(account_info_iter)?;
+                let exhibitor_nft_receiving_account_data =
+                    TokenAccount::unpack(&exhibitor_nft_receiving_account.try_borrow_data()?)?;
+                if exhibitor_nft_receiving_account_data.owner != auction_info.exhibitor_pubkey {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                let highest_bidder_ft_temp_account_data =
+                    TokenAccount::unpack(&highest_bidder_ft_temp_account.try_borrow_data()?)?;
+
+                msg!("Transferring NFT to the Exhibitor...");
+                Self::transfer_from_escrow(
+                    token_program,
+                    highest_bidder_ft_temp_account,
+                    exhibitor_nft_receiving_account,
+                    pda,
+                    pda_account,
+                    signers_seeds,
+                    highest_bidder_ft_temp_account_data.amount,
+                )?;
+
+                let proceeds = Proceeds::Token {
+                    token_program,
+                    temp_account: exhibiting_nft_temp_account,
+                    pda,
+                    pda_account,
+                    signers_seeds,
+                };
+                let metadata = Metadata::from_account_info_or_default(
+                    metadata_account,
+                    &highest_bidder_ft_temp_account_data.mint,
+                )?;
+                let royalties = Self::pay_royalties(
+                    &proceeds,
+                    &metadata,
+                    account_info_iter,
+                    auction_info.price,
+                )?;
+
+                let amount = auction_info.price - royalties;
+                let fee = Self::pay_protocol_fee(
+                    &proceeds,
+                    treasury_ft_account,
+                    auction_info.fee_bps,
+                    &auction_info.treasury_pubkey,
+                    amount,
+                )?;
+                let referral =
+                    Self::pay_referral(&proceeds, &auction_info, account_info_iter, amount)?;
+
+                msg!("Transferring FT to the Lowest Bidder...");
+                proceeds.transfer(highest_bidder_ft_receiving_account, amount - fee - referral)?;
+
+                let unspent_budget = exhibiting_nft_temp_account_data.amount - auction_info.price;
+                if unspent_budget > 0 {
+                    msg!("Returning the unspent budget to the Exhibitor...");
+                    proceeds.transfer(exhibitor_ft_receiving_account, unspent_budget)?;
+                }
+            }
+            AuctionKind::Barter => {
+                msg!("Barter auctions are settled with AcceptOffer");
+                return Err(AuctionError::InvalidInstruction.into());
+            }
+        }
+
+        Self::refund_runner_up(
+            &auction_info,
+            account_info_iter,
             token_program,
-            highest_bidder_ft_temp_account,
-            highest_bidder_account,
             pda,
             pda_account,
             signers_seeds,
+            vault_signers_seeds,
         )?;
 
+        if auction_info.pays_in_lamports {
+            msg!("Closing the Vault Account...");
+            Self::close_state_account(highest_bidder_ft_temp_account, exhibitor_account)?;
+        } else if auction_info.holds_ft_in_vault() {
+            Self::close_vault(
+                &auction_info,
+                account_info_iter,
+                token_program,
+                highest_bidder_ft_temp_account,
+                exhibitor_account,
+                Some(exhibitor_ft_receiving_account),
+                vault_signers_seeds,
+            )?;
+        } else {
+            let bid_rent_payer_account = Self::rent_payer_account(
+                &auction_info.highest_bidder_rent_payer_pubkey,
+                highest_bidder_account,
+                account_info_iter,
+            )?;
+            Self::close_temporary_ft(
+                token_program,
+                highest_bidder_ft_temp_account,
+                bid_rent_payer_account,
+                pda,
+                pda_account,
+                signers_seeds,
+            )?;
+        }
+
+        let rent_payer_account = Self::rent_payer_account(
+            &auction_info.rent_payer_pubkey,
+            exhibitor_account,
+            account_info_iter,
+        )?;
         Self::close_escrow(
             token_program,
             exhibiting_nft_temp_account,
-            exhibitor_account,
+            rent_payer_account,
             pda,
             pda_account,
             escrow_account,
@@ -427,72 +1587,6398 @@ impl Processor {
         )
     }
 
-    fn close_escrow<'a, 'b>(
-        token_program: &'a AccountInfo<'b>,
-        exhibiting_nft_temp_account: &'a AccountInfo<'b>,
-        exhibitor_account: &'a AccountInfo<'b>,
-        pda: Pubkey,
-        pda_account: &'a AccountInfo<'b>,
-        escrow_account: &'a AccountInfo<'b>,
-        signers_seed: &[&[&[u8]]],
+    fn process_migrate_auction(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_account = next_account_info(account_info_iter)?;
+
+        if !payer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let escrow_account = next_account_info(account_info_iter)?;
+        let sys_var_rent_account = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(sys_var_rent_account)?;
+        let system_program = next_account_info(account_info_iter)?;
+
+        if escrow_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        if escrow_account.data_len() != LEGACY_AUCTION_LEN {
+            msg!("Only auctions of the legacy layout can be migrated");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        let auction_info = Auction::unpack_legacy(&escrow_account.try_borrow_data()?)?;
+
+        let top_up = rent
+            .minimum_balance(Auction::LEN)
+            .saturating_sub(escrow_account.lamports());
+        if top_up > 0 {
+            msg!("Funding the rent of the migrated Escrow Account...");
+            invoke(
+                &system_instruction::transfer(payer_account.key, escrow_account.key, top_up),
+                &[
+                    payer_account.clone(),
+                    escrow_account.clone(),
+                    system_program.clone(),
+                ],
+            )?;
+        }
+
+        escrow_account.realloc(Auction::LEN, true)?;
+        Auction::pack(auction_info, &mut escrow_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    fn process_exhibit_raffle(
+        accounts: &[AccountInfo],
+        ticket_price: u64,
+        sale_duration_sec: u64,
+        seed_hash: [u8; 32],
+        program_id: &Pubkey,
     ) -> ProgramResult {
-        let close_pdas_temp_acc_ix = spl_token::instruction::close_account(
-            token_program.key,
-            exhibiting_nft_temp_account.key,
-            exhibitor_account.key,
-            &pda,
-            &[], // owner_pubkey is default signer when the signer_pubkeys is empty.
-        )?;
-        msg!("Closing the exhibitor's NFT temporary account...");
-        invoke_signed(
-            &close_pdas_temp_acc_ix,
-            &[
-                exhibiting_nft_temp_account.clone(),
-                exhibitor_account.clone(),
-                pda_account.clone(),
-                token_program.clone(),
-            ],
-            signers_seed,
-        );
+        let account_info_iter = &mut accounts.iter();
+        let exhibitor_account = next_account_info(account_info_iter)?;
 
-        msg!("Closing the Escrow Account...");
-        **exhibitor_account.try_borrow_mut_lamports()? = exhibitor_account
-            .lamports()
-            .checked_add(escrow_account.lamports())
+        if !exhibitor_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let exhibitor_nft_account = next_account_info(account_info_iter)?;
+        let exhibitor_nft_temp_account = next_account_info(account_info_iter)?;
+        let exhibitor_ft_receiving_account = next_account_info(account_info_iter)?;
+        let proceeds_ft_temp_account = next_account_info(account_info_iter)?;
+
+        let raffle_account = next_account_info(account_info_iter)?;
+        let sys_var_rent_account = next_account_info(account_info_iter)?;
+
+        let rent = &Rent::from_account_info(sys_var_rent_account)?;
+        if !rent.is_exempt(raffle_account.lamports(), raffle_account.data_len()) {
+            return Err(AuctionError::NotRentExempt.into());
+        }
+        if Raffle::range_capacity(raffle_account.data_len()) == 0 {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+
+        let mut raffle_info = Raffle::unpack_header(&raffle_account.try_borrow_data()?)?;
+        if raffle_info.is_initialized() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if ticket_price == 0 {
+            msg!("Tickets can't be free");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+
+        raffle_info.is_initialized = true;
+        raffle_info.exhibitor_pubkey = *exhibitor_account.key;
+        raffle_info.exhibiting_nft_temp_pubkey = *exhibitor_nft_temp_account.key;
+        raffle_info.exhibitor_ft_receiving_pubkey = *exhibitor_ft_receiving_account.key;
+        raffle_info.proceeds_ft_temp_pubkey = *proceeds_ft_temp_account.key;
+        raffle_info.ticket_price = ticket_price;
+        raffle_info.end_at = Self::end_at(clock, sale_duration_sec)?;
+        raffle_info.seed_hash = seed_hash;
+        raffle_info.tickets_sold = 0;
+        raffle_info.range_count = 0;
+        raffle_info.winner_pubkey = Pubkey::default();
+        raffle_info.reveal_by = raffle_info
+            .end_at
+            .checked_add(RAFFLE_REVEAL_PERIOD_SEC)
             .ok_or(AuctionError::AmountOverflow)?;
-        **escrow_account.try_borrow_mut_lamports()? = 0;
-        *escrow_account.try_borrow_mut_data()? = &mut [];
+        raffle_info.draw_slot = 0;
+        raffle_info.tickets_refunded = 0;
 
-        Ok(())
+        let (pda, _bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+        Self::check_not_paused(&config_info)?;
+        Self::check_duration(&config_info, sale_duration_sec)?;
+        raffle_info.fee_bps = config_info.fee_bps;
+        raffle_info.treasury_pubkey = config_info.treasury_pubkey;
+        Raffle::pack_header(raffle_info, &mut raffle_account.try_borrow_mut_data()?)?;
+
+        msg!("Transferring the NFT to the Escrow Account...");
+        Self::escrow_tokens(
+            token_program,
+            exhibitor_nft_account,
+            exhibitor_nft_temp_account,
+            exhibitor_account,
+            pda,
+            1,
+        )?;
+        Self::assign_to_pda(
+            token_program,
+            proceeds_ft_temp_account,
+            exhibitor_account,
+            pda,
+        )
     }
 
-    fn close_temporary_ft<'a, 'b>(
-        token_program: &'a AccountInfo<'b>,
-        highest_bidder_ft_temp_account: &'a AccountInfo<'b>,
-        highest_bidder_account: &'a AccountInfo<'b>,
-        pda: Pubkey,
-        pda_account: &'a AccountInfo<'b>,
-        signers_seeds: &[&[&[u8]]],
+    fn process_buy_tickets(
+        accounts: &[AccountInfo],
+        count: u64,
+        program_id: &Pubkey,
     ) -> ProgramResult {
-        let close_highest_bidder_ft_temp_acc_ix = spl_token::instruction::close_account(
+        let account_info_iter = &mut accounts.iter();
+        let buyer_account = next_account_info(account_info_iter)?;
+
+        if !buyer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let buyer_ft_account = next_account_info(account_info_iter)?;
+        let proceeds_ft_temp_account = next_account_info(account_info_iter)?;
+        let raffle_account = next_account_info(account_info_iter)?;
+        let mut raffle_info = Raffle::unpack_header(&raffle_account.try_borrow_data()?)?;
+        if !raffle_info.is_initialized() {
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if raffle_info.end_at <= clock.unix_timestamp {
+            return Err(AuctionError::InactiveAuction.into());
+        }
+        if count == 0 {
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if raffle_info.proceeds_ft_temp_pubkey != *proceeds_ft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if raffle_info.exhibitor_pubkey == *buyer_account.key {
+            msg!("The exhibitor can't buy tickets of their own raffle");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+
+        let amount = raffle_info
+            .ticket_price
+            .checked_mul(count)
+            .ok_or(AuctionError::AmountOverflow)?;
+        let tickets_sold = raffle_info
+            .tickets_sold
+            .checked_add(count)
+            .ok_or(AuctionError::AmountOverflow)?;
+
+        let token_program = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        Self::check_not_paused(&Self::unpack_config(config_account, program_id)?)?;
+        let transfer_to_escrow_ix = spl_token::instruction::transfer(
             token_program.key,
-            highest_bidder_ft_temp_account.key,
-            highest_bidder_account.key,
-            &pda,
-            &[],
+            buyer_ft_account.key,
+            proceeds_ft_temp_account.key,
+            buyer_account.key,
+            &[], // authority_pubkey is default signer when the signer_pubkeys is empty.
+            amount,
         )?;
-        msg!("Closing the Highest Bidder's FT temporary account...");
-        invoke_signed(
-            &close_highest_bidder_ft_temp_acc_ix,
+        msg!("Transferring FT to the Escrow Account from the buyer...");
+        invoke(
+            &transfer_to_escrow_ix,
             &[
-                highest_bidder_ft_temp_account.clone(),
-                highest_bidder_account.clone(),
-                pda_account.clone(),
+                buyer_ft_account.clone(),
+                proceeds_ft_temp_account.clone(),
+                buyer_account.clone(),
                 token_program.clone(),
             ],
-            signers_seeds,
+        )?;
+
+        let mut raffle_data = raffle_account.try_borrow_mut_data()?;
+        let range_count = raffle_info.range_count as usize;
+        let last_range = match range_count {
+            0 => None,
+            _ => Some(TicketRange::unpack_unchecked(
+                &raffle_data[Raffle::range_span(range_count - 1)],
+            )?),
+        };
+        // Consecutive purchases by the same buyer extend the last range instead of adding one.
+        let range_index = match last_range {
+            Some(range) if range.buyer_pubkey == *buyer_account.key => range_count - 1,
+            _ => {
+                if range_count >= Raffle::range_capacity(raffle_data.len()) {
+                    return Err(AuctionError::TicketLedgerFull.into());
+                }
+                raffle_info.range_count += 1;
+                range_count
+            }
+        };
+        TicketRange::pack(
+            TicketRange {
+                buyer_pubkey: *buyer_account.key,
+                end: tickets_sold,
+            },
+            &mut raffle_data[Raffle::range_span(range_index)],
+        )?;
+
+        msg!(
+            "Sold tickets {} to {}",
+            raffle_info.tickets_sold,
+            tickets_sold - 1
         );
+        raffle_info.tickets_sold = tickets_sold;
+        Raffle::pack_header(raffle_info, &mut raffle_data)
+    }
 
-        Ok(())
+    fn process_record_draw_slot(accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let exhibitor_account = next_account_info(account_info_iter)?;
+
+        if !exhibitor_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let raffle_account = next_account_info(account_info_iter)?;
+        let mut raffle_info = Raffle::unpack_header(&raffle_account.try_borrow_data()?)?;
+        if !raffle_info.is_initialized() {
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if raffle_info.exhibitor_pubkey != *exhibitor_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if raffle_info.end_at > clock.unix_timestamp {
+            return Err(AuctionError::ActiveAuction.into());
+        }
+        if raffle_info.winner_pubkey != Pubkey::default() {
+            return Err(AuctionError::AlreadyDrawn.into());
+        }
+        if raffle_info.tickets_sold == 0 {
+            return Err(AuctionError::NoBidderFound.into());
+        }
+        // Recording the slot again once its hash is known would let the exhibitor redraw an outcome they dislike.
+        if raffle_info.draw_slot != 0 {
+            msg!("The draw slot is already recorded");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+
+        msg!("Drawing from the hash of slot {}", clock.slot);
+        raffle_info.draw_slot = clock.slot;
+        Raffle::pack_header(raffle_info, &mut raffle_account.try_borrow_mut_data()?)
+    }
+
+    fn process_draw(accounts: &[AccountInfo], seed: [u8; 32]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let raffle_account = next_account_info(account_info_iter)?;
+        let mut raffle_info = Raffle::unpack_header(&raffle_account.try_borrow_data()?)?;
+        if !raffle_info.is_initialized() {
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if raffle_info.end_at > clock.unix_timestamp {
+            msg!(
+                "Raffle will end in {} seconds",
+                (raffle_info.end_at - clock.unix_timestamp)
+            );
+            return Err(AuctionError::ActiveAuction.into());
+        }
+        if raffle_info.winner_pubkey != Pubkey::default() {
+            return Err(AuctionError::AlreadyDrawn.into());
+        }
+        if raffle_info.reveal_by < clock.unix_timestamp {
+            msg!("The seed wasn't revealed in time, the tickets can be refunded");
+            return Err(AuctionError::InactiveAuction.into());
+        }
+        if raffle_info.tickets_sold == 0 {
+            return Err(AuctionError::NoBidderFound.into());
+        }
+        if hash(&seed).to_bytes() != raffle_info.seed_hash {
+            return Err(AuctionError::InvalidSeed.into());
+        }
+
+        // The seed was committed before any purchase and the draw slot after the last one, whose hash nobody
+        // knew while tickets could be bought, so neither the exhibitor nor the buyers can steer the outcome.
+        let slot_hashes_account = next_account_info(account_info_iter)?;
+        let slot_hash = Self::slot_hash(slot_hashes_account, raffle_info.draw_slot)?;
+        let randomness = hashv(&[&seed, &slot_hash, raffle_account.key.as_ref()]);
+        let winning_ticket =
+            u64::from_le_bytes(*array_ref![randomness.as_ref(), 0, 8]) % raffle_info.tickets_sold;
+
+        let raffle_data = raffle_account.try_borrow_data()?;
+        let (mut low, mut high) = (0, raffle_info.range_count as usize);
+        while low < high {
+            let mid = (low + high) / 2;
+            let range = TicketRange::unpack_unchecked(&raffle_data[Raffle::range_span(mid)])?;
+            if range.end <= winning_ticket {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        let winning_range = TicketRange::unpack_unchecked(&raffle_data[Raffle::range_span(low)])?;
+        drop(raffle_data);
+
+        msg!(
+            "Ticket {} drawn, won by {}",
+            winning_ticket,
+            winning_range.buyer_pubkey
+        );
+        raffle_info.winner_pubkey = winning_range.buyer_pubkey;
+        Raffle::pack_header(raffle_info, &mut raffle_account.try_borrow_mut_data()?)
+    }
+
+    /// Reads the hash of `slot` from the slot hashes sysvar, which only keeps the most recent slots.
+    fn slot_hash(slot_hashes_account: &AccountInfo, slot: u64) -> Result<[u8; 32], ProgramError> {
+        if *slot_hashes_account.key != slot_hashes::id() {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if slot == 0 {
+            msg!("The draw slot must be recorded with RecordDrawSlot first");
+            return Err(AuctionError::SlotHashUnavailable.into());
+        }
+        // The sysvar is too large to deserialize, its entries are read in place, most recent slot first.
+        let data = slot_hashes_account.try_borrow_data()?;
+        let entry_count = data
+            .get(..8)
+            .map(|count| u64::from_le_bytes(*array_ref![count, 0, 8]))
+            .ok_or(ProgramError::InvalidAccountData)?;
+        data.get(8..)
+            .ok_or(ProgramError::InvalidAccountData)?
+            .chunks_exact(40)
+            .take(entry_count as usize)
+            .find(|entry| u64::from_le_bytes(*array_ref![entry, 0, 8]) == slot)
+            .map(|entry| *array_ref![entry, 8, 32])
+            .ok_or_else(|| {
+                msg!("The hash of slot {} is no longer available", slot);
+                AuctionError::SlotHashUnavailable.into()
+            })
+    }
+
+    fn process_settle_raffle(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let settler_account = next_account_info(account_info_iter)?;
+
+        if !settler_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let exhibitor_account = next_account_info(account_info_iter)?;
+        let exhibiting_nft_temp_account = next_account_info(account_info_iter)?;
+        let exhibitor_ft_receiving_account = next_account_info(account_info_iter)?;
+        let proceeds_ft_temp_account = next_account_info(account_info_iter)?;
+        let winner_nft_receiving_account = next_account_info(account_info_iter)?;
+        let raffle_account = next_account_info(account_info_iter)?;
+        let raffle_info = Raffle::unpack_header(&raffle_account.try_borrow_data()?)?;
+        if !raffle_info.is_initialized() {
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        if raffle_info.winner_pubkey == Pubkey::default() {
+            return Err(AuctionError::NotDrawn.into());
+        }
+        if *settler_account.key != raffle_info.winner_pubkey
+            && *settler_account.key != raffle_info.exhibitor_pubkey
+        {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if raffle_info.exhibitor_pubkey != *exhibitor_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if raffle_info.exhibiting_nft_temp_pubkey != *exhibiting_nft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if raffle_info.exhibitor_ft_receiving_pubkey != *exhibitor_ft_receiving_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if raffle_info.proceeds_ft_temp_pubkey != *proceeds_ft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let winner_nft_receiving_account_data =
+            TokenAccount::unpack(&winner_nft_receiving_account.try_borrow_data()?)?;
+        if winner_nft_receiving_account_data.owner != raffle_info.winner_pubkey {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+        let treasury_ft_account =
+            Self::next_treasury_account(account_info_iter, raffle_info.fee_bps)?;
+
+        let exhibiting_nft_temp_account_data =
+            TokenAccount::unpack(&exhibiting_nft_temp_account.try_borrow_data()?)?;
+        msg!("Transferring NFT to the Winner...");
+        Self::transfer_from_escrow(
+            token_program,
+            exhibiting_nft_temp_account,
+            winner_nft_receiving_account,
+            pda,
+            pda_account,
+            signers_seeds,
+            exhibiting_nft_temp_account_data.amount,
+        )?;
+
+        let proceeds_ft_temp_account_data =
+            TokenAccount::unpack(&proceeds_ft_temp_account.try_borrow_data()?)?;
+        msg!("Transferring the proceeds to the Exhibitor...");
+        Self::transfer_proceeds(
+            &Proceeds::Token {
+                token_program,
+                temp_account: proceeds_ft_temp_account,
+                pda,
+                pda_account,
+                signers_seeds,
+            },
+            exhibitor_ft_receiving_account,
+            treasury_ft_account,
+            raffle_info.fee_bps,
+            &raffle_info.treasury_pubkey,
+            proceeds_ft_temp_account_data.amount,
+        )?;
+
+        Self::close_temporary_ft(
+            token_program,
+            proceeds_ft_temp_account,
+            exhibitor_account,
+            pda,
+            pda_account,
+            signers_seeds,
+        )?;
+
+        Self::close_escrow(
+            token_program,
+            exhibiting_nft_temp_account,
+            exhibitor_account,
+            pda,
+            pda_account,
+            raffle_account,
+            signers_seeds,
+        )
+    }
+
+    fn process_cancel_raffle(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let exhibitor_account = next_account_info(account_info_iter)?;
+
+        if !exhibitor_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let exhibiting_nft_temp_account = next_account_info(account_info_iter)?;
+        let exhibiting_nft_returning_account = next_account_info(account_info_iter)?;
+        let proceeds_ft_temp_account = next_account_info(account_info_iter)?;
+        let raffle_account = next_account_info(account_info_iter)?;
+        let mut raffle_info = Raffle::unpack_header(&raffle_account.try_borrow_data()?)?;
+        if !raffle_info.is_initialized() {
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        if raffle_info.exhibitor_pubkey != *exhibitor_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if raffle_info.proceeds_ft_temp_pubkey != *proceeds_ft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+
+        // Once tickets are sold, the raffle can only be cancelled after the exhibitor missed the reveal.
+        if raffle_info.tickets_sold != 0 {
+            let sys_var_clock_account = next_account_info(account_info_iter)?;
+            let clock = &Clock::from_account_info(sys_var_clock_account)?;
+            if raffle_info.winner_pubkey != Pubkey::default() {
+                return Err(AuctionError::AlreadyDrawn.into());
+            }
+            if raffle_info.reveal_by >= clock.unix_timestamp {
+                return Err(AuctionError::AlreadyBid.into());
+            }
+        }
+
+        if raffle_info.exhibiting_nft_temp_pubkey != Pubkey::default() {
+            if raffle_info.exhibiting_nft_temp_pubkey != *exhibiting_nft_temp_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let exhibiting_nft_temp_account_data =
+                TokenAccount::unpack(&exhibiting_nft_temp_account.try_borrow_data()?)?;
+            msg!("Transferring NFT to the Exhibitor...");
+            Self::transfer_from_escrow(
+                token_program,
+                exhibiting_nft_temp_account,
+                exhibiting_nft_returning_account,
+                pda,
+                pda_account,
+                signers_seeds,
+                exhibiting_nft_temp_account_data.amount,
+            )?;
+            Self::close_temporary_ft(
+                token_program,
+                exhibiting_nft_temp_account,
+                exhibitor_account,
+                pda,
+                pda_account,
+                signers_seeds,
+            )?;
+            raffle_info.exhibiting_nft_temp_pubkey = Pubkey::default();
+        }
+
+        if raffle_info.tickets_refunded < raffle_info.tickets_sold {
+            msg!(
+                "{} tickets are left to refund before the raffle can be closed",
+                raffle_info.tickets_sold - raffle_info.tickets_refunded
+            );
+            return Raffle::pack_header(raffle_info, &mut raffle_account.try_borrow_mut_data()?);
+        }
+
+        // Anything left in the proceeds account wasn't paid for tickets.
+        let proceeds_ft_temp_account_data =
+            TokenAccount::unpack(&proceeds_ft_temp_account.try_borrow_data()?)?;
+        if proceeds_ft_temp_account_data.amount > 0 {
+            let exhibitor_ft_receiving_account = next_account_info(account_info_iter)?;
+            if raffle_info.exhibitor_ft_receiving_pubkey != *exhibitor_ft_receiving_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            msg!("Transferring the remaining FT to the Exhibitor...");
+            Self::transfer_from_escrow(
+                token_program,
+                proceeds_ft_temp_account,
+                exhibitor_ft_receiving_account,
+                pda,
+                pda_account,
+                signers_seeds,
+                proceeds_ft_temp_account_data.amount,
+            )?;
+        }
+
+        Self::close_temporary_ft(
+            token_program,
+            proceeds_ft_temp_account,
+            exhibitor_account,
+            pda,
+            pda_account,
+            signers_seeds,
+        )?;
+
+        msg!("Closing the Raffle Account...");
+        Self::close_state_account(raffle_account, exhibitor_account)
+    }
+
+    fn process_refund_tickets(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let buyer_account = next_account_info(account_info_iter)?;
+
+        if !buyer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let buyer_ft_returning_account = next_account_info(account_info_iter)?;
+        let proceeds_ft_temp_account = next_account_info(account_info_iter)?;
+        let raffle_account = next_account_info(account_info_iter)?;
+        let mut raffle_info = Raffle::unpack_header(&raffle_account.try_borrow_data()?)?;
+        if !raffle_info.is_initialized() {
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if raffle_info.proceeds_ft_temp_pubkey != *proceeds_ft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if raffle_info.winner_pubkey != Pubkey::default() {
+            return Err(AuctionError::AlreadyDrawn.into());
+        }
+        if raffle_info.reveal_by >= clock.unix_timestamp {
+            msg!(
+                "The exhibitor can reveal the seed for {} more seconds",
+                (raffle_info.reveal_by - clock.unix_timestamp)
+            );
+            return Err(AuctionError::ActiveAuction.into());
+        }
+
+        let mut raffle_data = raffle_account.try_borrow_mut_data()?;
+        let mut refunded: u64 = 0;
+        let mut start = 0;
+        for index in 0..raffle_info.range_count as usize {
+            let mut range = TicketRange::unpack_unchecked(&raffle_data[Raffle::range_span(index)])?;
+            let end = range.end;
+            if range.buyer_pubkey == *buyer_account.key {
+                refunded += end - start;
+                range.buyer_pubkey = Pubkey::default();
+                TicketRange::pack(range, &mut raffle_data[Raffle::range_span(index)])?;
+            }
+            start = end;
+        }
+        if refunded == 0 {
+            msg!("No tickets of the buyer are left to refund");
+            return Err(AuctionError::NoBidderFound.into());
+        }
+        raffle_info.tickets_refunded = raffle_info
+            .tickets_refunded
+            .checked_add(refunded)
+            .ok_or(AuctionError::AmountOverflow)?;
+        let amount = raffle_info
+            .ticket_price
+            .checked_mul(refunded)
+            .ok_or(AuctionError::AmountOverflow)?;
+        Raffle::pack_header(raffle_info, &mut raffle_data)?;
+        drop(raffle_data);
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+
+        msg!("Refunding {} tickets to the buyer...", refunded);
+        Self::transfer_from_escrow(
+            token_program,
+            proceeds_ft_temp_account,
+            buyer_ft_returning_account,
+            pda,
+            pda_account,
+            signers_seeds,
+            amount,
+        )
+    }
+
+    fn process_exhibit_gda(
+        accounts: &[AccountInfo],
+        initial_price: u64,
+        price_scale: u64,
+        decay_rate: u64,
+        max_supply: u64,
+        mints_on_purchase: bool,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let exhibitor_account = next_account_info(account_info_iter)?;
+
+        if !exhibitor_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let supply_account = next_account_info(account_info_iter)?;
+        let exhibitor_ft_receiving_account = next_account_info(account_info_iter)?;
+
+        let gda_account = next_account_info(account_info_iter)?;
+        let sys_var_rent_account = next_account_info(account_info_iter)?;
+
+        let rent = &Rent::from_account_info(sys_var_rent_account)?;
+        if !rent.is_exempt(gda_account.lamports(), gda_account.data_len()) {
+            return Err(AuctionError::NotRentExempt.into());
+        }
+
+        let mut gda_info = GradualDutchAuction::unpack_unchecked(&gda_account.try_borrow_data()?)?;
+        if gda_info.is_initialized() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        if price_scale < FIXED_POINT_ONE || decay_rate > FIXED_POINT_ONE {
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+
+        let max_supply = if mints_on_purchase {
+            max_supply
+        } else {
+            TokenAccount::unpack(&supply_account.try_borrow_data()?)?.amount
+        };
+        if max_supply == 0 {
+            return Err(AuctionError::InsufficientSupply.into());
+        }
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        gda_info.is_initialized = true;
+        gda_info.exhibitor_pubkey = *exhibitor_account.key;
+        gda_info.supply_pubkey = *supply_account.key;
+        gda_info.mints_on_purchase = mints_on_purchase;
+        gda_info.exhibitor_ft_receiving_pubkey = *exhibitor_ft_receiving_account.key;
+        gda_info.initial_price = initial_price;
+        gda_info.price_scale = price_scale;
+        gda_info.decay_rate = decay_rate;
+        gda_info.start_at = clock.unix_timestamp;
+        gda_info.sold = 0;
+        gda_info.max_supply = max_supply;
+
+        let (pda, _bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+        Self::check_not_paused(&config_info)?;
+        gda_info.fee_bps = config_info.fee_bps;
+        gda_info.treasury_pubkey = config_info.treasury_pubkey;
+        GradualDutchAuction::pack(gda_info, &mut gda_account.try_borrow_mut_data()?)?;
+
+        if !mints_on_purchase {
+            return Self::assign_to_pda(token_program, supply_account, exhibitor_account, pda);
+        }
+
+        let authority_change_ix = spl_token::instruction::set_authority(
+            token_program.key,
+            supply_account.key,
+            Some(&pda),
+            spl_token::instruction::AuthorityType::MintTokens,
+            exhibitor_account.key,
+            &[], // owner_pubkey is default signer when the signer_pubkeys is empty.
+        )?;
+        msg!("Changing mint authority of the series mint...");
+        invoke(
+            &authority_change_ix,
+            &[
+                supply_account.clone(),
+                exhibitor_account.clone(),
+                token_program.clone(),
+            ],
+        )
+    }
+
+    fn process_purchase_gda(
+        accounts: &[AccountInfo],
+        quantity: u64,
+        max_total_price: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let buyer_account = next_account_info(account_info_iter)?;
+
+        if !buyer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let buyer_ft_account = next_account_info(account_info_iter)?;
+        let exhibitor_ft_receiving_account = next_account_info(account_info_iter)?;
+        let buyer_receiving_account = next_account_info(account_info_iter)?;
+        let supply_account = next_account_info(account_info_iter)?;
+        let gda_account = next_account_info(account_info_iter)?;
+        let mut gda_info = GradualDutchAuction::unpack(&gda_account.try_borrow_data()?)?;
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if gda_info.exhibitor_ft_receiving_pubkey != *exhibitor_ft_receiving_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if gda_info.supply_pubkey != *supply_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if quantity == 0 {
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if quantity > gda_info.max_supply - gda_info.sold {
+            return Err(AuctionError::InsufficientSupply.into());
+        }
+
+        let elapsed_sec = clock
+            .unix_timestamp
+            .saturating_sub(gda_info.start_at)
+            .max(0) as u64;
+        let total_price = Self::gda_purchase_price(&gda_info, quantity, elapsed_sec)?;
+        if total_price > max_total_price {
+            msg!("Current price for {} items is {}", quantity, total_price);
+            return Err(AuctionError::PriceLimitExceeded.into());
+        }
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        Self::check_not_paused(&Self::unpack_config(config_account, program_id)?)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+        let treasury_ft_account = Self::next_treasury_account(account_info_iter, gda_info.fee_bps)?;
+
+        let fee = Self::protocol_fee(gda_info.fee_bps, total_price);
+        if let Some(treasury_ft_account) = treasury_ft_account {
+            let treasury_ft_account_data =
+                TokenAccount::unpack(&treasury_ft_account.try_borrow_data()?)?;
+            if treasury_ft_account_data.owner != gda_info.treasury_pubkey {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if fee > 0 {
+                let transfer_to_treasury_ix = spl_token::instruction::transfer(
+                    token_program.key,
+                    buyer_ft_account.key,
+                    treasury_ft_account.key,
+                    buyer_account.key,
+                    &[], // authority_pubkey is default signer when the signer_pubkeys is empty.
+                    fee,
+                )?;
+                msg!("Transferring the protocol fee to the Treasury...");
+                invoke(
+                    &transfer_to_treasury_ix,
+                    &[
+                        buyer_ft_account.clone(),
+                        treasury_ft_account.clone(),
+                        buyer_account.clone(),
+                        token_program.clone(),
+                    ],
+                )?;
+            }
+        }
+
+        let transfer_to_exhibitor_ix = spl_token::instruction::transfer(
+            token_program.key,
+            buyer_ft_account.key,
+            exhibitor_ft_receiving_account.key,
+            buyer_account.key,
+            &[], // authority_pubkey is default signer when the signer_pubkeys is empty.
+            total_price - fee,
+        )?;
+        msg!("Transferring FT to the Exhibitor...");
+        invoke(
+            &transfer_to_exhibitor_ix,
+            &[
+                buyer_ft_account.clone(),
+                exhibitor_ft_receiving_account.clone(),
+                buyer_account.clone(),
+                token_program.clone(),
+            ],
+        )?;
+
+        if gda_info.mints_on_purchase {
+            let mint_to_buyer_ix = spl_token::instruction::mint_to(
+                token_program.key,
+                supply_account.key,
+                buyer_receiving_account.key,
+                &pda,
+                &[], // owner_pubkey is default signer when the signer_pubkeys is empty.
+                quantity,
+            )?;
+            msg!("Minting the items to the buyer...");
+            invoke_signed(
+                &mint_to_buyer_ix,
+                &[
+                    supply_account.clone(),
+                    buyer_receiving_account.clone(),
+                    pda_account.clone(),
+                    token_program.clone(),
+                ],
+                signers_seeds,
+            )?;
+        } else {
+            msg!("Transferring the items to the buyer...");
+            Self::transfer_from_escrow(
+                token_program,
+                supply_account,
+                buyer_receiving_account,
+                pda,
+                pda_account,
+                signers_seeds,
+                quantity,
+            )?;
+        }
+
+        msg!("Sold {} items for {}", quantity, total_price);
+        gda_info.sold += quantity;
+        GradualDutchAuction::pack(gda_info, &mut gda_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    fn process_close_gda(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let exhibitor_account = next_account_info(account_info_iter)?;
+
+        if !exhibitor_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let supply_account = next_account_info(account_info_iter)?;
+        let gda_account = next_account_info(account_info_iter)?;
+        let gda_info = GradualDutchAuction::unpack(&gda_account.try_borrow_data()?)?;
+
+        if gda_info.exhibitor_pubkey != *exhibitor_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if gda_info.supply_pubkey != *supply_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+
+        if gda_info.mints_on_purchase {
+            let authority_change_ix = spl_token::instruction::set_authority(
+                token_program.key,
+                supply_account.key,
+                Some(exhibitor_account.key),
+                spl_token::instruction::AuthorityType::MintTokens,
+                &pda,
+                &[], // owner_pubkey is default signer when the signer_pubkeys is empty.
+            )?;
+            msg!("Returning mint authority of the series mint...");
+            invoke_signed(
+                &authority_change_ix,
+                &[
+                    supply_account.clone(),
+                    pda_account.clone(),
+                    token_program.clone(),
+                ],
+                signers_seeds,
+            )?;
+
+            msg!("Closing the Escrow Account...");
+            return Self::close_state_account(gda_account, exhibitor_account);
+        }
+
+        let exhibitor_receiving_account = next_account_info(account_info_iter)?;
+        let supply_account_data = TokenAccount::unpack(&supply_account.try_borrow_data()?)?;
+        if supply_account_data.amount > 0 {
+            msg!("Transferring the unsold items to the Exhibitor...");
+            Self::transfer_from_escrow(
+                token_program,
+                supply_account,
+                exhibitor_receiving_account,
+                pda,
+                pda_account,
+                signers_seeds,
+                supply_account_data.amount,
+            )?;
+        }
+
+        Self::close_escrow(
+            token_program,
+            supply_account,
+            exhibitor_account,
+            pda,
+            pda_account,
+            gda_account,
+            signers_seeds,
+        )
+    }
+
+    /// Total price of the next `quantity` items, each priced at
+    /// `initial_price * price_scale ^ sold * decay_rate ^ elapsed_sec`.
+    fn gda_purchase_price(
+        gda_info: &GradualDutchAuction,
+        quantity: u64,
+        elapsed_sec: u64,
+    ) -> Result<u64, ProgramError> {
+        let one = FIXED_POINT_ONE as u128;
+        let price_scale = gda_info.price_scale as u128;
+        let curve = Self::fixed_mul(
+            Self::fixed_pow(price_scale, gda_info.sold)?,
+            Self::fixed_pow(gda_info.decay_rate as u128, elapsed_sec)?,
+        )?;
+
+        let item_price = (gda_info.initial_price as u128)
+            .checked_mul(curve)
+            .ok_or(AuctionError::AmountOverflow)?;
+        // The item prices form a geometric series of ratio `price_scale`, summed in closed form
+        // as (price_scale^quantity - 1) / (price_scale - 1) so the cost doesn't grow with the quantity.
+        let series = if price_scale == one {
+            (quantity as u128).checked_mul(one)
+        } else {
+            let numerator = Self::fixed_pow(price_scale, quantity)? - one;
+            let denominator = price_scale - one;
+            (numerator / denominator)
+                .checked_mul(one)
+                .and_then(|high| {
+                    (numerator % denominator)
+                        .checked_mul(one)
+                        .map(|low| (high, low.div_ceil(denominator)))
+                })
+                .and_then(|(high, low)| high.checked_add(low))
+        }
+        .ok_or(AuctionError::AmountOverflow)?;
+        let total_price = Self::fixed_mul(item_price, series)?;
+
+        // Round up so the exhibitor never receives less than the curve price.
+        u64::try_from(total_price.div_ceil(one)).map_err(|_| AuctionError::AmountOverflow.into())
+    }
+
+    fn fixed_mul(a: u128, b: u128) -> Result<u128, ProgramError> {
+        let one = FIXED_POINT_ONE as u128;
+        // Split `a` so the intermediate products stay within u128 for large prices.
+        (a / one)
+            .checked_mul(b)
+            .and_then(|high| (a % one).checked_mul(b).map(|low| (high, low / one)))
+            .and_then(|(high, low)| high.checked_add(low))
+            .ok_or_else(|| AuctionError::AmountOverflow.into())
+    }
+
+    fn fixed_pow(mut base: u128, mut exponent: u64) -> Result<u128, ProgramError> {
+        let mut result = FIXED_POINT_ONE as u128;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = Self::fixed_mul(result, base)?;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = Self::fixed_mul(base, base)?;
+            }
+        }
+        Ok(result)
+    }
+
+    fn process_open_call_auction(accounts: &[AccountInfo], epoch_duration: u64) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_account = next_account_info(account_info_iter)?;
+
+        if !authority_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let collection_mint_account = next_account_info(account_info_iter)?;
+        let ft_mint_account = next_account_info(account_info_iter)?;
+
+        let call_auction_account = next_account_info(account_info_iter)?;
+        let sys_var_rent_account = next_account_info(account_info_iter)?;
+
+        let rent = &Rent::from_account_info(sys_var_rent_account)?;
+        if !rent.is_exempt(
+            call_auction_account.lamports(),
+            call_auction_account.data_len(),
+        ) {
+            return Err(AuctionError::NotRentExempt.into());
+        }
+        // The book must at least hold an ask and a bid.
+        if CallAuction::entry_capacity(call_auction_account.data_len()) < 2 {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+
+        let mut call_auction_info =
+            CallAuction::unpack_header(&call_auction_account.try_borrow_data()?)?;
+        if call_auction_info.is_initialized() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        if epoch_duration == 0 {
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        call_auction_info.is_initialized = true;
+        call_auction_info.authority_pubkey = *authority_account.key;
+        call_auction_info.collection_pubkey = *collection_mint_account.key;
+        call_auction_info.ft_mint_pubkey = *ft_mint_account.key;
+        call_auction_info.epoch_duration = epoch_duration;
+        call_auction_info.epoch = 0;
+        call_auction_info.epoch_end_at = Self::end_at(clock, epoch_duration)?;
+        call_auction_info.open_asks = 0;
+        call_auction_info.open_bids = 0;
+        call_auction_info.entry_count = 0;
+        CallAuction::pack_header(
+            call_auction_info,
+            &mut call_auction_account.try_borrow_mut_data()?,
+        )?;
+        Ok(())
+    }
+
+    fn process_post_ask(
+        accounts: &[AccountInfo],
+        price: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let seller_account = next_account_info(account_info_iter)?;
+
+        if !seller_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let seller_nft_account = next_account_info(account_info_iter)?;
+        let seller_nft_temp_account = next_account_info(account_info_iter)?;
+        let seller_ft_receiving_account = next_account_info(account_info_iter)?;
+        let metadata_account = next_account_info(account_info_iter)?;
+
+        let call_auction_account = next_account_info(account_info_iter)?;
+        let mut call_auction_info = Self::unpack_call_auction(call_auction_account, program_id)?;
+
+        let order_account = next_account_info(account_info_iter)?;
+        let sys_var_rent_account = next_account_info(account_info_iter)?;
+
+        let rent = &Rent::from_account_info(sys_var_rent_account)?;
+        if !rent.is_exempt(order_account.lamports(), order_account.data_len()) {
+            return Err(AuctionError::NotRentExempt.into());
+        }
+
+        let mut order_info = CallOrder::unpack_unchecked(&order_account.try_borrow_data()?)?;
+        if order_info.is_initialized() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if call_auction_info.epoch_end_at <= clock.unix_timestamp {
+            return Err(AuctionError::InactiveAuction.into());
+        }
+
+        let seller_nft_account_data = TokenAccount::unpack(&seller_nft_account.try_borrow_data()?)?;
+        let metadata =
+            Metadata::from_account_info(metadata_account, &seller_nft_account_data.mint)?;
+        if !metadata.is_verified_member_of(&call_auction_info.collection_pubkey) {
+            return Err(AuctionError::CollectionMismatch.into());
+        }
+
+        order_info.is_initialized = true;
+        order_info.call_auction_pubkey = *call_auction_account.key;
+        order_info.side = OrderSide::Ask;
+        order_info.owner_pubkey = *seller_account.key;
+        order_info.escrow_temp_pubkey = *seller_nft_temp_account.key;
+        order_info.receiving_pubkey = *seller_ft_receiving_account.key;
+        order_info.returning_pubkey = *seller_nft_account.key;
+        order_info.limit_price = price;
+        CallOrder::pack(order_info, &mut order_account.try_borrow_mut_data()?)?;
+
+        call_auction_info.open_asks = call_auction_info
+            .open_asks
+            .checked_add(1)
+            .ok_or(AuctionError::AmountOverflow)?;
+        Self::push_book_entry(
+            call_auction_account,
+            call_auction_info,
+            order_account.key,
+            OrderSide::Ask,
+            price,
+        )?;
+
+        let (pda, _bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        Self::check_not_paused(&Self::unpack_config(config_account, program_id)?)?;
+
+        msg!("Transferring the NFT to the Escrow Account...");
+        Self::escrow_tokens(
+            token_program,
+            seller_nft_account,
+            seller_nft_temp_account,
+            seller_account,
+            pda,
+            1,
+        )
+    }
+
+    fn process_post_bid(
+        accounts: &[AccountInfo],
+        price: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let buyer_account = next_account_info(account_info_iter)?;
+
+        if !buyer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let buyer_ft_account = next_account_info(account_info_iter)?;
+        let buyer_ft_temp_account = next_account_info(account_info_iter)?;
+
+        let call_auction_account = next_account_info(account_info_iter)?;
+        let mut call_auction_info = Self::unpack_call_auction(call_auction_account, program_id)?;
+
+        let order_account = next_account_info(account_info_iter)?;
+        let sys_var_rent_account = next_account_info(account_info_iter)?;
+
+        let rent = &Rent::from_account_info(sys_var_rent_account)?;
+        if !rent.is_exempt(order_account.lamports(), order_account.data_len()) {
+            return Err(AuctionError::NotRentExempt.into());
+        }
+
+        let mut order_info = CallOrder::unpack_unchecked(&order_account.try_borrow_data()?)?;
+        if order_info.is_initialized() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if call_auction_info.epoch_end_at <= clock.unix_timestamp {
+            return Err(AuctionError::InactiveAuction.into());
+        }
+        if price == 0 {
+            return Err(AuctionError::InsufficientBidPrice.into());
+        }
+
+        let buyer_ft_temp_account_data =
+            TokenAccount::unpack(&buyer_ft_temp_account.try_borrow_data()?)?;
+        if buyer_ft_temp_account_data.mint != call_auction_info.ft_mint_pubkey {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        order_info.is_initialized = true;
+        order_info.call_auction_pubkey = *call_auction_account.key;
+        order_info.side = OrderSide::Bid;
+        order_info.owner_pubkey = *buyer_account.key;
+        order_info.escrow_temp_pubkey = *buyer_ft_temp_account.key;
+        order_info.receiving_pubkey = Pubkey::default();
+        order_info.returning_pubkey = *buyer_ft_account.key;
+        order_info.limit_price = price;
+        CallOrder::pack(order_info, &mut order_account.try_borrow_mut_data()?)?;
+
+        call_auction_info.open_bids = call_auction_info
+            .open_bids
+            .checked_add(1)
+            .ok_or(AuctionError::AmountOverflow)?;
+        Self::push_book_entry(
+            call_auction_account,
+            call_auction_info,
+            order_account.key,
+            OrderSide::Bid,
+            price,
+        )?;
+
+        let (pda, _bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        Self::check_not_paused(&Self::unpack_config(config_account, program_id)?)?;
+
+        msg!("Transferring FT to the Escrow Account from the buyer...");
+        Self::escrow_tokens(
+            token_program,
+            buyer_ft_account,
+            buyer_ft_temp_account,
+            buyer_account,
+            pda,
+            price,
+        )
+    }
+
+    fn process_cancel_order(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_account = next_account_info(account_info_iter)?;
+
+        if !owner_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let escrow_temp_account = next_account_info(account_info_iter)?;
+        let returning_account = next_account_info(account_info_iter)?;
+
+        let call_auction_account = next_account_info(account_info_iter)?;
+        let mut call_auction_info = Self::unpack_call_auction(call_auction_account, program_id)?;
+
+        let order_account = next_account_info(account_info_iter)?;
+        let order_info = CallOrder::unpack(&order_account.try_borrow_data()?)?;
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if order_info.call_auction_pubkey != *call_auction_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if order_info.owner_pubkey != *owner_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if order_info.escrow_temp_pubkey != *escrow_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if order_info.returning_pubkey != *returning_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let entry_index =
+            Self::find_book_entry(call_auction_account, &call_auction_info, order_account.key)?;
+        let entry = BookEntry::unpack_unchecked(
+            &call_auction_account.try_borrow_data()?[CallAuction::entry_span(entry_index)],
+        )?;
+        if entry.matched_order_pubkey != Pubkey::default() {
+            // A filled order is left to the buyer to settle for the grace period.
+            let settle_by = entry
+                .filled_at
+                .checked_add(SETTLEMENT_GRACE_PERIOD_SEC)
+                .ok_or(AuctionError::AmountOverflow)?;
+            if settle_by > clock.unix_timestamp {
+                msg!(
+                    "The fill can be settled for {} more seconds",
+                    (settle_by - clock.unix_timestamp)
+                );
+                return Err(AuctionError::ActiveAuction.into());
+            }
+        } else {
+            let clear_by = call_auction_info
+                .epoch_end_at
+                .checked_add(SETTLEMENT_GRACE_PERIOD_SEC)
+                .ok_or(AuctionError::AmountOverflow)?;
+            if call_auction_info.epoch_end_at <= clock.unix_timestamp
+                && clear_by > clock.unix_timestamp
+            {
+                msg!("The epoch window has ended, the order is locked until it is cleared");
+                return Err(AuctionError::InactiveAuction.into());
+            }
+            match order_info.side {
+                OrderSide::Ask => call_auction_info.open_asks -= 1,
+                OrderSide::Bid => call_auction_info.open_bids -= 1,
+            }
+        }
+        Self::remove_book_entry(call_auction_account, &mut call_auction_info, entry_index)?;
+        CallAuction::pack_header(
+            call_auction_info,
+            &mut call_auction_account.try_borrow_mut_data()?,
+        )?;
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+
+        let escrow_temp_account_data =
+            TokenAccount::unpack(&escrow_temp_account.try_borrow_data()?)?;
+        msg!("Returning the escrowed tokens to the owner...");
+        Self::transfer_from_escrow(
+            token_program,
+            escrow_temp_account,
+            returning_account,
+            pda,
+            pda_account,
+            signers_seeds,
+            escrow_temp_account_data.amount,
+        )?;
+
+        Self::close_temporary_ft(
+            token_program,
+            escrow_temp_account,
+            owner_account,
+            pda,
+            pda_account,
+            signers_seeds,
+        )?;
+
+        msg!("Closing the Order Account...");
+        Self::close_state_account(order_account, owner_account)
+    }
+
+    fn process_clear(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let call_auction_account = next_account_info(account_info_iter)?;
+        let mut call_auction_info = Self::unpack_call_auction(call_auction_account, program_id)?;
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if call_auction_info.epoch_end_at > clock.unix_timestamp {
+            msg!(
+                "Epoch will end in {} seconds",
+                (call_auction_info.epoch_end_at - clock.unix_timestamp)
+            );
+            return Err(AuctionError::ActiveAuction.into());
+        }
+
+        let mut call_auction_data = call_auction_account.try_borrow_mut_data()?;
+        let mut asks = Vec::with_capacity(call_auction_info.open_asks as usize);
+        let mut bids = Vec::with_capacity(call_auction_info.open_bids as usize);
+        for index in 0..call_auction_info.entry_count as usize {
+            let entry =
+                BookEntry::unpack_unchecked(&call_auction_data[CallAuction::entry_span(index)])?;
+            if entry.matched_order_pubkey != Pubkey::default() {
+                continue;
+            }
+            match entry.side {
+                OrderSide::Ask => asks.push((index, entry)),
+                OrderSide::Bid => bids.push((index, entry)),
+            }
+        }
+
+        asks.sort_by_key(|(_, ask)| ask.limit_price);
+        bids.sort_by_key(|(_, bid)| Reverse(bid.limit_price));
+        let fills = asks
+            .iter()
+            .zip(bids.iter())
+            .take_while(|((_, ask), (_, bid))| bid.limit_price >= ask.limit_price)
+            .count();
+
+        if fills > 0 {
+            // Any price between the marginal matched and unmatched orders clears the market,
+            // the midpoint splits the surplus between both sides.
+            let lower_price = asks[fills - 1]
+                .1
+                .limit_price
+                .max(bids.get(fills).map_or(0, |(_, bid)| bid.limit_price));
+            let upper_price = bids[fills - 1]
+                .1
+                .limit_price
+                .min(asks.get(fills).map_or(u64::MAX, |(_, ask)| ask.limit_price));
+            let clearing_price = lower_price + (upper_price - lower_price) / 2;
+            msg!("Clearing {} fills at {}", fills, clearing_price);
+
+            // Each filled ask is paired with a filled bid, which settles the fill later.
+            for ((ask_index, ask), (bid_index, bid)) in asks.iter().zip(bids.iter()).take(fills) {
+                for (index, entry, matched_order_pubkey) in [
+                    (ask_index, ask, bid.order_pubkey),
+                    (bid_index, bid, ask.order_pubkey),
+                ] {
+                    BookEntry::pack(
+                        BookEntry {
+                            order_pubkey: entry.order_pubkey,
+                            side: entry.side,
+                            limit_price: entry.limit_price,
+                            matched_order_pubkey,
+                            fill_price: clearing_price,
+                            filled_at: clock.unix_timestamp,
+                        },
+                        &mut call_auction_data[CallAuction::entry_span(*index)],
+                    )?;
+                }
+            }
+        }
+
+        call_auction_info.open_asks -= fills as u32;
+        call_auction_info.open_bids -= fills as u32;
+        call_auction_info.epoch += 1;
+        call_auction_info.epoch_end_at = clock
+            .unix_timestamp
+            .checked_add(call_auction_info.epoch_duration as i64)
+            .ok_or(AuctionError::AmountOverflow)?;
+        CallAuction::pack_header(call_auction_info, &mut call_auction_data)
+    }
+
+    fn process_settle_fill(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let buyer_account = next_account_info(account_info_iter)?;
+
+        if !buyer_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let buyer_nft_receiving_account = next_account_info(account_info_iter)?;
+        let bid_order_account = next_account_info(account_info_iter)?;
+        let bid_info = CallOrder::unpack(&bid_order_account.try_borrow_data()?)?;
+        let buyer_ft_temp_account = next_account_info(account_info_iter)?;
+        let buyer_ft_returning_account = next_account_info(account_info_iter)?;
+        let ask_order_account = next_account_info(account_info_iter)?;
+        let ask_info = CallOrder::unpack(&ask_order_account.try_borrow_data()?)?;
+        let seller_nft_temp_account = next_account_info(account_info_iter)?;
+        let seller_ft_receiving_account = next_account_info(account_info_iter)?;
+        let seller_account = next_account_info(account_info_iter)?;
+
+        let call_auction_account = next_account_info(account_info_iter)?;
+        let mut call_auction_info = Self::unpack_call_auction(call_auction_account, program_id)?;
+
+        if bid_info.call_auction_pubkey != *call_auction_account.key
+            || bid_info.side != OrderSide::Bid
+            || bid_info.owner_pubkey != *buyer_account.key
+            || bid_info.escrow_temp_pubkey != *buyer_ft_temp_account.key
+            || bid_info.returning_pubkey != *buyer_ft_returning_account.key
+        {
+            return Err(AuctionError::OrderSetMismatch.into());
+        }
+        if ask_info.call_auction_pubkey != *call_auction_account.key
+            || ask_info.side != OrderSide::Ask
+            || ask_info.owner_pubkey != *seller_account.key
+            || ask_info.escrow_temp_pubkey != *seller_nft_temp_account.key
+            || ask_info.receiving_pubkey != *seller_ft_receiving_account.key
+        {
+            return Err(AuctionError::OrderSetMismatch.into());
+        }
+
+        let bid_index = Self::find_book_entry(
+            call_auction_account,
+            &call_auction_info,
+            bid_order_account.key,
+        )?;
+        let ask_index = Self::find_book_entry(
+            call_auction_account,
+            &call_auction_info,
+            ask_order_account.key,
+        )?;
+        let bid_entry = BookEntry::unpack_unchecked(
+            &call_auction_account.try_borrow_data()?[CallAuction::entry_span(bid_index)],
+        )?;
+        if bid_entry.matched_order_pubkey != *ask_order_account.key {
+            msg!("The orders weren't matched with each other");
+            return Err(AuctionError::OrderSetMismatch.into());
+        }
+
+        let seller_nft_temp_account_data =
+            TokenAccount::unpack(&seller_nft_temp_account.try_borrow_data()?)?;
+        let buyer_nft_receiving_account_data =
+            TokenAccount::unpack(&buyer_nft_receiving_account.try_borrow_data()?)?;
+        if buyer_nft_receiving_account_data.owner != *buyer_account.key
+            || buyer_nft_receiving_account_data.mint != seller_nft_temp_account_data.mint
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // Remove the later entry first, so removing it doesn't move the other one.
+        Self::remove_book_entry(
+            call_auction_account,
+            &mut call_auction_info,
+            bid_index.max(ask_index),
+        )?;
+        Self::remove_book_entry(
+            call_auction_account,
+            &mut call_auction_info,
+            bid_index.min(ask_index),
+        )?;
+        CallAuction::pack_header(
+            call_auction_info,
+            &mut call_auction_account.try_borrow_mut_data()?,
+        )?;
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+        let treasury_ft_account =
+            Self::next_treasury_account(account_info_iter, config_info.fee_bps)?;
+
+        msg!("Transferring NFT to the buyer...");
+        Self::transfer_from_escrow(
+            token_program,
+            seller_nft_temp_account,
+            buyer_nft_receiving_account,
+            pda,
+            pda_account,
+            signers_seeds,
+            seller_nft_temp_account_data.amount,
+        )?;
+
+        msg!("Transferring FT to the seller...");
+        Self::transfer_proceeds(
+            &Proceeds::Token {
+                token_program,
+                temp_account: buyer_ft_temp_account,
+                pda,
+                pda_account,
+                signers_seeds,
+            },
+            seller_ft_receiving_account,
+            treasury_ft_account,
+            config_info.fee_bps,
+            &config_info.treasury_pubkey,
+            bid_entry.fill_price,
+        )?;
+
+        // Everything left in escrow, the unspent limit and any FT sent to the account since, goes back to the buyer.
+        let unspent = TokenAccount::unpack(&buyer_ft_temp_account.try_borrow_data()?)?.amount;
+        if unspent > 0 {
+            msg!("Returning the unspent FT to the buyer...");
+            Self::transfer_from_escrow(
+                token_program,
+                buyer_ft_temp_account,
+                buyer_ft_returning_account,
+                pda,
+                pda_account,
+                signers_seeds,
+                unspent,
+            )?;
+        }
+
+        Self::close_temporary_ft(
+            token_program,
+            seller_nft_temp_account,
+            seller_account,
+            pda,
+            pda_account,
+            signers_seeds,
+        )?;
+        Self::close_temporary_ft(
+            token_program,
+            buyer_ft_temp_account,
+            buyer_account,
+            pda,
+            pda_account,
+            signers_seeds,
+        )?;
+
+        msg!("Closing the Order Accounts...");
+        Self::close_state_account(ask_order_account, seller_account)?;
+        Self::close_state_account(bid_order_account, buyer_account)
+    }
+
+    fn unpack_call_auction(
+        call_auction_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<CallAuction, ProgramError> {
+        if call_auction_account.owner != program_id {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let call_auction_info =
+            CallAuction::unpack_header(&call_auction_account.try_borrow_data()?)?;
+        if !call_auction_info.is_initialized() {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        Ok(call_auction_info)
+    }
+
+    /// Appends an open order to the book of a call auction and packs its header.
+    fn push_book_entry(
+        call_auction_account: &AccountInfo,
+        mut call_auction_info: CallAuction,
+        order_pubkey: &Pubkey,
+        side: OrderSide,
+        limit_price: u64,
+    ) -> ProgramResult {
+        let mut call_auction_data = call_auction_account.try_borrow_mut_data()?;
+        let index = call_auction_info.entry_count as usize;
+        if index >= CallAuction::entry_capacity(call_auction_data.len()) {
+            return Err(AuctionError::OrderBookFull.into());
+        }
+        BookEntry::pack(
+            BookEntry {
+                order_pubkey: *order_pubkey,
+                side,
+                limit_price,
+                matched_order_pubkey: Pubkey::default(),
+                fill_price: 0,
+                filled_at: 0,
+            },
+            &mut call_auction_data[CallAuction::entry_span(index)],
+        )?;
+        call_auction_info.entry_count += 1;
+        CallAuction::pack_header(call_auction_info, &mut call_auction_data)
+    }
+
+    /// Index of the book entry of `order_pubkey` in a call auction.
+    fn find_book_entry(
+        call_auction_account: &AccountInfo,
+        call_auction_info: &CallAuction,
+        order_pubkey: &Pubkey,
+    ) -> Result<usize, ProgramError> {
+        let call_auction_data = call_auction_account.try_borrow_data()?;
+        (0..call_auction_info.entry_count as usize)
+            .find(|index| {
+                &call_auction_data[CallAuction::entry_span(*index)][..32] == order_pubkey.as_ref()
+            })
+            .ok_or_else(|| AuctionError::OrderSetMismatch.into())
+    }
+
+    /// Removes the book entry at `index` by moving the last entry into its place.
+    fn remove_book_entry(
+        call_auction_account: &AccountInfo,
+        call_auction_info: &mut CallAuction,
+        index: usize,
+    ) -> ProgramResult {
+        let mut call_auction_data = call_auction_account.try_borrow_mut_data()?;
+        let last_index = call_auction_info.entry_count as usize - 1;
+        if index != last_index {
+            let last_span = CallAuction::entry_span(last_index);
+            call_auction_data.copy_within(last_span, CallAuction::entry_span(index).start);
+        }
+        call_auction_info.entry_count -= 1;
+        Ok(())
+    }
+
+    fn process_make_offer(
+        accounts: &[AccountInfo],
+        ft_amount: u64,
+        nft_count: u8,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let bidder_account = next_account_info(account_info_iter)?;
+
+        if !bidder_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let bidder_nft_receiving_account = next_account_info(account_info_iter)?;
+        let escrow_account = next_account_info(account_info_iter)?;
+        let auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+
+        let offer_account = next_account_info(account_info_iter)?;
+        let sys_var_rent_account = next_account_info(account_info_iter)?;
+
+        let rent = &Rent::from_account_info(sys_var_rent_account)?;
+        if !rent.is_exempt(offer_account.lamports(), offer_account.data_len()) {
+            return Err(AuctionError::NotRentExempt.into());
+        }
+
+        let mut offer_info = Offer::unpack_unchecked(&offer_account.try_borrow_data()?)?;
+        if offer_info.is_initialized() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if auction_info.kind != AuctionKind::Barter {
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if auction_info.end_at <= clock.unix_timestamp {
+            return Err(AuctionError::InactiveAuction.into());
+        }
+        if nft_count as usize > MAX_OFFERED_NFTS || (nft_count == 0 && ft_amount == 0) {
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+
+        let (pda, _bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        Self::check_not_paused(&Self::unpack_config(config_account, program_id)?)?;
+
+        offer_info.is_initialized = true;
+        offer_info.auction_pubkey = *escrow_account.key;
+        offer_info.bidder_pubkey = *bidder_account.key;
+        offer_info.bidder_nft_receiving_pubkey = *bidder_nft_receiving_account.key;
+        offer_info.ft_temp_pubkey = Pubkey::default();
+        offer_info.ft_amount = ft_amount;
+        offer_info.ft_returning_pubkey = Pubkey::default();
+        offer_info.nft_count = nft_count;
+        offer_info.nft_temp_pubkeys = [Pubkey::default(); MAX_OFFERED_NFTS];
+        offer_info.nft_returning_pubkeys = [Pubkey::default(); MAX_OFFERED_NFTS];
+
+        if ft_amount > 0 {
+            let bidder_ft_account = next_account_info(account_info_iter)?;
+            let bidder_ft_temp_account = next_account_info(account_info_iter)?;
+            offer_info.ft_temp_pubkey = *bidder_ft_temp_account.key;
+            offer_info.ft_returning_pubkey = *bidder_ft_account.key;
+
+            msg!("Transferring FT to the Escrow Account from the bidder...");
+            Self::escrow_tokens(
+                token_program,
+                bidder_ft_account,
+                bidder_ft_temp_account,
+                bidder_account,
+                pda,
+                ft_amount,
+            )?;
+        }
+
+        for index in 0..nft_count as usize {
+            let bidder_nft_account = next_account_info(account_info_iter)?;
+            let bidder_nft_temp_account = next_account_info(account_info_iter)?;
+            offer_info.nft_temp_pubkeys[index] = *bidder_nft_temp_account.key;
+            offer_info.nft_returning_pubkeys[index] = *bidder_nft_account.key;
+
+            msg!("Transferring NFT to the Escrow Account from the bidder...");
+            Self::escrow_tokens(
+                token_program,
+                bidder_nft_account,
+                bidder_nft_temp_account,
+                bidder_account,
+                pda,
+                1,
+            )?;
+        }
+
+        Offer::pack(offer_info, &mut offer_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    fn process_accept_offer(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let exhibitor_account = next_account_info(account_info_iter)?;
+
+        if !exhibitor_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let exhibiting_nft_temp_account = next_account_info(account_info_iter)?;
+        let bidder_nft_receiving_account = next_account_info(account_info_iter)?;
+        let escrow_account = next_account_info(account_info_iter)?;
+        let auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+        let offer_account = next_account_info(account_info_iter)?;
+        let offer_info = Offer::unpack(&offer_account.try_borrow_data()?)?;
+        let bidder_account = next_account_info(account_info_iter)?;
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if auction_info.end_at > clock.unix_timestamp {
+            msg!(
+                "Auction will end in {} seconds",
+                (auction_info.end_at - clock.unix_timestamp)
+            );
+            return Err(AuctionError::ActiveAuction.into());
+        }
+        if auction_info.exhibitor_pubkey != *exhibitor_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.exhibiting_nft_temp_pubkey != *exhibiting_nft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if offer_info.auction_pubkey != *escrow_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if offer_info.bidder_pubkey != *bidder_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if offer_info.bidder_nft_receiving_pubkey != *bidder_nft_receiving_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+
+        if offer_info.ft_amount > 0 {
+            let bidder_ft_temp_account = next_account_info(account_info_iter)?;
+            let exhibitor_ft_receiving_account = next_account_info(account_info_iter)?;
+            let treasury_ft_account =
+                Self::next_treasury_account(account_info_iter, auction_info.fee_bps)?;
+            if offer_info.ft_temp_pubkey != *bidder_ft_temp_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if auction_info.exhibitor_ft_receiving_pubkey != *exhibitor_ft_receiving_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            // The whole balance is paid out, so FT sent to the account after the offer can't keep it from closing.
+            let bidder_ft_temp_account_data =
+                TokenAccount::unpack(&bidder_ft_temp_account.try_borrow_data()?)?;
+            msg!("Transferring FT to the Exhibitor...");
+            Self::transfer_proceeds(
+                &Proceeds::Token {
+                    token_program,
+                    temp_account: bidder_ft_temp_account,
+                    pda,
+                    pda_account,
+                    signers_seeds,
+                },
+                exhibitor_ft_receiving_account,
+                treasury_ft_account,
+                auction_info.fee_bps,
+                &auction_info.treasury_pubkey,
+                bidder_ft_temp_account_data.amount,
+            )?;
+            Self::close_temporary_ft(
+                token_program,
+                bidder_ft_temp_account,
+                bidder_account,
+                pda,
+                pda_account,
+                signers_seeds,
+            )?;
+        }
+
+        for index in 0..offer_info.nft_count as usize {
+            let bidder_nft_temp_account = next_account_info(account_info_iter)?;
+            let exhibitor_nft_receiving_account = next_account_info(account_info_iter)?;
+            if offer_info.nft_temp_pubkeys[index] != *bidder_nft_temp_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let exhibitor_nft_receiving_account_data =
+                TokenAccount::unpack(&exhibitor_nft_receiving_account.try_borrow_data()?)?;
+            if exhibitor_nft_receiving_account_data.owner != auction_info.exhibitor_pubkey {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let bidder_nft_temp_account_data =
+                TokenAccount::unpack(&bidder_nft_temp_account.try_borrow_data()?)?;
+            msg!("Transferring the offered NFT to the Exhibitor...");
+            Self::transfer_from_escrow(
+                token_program,
+                bidder_nft_temp_account,
+                exhibitor_nft_receiving_account,
+                pda,
+                pda_account,
+                signers_seeds,
+                bidder_nft_temp_account_data.amount,
+            )?;
+            Self::close_temporary_ft(
+                token_program,
+                bidder_nft_temp_account,
+                bidder_account,
+                pda,
+                pda_account,
+                signers_seeds,
+            )?;
+        }
+
+        let exhibiting_nft_temp_account_data =
+            TokenAccount::unpack(&exhibiting_nft_temp_account.try_borrow_data()?)?;
+        msg!("Transferring NFT to the Bidder...");
+        Self::transfer_from_escrow(
+            token_program,
+            exhibiting_nft_temp_account,
+            bidder_nft_receiving_account,
+            pda,
+            pda_account,
+            signers_seeds,
+            exhibiting_nft_temp_account_data.amount,
+        )?;
+
+        msg!("Closing the Offer Account...");
+        Self::close_state_account(offer_account, bidder_account)?;
+
+        let rent_payer_account = Self::rent_payer_account(
+            &auction_info.rent_payer_pubkey,
+            exhibitor_account,
+            account_info_iter,
+        )?;
+        Self::close_escrow(
+            token_program,
+            exhibiting_nft_temp_account,
+            rent_payer_account,
+            pda,
+            pda_account,
+            escrow_account,
+            signers_seeds,
+        )
+    }
+
+    fn process_withdraw_offer(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let bidder_account = next_account_info(account_info_iter)?;
+
+        if !bidder_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let escrow_account = next_account_info(account_info_iter)?;
+        let offer_account = next_account_info(account_info_iter)?;
+        let offer_info = Offer::unpack(&offer_account.try_borrow_data()?)?;
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if offer_info.auction_pubkey != *escrow_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if offer_info.bidder_pubkey != *bidder_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // Once the auction is settled or cancelled its escrow account is closed and every
+        // remaining offer is free to go.
+        if !escrow_account.data_is_empty() {
+            let auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+            let select_by = auction_info
+                .end_at
+                .checked_add(OFFER_SELECTION_PERIOD_SEC)
+                .ok_or(AuctionError::AmountOverflow)?;
+            if auction_info.end_at <= clock.unix_timestamp && select_by > clock.unix_timestamp {
+                msg!("The exhibitor is picking an offer, the offer is locked until then");
+                return Err(AuctionError::InactiveAuction.into());
+            }
+        }
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+
+        let ft_accounts = match offer_info.ft_amount {
+            0 => None,
+            _ => Some((&offer_info.ft_temp_pubkey, &offer_info.ft_returning_pubkey)),
+        };
+        let nft_accounts = offer_info
+            .nft_temp_pubkeys
+            .iter()
+            .zip(offer_info.nft_returning_pubkeys.iter())
+            .take(offer_info.nft_count as usize);
+
+        for (temp_pubkey, returning_pubkey) in ft_accounts.into_iter().chain(nft_accounts) {
+            let temp_account = next_account_info(account_info_iter)?;
+            let returning_account = next_account_info(account_info_iter)?;
+            if *temp_pubkey != *temp_account.key || *returning_pubkey != *returning_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let temp_account_data = TokenAccount::unpack(&temp_account.try_borrow_data()?)?;
+            msg!("Returning the offered tokens to the bidder...");
+            Self::transfer_from_escrow(
+                token_program,
+                temp_account,
+                returning_account,
+                pda,
+                pda_account,
+                signers_seeds,
+                temp_account_data.amount,
+            )?;
+            Self::close_temporary_ft(
+                token_program,
+                temp_account,
+                bidder_account,
+                pda,
+                pda_account,
+                signers_seeds,
+            )?;
+        }
+
+        msg!("Closing the Offer Account...");
+        Self::close_state_account(offer_account, bidder_account)
+    }
+
+    fn process_init_config(
+        accounts: &[AccountInfo],
+        fee_bps: u16,
+        referral_bps: u16,
+        cancel_penalty_bps: u16,
+        retraction_penalty_bps: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+
+        if !admin_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let treasury_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let sys_var_rent_account = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(sys_var_rent_account)?;
+        let system_program = next_account_info(account_info_iter)?;
+        let program_data_account = next_account_info(account_info_iter)?;
+        Self::check_upgrade_authority(program_data_account, admin_account, program_id)?;
+
+        let (config_pubkey, bump_seed) = Pubkey::find_program_address(&[b"config"], program_id);
+        if config_pubkey != *config_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !config_account.data_is_empty() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        if fee_bps > MAX_FEE_BPS
+            || fee_bps as u64 + referral_bps as u64 > BASIS_POINTS
+            || cancel_penalty_bps as u64 > BASIS_POINTS
+            || retraction_penalty_bps as u64 > BASIS_POINTS
+        {
+            return Err(AuctionError::InvalidBasisPoints.into());
+        }
+
+        msg!("Creating the Config Account...");
+        Self::create_pda_account(
+            admin_account,
+            config_account,
+            system_program,
+            rent.minimum_balance(Config::LEN),
+            Config::LEN,
+            program_id,
+            &[&[&b"config"[..], &[bump_seed]]],
+        )?;
+
+        let config_info = Config {
+            is_initialized: true,
+            admin_pubkey: *admin_account.key,
+            treasury_pubkey: *treasury_account.key,
+            fee_bps,
+            referral_bps,
+            price_authority_pubkey: *admin_account.key,
+            cancel_penalty_bps,
+            retraction_penalty_bps,
+            paused: false,
+            min_duration_sec: 0,
+            max_duration_sec: 0,
+        };
+        Config::pack(config_info, &mut config_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    /// Checks the admin is the upgrade authority of the program, so the config can't be created by anyone else.
+    fn check_upgrade_authority(
+        program_data_account: &AccountInfo,
+        admin_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let program_data_pubkey =
+            Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0;
+        if program_data_pubkey != *program_data_account.key
+            || *program_data_account.owner != bpf_loader_upgradeable::id()
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        // The program data starts with the `ProgramData` tag, the deployment slot and the optional authority.
+        let data = program_data_account.try_borrow_data()?;
+        let header = data.get(..45).ok_or(ProgramError::InvalidAccountData)?;
+        let (tag, _slot, has_authority, authority) =
+            array_refs![array_ref![header, 0, 45], 4, 8, 1, 32];
+        if u32::from_le_bytes(*tag) != 3
+            || has_authority[0] != 1
+            || authority != admin_account.key.as_ref()
+        {
+            msg!("Only the upgrade authority of the program can create the config");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(())
+    }
+
+    fn process_update_fee(
+        accounts: &[AccountInfo],
+        fee_bps: u16,
+        referral_bps: u16,
+        cancel_penalty_bps: u16,
+        retraction_penalty_bps: u16,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let mut config_info = Self::unpack_config(config_account, program_id)?;
+        let treasury_account = next_account_info(account_info_iter)?;
+
+        Self::check_admin(&config_info, admin_account)?;
+        if fee_bps > MAX_FEE_BPS
+            || fee_bps as u64 + referral_bps as u64 > BASIS_POINTS
+            || cancel_penalty_bps as u64 > BASIS_POINTS
+            || retraction_penalty_bps as u64 > BASIS_POINTS
+        {
+            return Err(AuctionError::InvalidBasisPoints.into());
+        }
+
+        msg!(
+            "Protocol fee changed from {} to {} bps, referral share from {} to {} bps",
+            config_info.fee_bps,
+            fee_bps,
+            config_info.referral_bps,
+            referral_bps
+        );
+        msg!(
+            "Cancellation penalty changed from {} to {} bps",
+            config_info.cancel_penalty_bps,
+            cancel_penalty_bps
+        );
+        msg!(
+            "Retraction penalty changed from {} to {} bps",
+            config_info.retraction_penalty_bps,
+            retraction_penalty_bps
+        );
+        config_info.fee_bps = fee_bps;
+        config_info.referral_bps = referral_bps;
+        config_info.cancel_penalty_bps = cancel_penalty_bps;
+        config_info.retraction_penalty_bps = retraction_penalty_bps;
+        config_info.treasury_pubkey = *treasury_account.key;
+        Config::pack(config_info, &mut config_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    fn process_set_paused(
+        accounts: &[AccountInfo],
+        paused: bool,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let mut config_info = Self::unpack_config(config_account, program_id)?;
+
+        Self::check_admin(&config_info, admin_account)?;
+
+        msg!("Paused changed from {} to {}", config_info.paused, paused);
+        config_info.paused = paused;
+        Config::pack(config_info, &mut config_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    fn process_add_to_denylist(
+        accounts: &[AccountInfo],
+        wallet: Pubkey,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+        let denylist_page_account = next_account_info(account_info_iter)?;
+        let sys_var_rent_account = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(sys_var_rent_account)?;
+        let system_program = next_account_info(account_info_iter)?;
+
+        Self::check_admin(&config_info, admin_account)?;
+
+        let (denylist_page_pubkey, bump_seed) =
+            Pubkey::find_program_address(&[b"denylist", &wallet.as_ref()[..1]], program_id);
+        if denylist_page_pubkey != *denylist_page_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if denylist_page_account.data_is_empty() {
+            msg!("Creating the Denylist Page Account...");
+            Self::create_pda_account(
+                admin_account,
+                denylist_page_account,
+                system_program,
+                rent.minimum_balance(DenylistPage::LEN),
+                DenylistPage::LEN,
+                program_id,
+                &[&[&b"denylist"[..], &wallet.as_ref()[..1], &[bump_seed]]],
+            )?;
+        }
+
+        let mut denylist_page_info =
+            DenylistPage::unpack_header(&denylist_page_account.try_borrow_data()?)?;
+        if denylist_page_info.contains(&denylist_page_account.try_borrow_data()?, &wallet)? {
+            msg!("The wallet is already denied");
+            return Ok(());
+        }
+
+        // The page grows by a wallet at a time, so any number of wallets sharing the first byte can be denied.
+        let page_len = DenylistPage::wallet_span(denylist_page_info.count as usize).end;
+        if denylist_page_account.data_len() < page_len {
+            let top_up = rent
+                .minimum_balance(page_len)
+                .saturating_sub(denylist_page_account.lamports());
+            if top_up > 0 {
+                msg!("Funding the rent of the grown Denylist Page Account...");
+                invoke(
+                    &system_instruction::transfer(
+                        admin_account.key,
+                        denylist_page_account.key,
+                        top_up,
+                    ),
+                    &[
+                        admin_account.clone(),
+                        denylist_page_account.clone(),
+                        system_program.clone(),
+                    ],
+                )?;
+            }
+            denylist_page_account.realloc(page_len, false)?;
+        }
+
+        denylist_page_info.is_initialized = true;
+        denylist_page_info.insert(&mut denylist_page_account.try_borrow_mut_data()?, &wallet)?;
+        DenylistPage::pack_header(
+            denylist_page_info,
+            &mut denylist_page_account.try_borrow_mut_data()?,
+        )
+    }
+
+    fn process_remove_from_denylist(
+        accounts: &[AccountInfo],
+        wallet: Pubkey,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+        let denylist_page_account = next_account_info(account_info_iter)?;
+
+        Self::check_admin(&config_info, admin_account)?;
+
+        let (denylist_page_pubkey, _bump_seed) =
+            Pubkey::find_program_address(&[b"denylist", &wallet.as_ref()[..1]], program_id);
+        if denylist_page_pubkey != *denylist_page_account.key
+            || denylist_page_account.owner != program_id
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut denylist_page_info =
+            DenylistPage::unpack_header(&denylist_page_account.try_borrow_data()?)?;
+        if !denylist_page_info.remove(&mut denylist_page_account.try_borrow_mut_data()?, &wallet)? {
+            msg!("The wallet isn't denied");
+        }
+        DenylistPage::pack_header(
+            denylist_page_info,
+            &mut denylist_page_account.try_borrow_mut_data()?,
+        )
+    }
+
+    fn process_set_duration_bounds(
+        accounts: &[AccountInfo],
+        min_duration_sec: u64,
+        max_duration_sec: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let mut config_info = Self::unpack_config(config_account, program_id)?;
+
+        Self::check_admin(&config_info, admin_account)?;
+        if max_duration_sec > 0 && min_duration_sec > max_duration_sec {
+            return Err(AuctionError::InvalidDuration.into());
+        }
+
+        msg!(
+            "Duration bounds changed from {}..{} to {}..{} seconds",
+            config_info.min_duration_sec,
+            config_info.max_duration_sec,
+            min_duration_sec,
+            max_duration_sec
+        );
+        config_info.min_duration_sec = min_duration_sec;
+        config_info.max_duration_sec = max_duration_sec;
+        Config::pack(config_info, &mut config_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    fn process_set_admin(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+        let new_admin_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let mut config_info = Self::unpack_config(config_account, program_id)?;
+
+        Self::check_admin(&config_info, admin_account)?;
+        // The new admin signs too, so the config can't be handed to a key nobody holds.
+        if !new_admin_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        config_info.admin_pubkey = *new_admin_account.key;
+        Config::pack(config_info, &mut config_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    fn process_update_price(
+        accounts: &[AccountInfo],
+        price: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let price_authority_account = next_account_info(account_info_iter)?;
+
+        if !price_authority_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mint_account = next_account_info(account_info_iter)?;
+        let price_feed_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+        let sys_var_rent_account = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(sys_var_rent_account)?;
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+        let system_program = next_account_info(account_info_iter)?;
+
+        if config_info.price_authority_pubkey != *price_authority_account.key {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if price == 0 {
+            msg!("A mint can't be priced at zero");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        let (price_feed_pubkey, bump_seed) =
+            Pubkey::find_program_address(&[b"price_feed", mint_account.key.as_ref()], program_id);
+        if price_feed_pubkey != *price_feed_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if price_feed_account.data_is_empty() {
+            msg!("Creating the Price Feed Account...");
+            Self::create_pda_account(
+                price_authority_account,
+                price_feed_account,
+                system_program,
+                rent.minimum_balance(PriceFeed::LEN),
+                PriceFeed::LEN,
+                program_id,
+                &[&[&b"price_feed"[..], mint_account.key.as_ref(), &[bump_seed]]],
+            )?;
+        }
+
+        msg!("Price of {} set to {}", mint_account.key, price);
+        let price_feed_info = PriceFeed {
+            is_initialized: true,
+            mint_pubkey: *mint_account.key,
+            price,
+            updated_at: clock.unix_timestamp,
+        };
+        PriceFeed::pack(
+            price_feed_info,
+            &mut price_feed_account.try_borrow_mut_data()?,
+        )?;
+        Ok(())
+    }
+
+    fn process_set_price_authority(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+        let price_authority_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let mut config_info = Self::unpack_config(config_account, program_id)?;
+
+        Self::check_admin(&config_info, admin_account)?;
+
+        config_info.price_authority_pubkey = *price_authority_account.key;
+        Config::pack(config_info, &mut config_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    /// Reads the price feed of `mint`, checking it is the canonical price feed PDA and fresh.
+    fn unpack_price_feed(
+        price_feed_account: &AccountInfo,
+        mint: &Pubkey,
+        clock: &Clock,
+        program_id: &Pubkey,
+    ) -> Result<PriceFeed, ProgramError> {
+        let (price_feed_pubkey, _bump_seed) =
+            Pubkey::find_program_address(&[b"price_feed", mint.as_ref()], program_id);
+        if price_feed_pubkey != *price_feed_account.key || price_feed_account.owner != program_id {
+            return Err(AuctionError::StalePrice.into());
+        }
+        let price_feed_info = PriceFeed::unpack(&price_feed_account.try_borrow_data()?)?;
+        if clock
+            .unix_timestamp
+            .saturating_sub(price_feed_info.updated_at)
+            > MAX_PRICE_AGE_SEC
+        {
+            return Err(AuctionError::StalePrice.into());
+        }
+        Ok(price_feed_info)
+    }
+
+    /// Reads the program config, checking it is the canonical config PDA.
+    fn unpack_config(
+        config_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<Config, ProgramError> {
+        let (config_pubkey, _bump_seed) = Pubkey::find_program_address(&[b"config"], program_id);
+        if config_pubkey != *config_account.key || config_account.owner != program_id {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Config::unpack(&config_account.try_borrow_data()?)
+    }
+
+    fn check_not_paused(config_info: &Config) -> ProgramResult {
+        if config_info.paused {
+            return Err(AuctionError::Paused.into());
+        }
+        Ok(())
+    }
+
+    /// Checks an auction lasts at least a second, within the bounds of the config.
+    fn check_duration(config_info: &Config, duration_sec: u64) -> ProgramResult {
+        if duration_sec == 0
+            || duration_sec < config_info.min_duration_sec
+            || (config_info.max_duration_sec > 0 && duration_sec > config_info.max_duration_sec)
+        {
+            msg!(
+                "Auctions last {} to {} seconds",
+                config_info.min_duration_sec.max(1),
+                config_info.max_duration_sec
+            );
+            return Err(AuctionError::InvalidDuration.into());
+        }
+        Ok(())
+    }
+
+    fn check_house_duration(house_info: &AuctionHouse, duration_sec: u64) -> ProgramResult {
+        if duration_sec < house_info.min_duration_sec
+            || (house_info.max_duration_sec > 0 && duration_sec > house_info.max_duration_sec)
+        {
+            msg!(
+                "The house takes auctions lasting {} to {} seconds",
+                house_info.min_duration_sec,
+                house_info.max_duration_sec
+            );
+            return Err(AuctionError::HousePolicyViolation.into());
+        }
+        Ok(())
+    }
+
+    /// Time `duration_sec` from now, failing rather than wrapping on huge durations.
+    fn end_at(clock: &Clock, duration_sec: u64) -> Result<i64, ProgramError> {
+        i64::try_from(duration_sec)
+            .ok()
+            .and_then(|duration_sec| clock.unix_timestamp.checked_add(duration_sec))
+            .ok_or_else(|| AuctionError::AmountOverflow.into())
+    }
+
+    /// Checks `wallet` isn't on the denylist page it falls in, a page that was never created denying no one.
+    fn check_not_denied(
+        wallet: &Pubkey,
+        denylist_page_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let (denylist_page_pubkey, _bump_seed) =
+            Pubkey::find_program_address(&[b"denylist", &wallet.as_ref()[..1]], program_id);
+        if denylist_page_pubkey != *denylist_page_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if denylist_page_account.owner != program_id {
+            return Ok(());
+        }
+        let denylist_page_data = denylist_page_account.try_borrow_data()?;
+        let denylist_page_info = DenylistPage::unpack_header(&denylist_page_data)?;
+        if denylist_page_info.contains(&denylist_page_data, wallet)? {
+            return Err(AuctionError::Denied.into());
+        }
+        Ok(())
+    }
+
+    fn check_admin(config_info: &Config, admin_account: &AccountInfo) -> ProgramResult {
+        if !admin_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if config_info.admin_pubkey != *admin_account.key {
+            return Err(AuctionError::NotAdmin.into());
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_create_house(
+        accounts: &[AccountInfo],
+        fee_bps: u16,
+        min_increment_bps: u16,
+        min_duration_sec: u64,
+        max_duration_sec: u64,
+        anti_snipe_window_sec: u32,
+        anti_snipe_extension_sec: u32,
+        mint_count: u8,
+        exhibitor_allowlist_root: [u8; 32],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_account = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+        let authority_account = next_account_info(account_info_iter)?;
+        let treasury_account = next_account_info(account_info_iter)?;
+
+        Self::check_admin(&config_info, admin_account)?;
+
+        let house_account = next_account_info(account_info_iter)?;
+        let sys_var_rent_account = next_account_info(account_info_iter)?;
+
+        let rent = &Rent::from_account_info(sys_var_rent_account)?;
+        if !rent.is_exempt(house_account.lamports(), house_account.data_len()) {
+            return Err(AuctionError::NotRentExempt.into());
+        }
+        if house_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut house_info = AuctionHouse::unpack_unchecked(&house_account.try_borrow_data()?)?;
+        if house_info.is_initialized() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        house_info.is_initialized = true;
+        house_info.authority_pubkey = *authority_account.key;
+        Self::set_house_policy(
+            &mut house_info,
+            &config_info,
+            treasury_account,
+            account_info_iter,
+            fee_bps,
+            min_increment_bps,
+            min_duration_sec,
+            max_duration_sec,
+            anti_snipe_window_sec,
+            anti_snipe_extension_sec,
+            mint_count,
+            exhibitor_allowlist_root,
+        )?;
+        AuctionHouse::pack(house_info, &mut house_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_update_house(
+        accounts: &[AccountInfo],
+        fee_bps: u16,
+        min_increment_bps: u16,
+        min_duration_sec: u64,
+        max_duration_sec: u64,
+        anti_snipe_window_sec: u32,
+        anti_snipe_extension_sec: u32,
+        mint_count: u8,
+        exhibitor_allowlist_root: [u8; 32],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_account = next_account_info(account_info_iter)?;
+
+        if !authority_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+        let house_account = next_account_info(account_info_iter)?;
+        let mut house_info = Self::unpack_house(house_account, program_id)?;
+        let treasury_account = next_account_info(account_info_iter)?;
+
+        if house_info.authority_pubkey != *authority_account.key {
+            return Err(AuctionError::NotHouseAuthority.into());
+        }
+
+        msg!(
+            "House fee changed from {} to {} bps, minimum increment from {} to {} bps",
+            house_info.fee_bps,
+            fee_bps,
+            house_info.min_increment_bps,
+            min_increment_bps
+        );
+        Self::set_house_policy(
+            &mut house_info,
+            &config_info,
+            treasury_account,
+            account_info_iter,
+            fee_bps,
+            min_increment_bps,
+            min_duration_sec,
+            max_duration_sec,
+            anti_snipe_window_sec,
+            anti_snipe_extension_sec,
+            mint_count,
+            exhibitor_allowlist_root,
+        )?;
+        AuctionHouse::pack(house_info, &mut house_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    /// Validates and stores the policy of a house, reading its payment mints.
+    #[allow(clippy::too_many_arguments)]
+    fn set_house_policy<'a, 'b: 'a>(
+        house_info: &mut AuctionHouse,
+        config_info: &Config,
+        treasury_account: &AccountInfo,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        fee_bps: u16,
+        min_increment_bps: u16,
+        min_duration_sec: u64,
+        max_duration_sec: u64,
+        anti_snipe_window_sec: u32,
+        anti_snipe_extension_sec: u32,
+        mint_count: u8,
+        exhibitor_allowlist_root: [u8; 32],
+    ) -> ProgramResult {
+        // The fee of the house replaces the protocol fee, and is taken alongside the referral share.
+        if fee_bps > MAX_FEE_BPS
+            || fee_bps as u64 + config_info.referral_bps as u64 > BASIS_POINTS
+            || min_increment_bps as u64 > BASIS_POINTS
+        {
+            return Err(AuctionError::InvalidBasisPoints.into());
+        }
+        if (max_duration_sec > 0 && min_duration_sec > max_duration_sec)
+            || mint_count as usize > MAX_HOUSE_MINTS
+        {
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+
+        house_info.treasury_pubkey = *treasury_account.key;
+        house_info.fee_bps = fee_bps;
+        house_info.min_increment_bps = min_increment_bps;
+        house_info.min_duration_sec = min_duration_sec;
+        house_info.max_duration_sec = max_duration_sec;
+        house_info.anti_snipe_window_sec = anti_snipe_window_sec;
+        house_info.anti_snipe_extension_sec = anti_snipe_extension_sec;
+        house_info.exhibitor_allowlist_root = exhibitor_allowlist_root;
+        house_info.payment_mints = [Pubkey::default(); MAX_HOUSE_MINTS];
+        for index in 0..mint_count as usize {
+            let mint_account = next_account_info(account_info_iter)?;
+            house_info.payment_mints[index] = *mint_account.key;
+        }
+        house_info.payment_mint_count = mint_count;
+        Ok(())
+    }
+
+    fn unpack_house(
+        house_account: &AccountInfo,
+        program_id: &Pubkey,
+    ) -> Result<AuctionHouse, ProgramError> {
+        if house_account.owner != program_id {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        AuctionHouse::unpack(&house_account.try_borrow_data()?)
+    }
+
+    fn process_post_bond(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let bidder_account = next_account_info(account_info_iter)?;
+
+        if !bidder_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let escrow_account = next_account_info(account_info_iter)?;
+        let auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+        let bond_account = next_account_info(account_info_iter)?;
+        let sys_var_rent_account = next_account_info(account_info_iter)?;
+        let rent = &Rent::from_account_info(sys_var_rent_account)?;
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+        let system_program = next_account_info(account_info_iter)?;
+        let config_account = next_account_info(account_info_iter)?;
+        Self::check_not_paused(&Self::unpack_config(config_account, program_id)?)?;
+
+        if auction_info.bond_amount == 0 {
+            msg!("The auction doesn't take bonds");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if auction_info.end_at <= clock.unix_timestamp {
+            return Err(AuctionError::InactiveAuction.into());
+        }
+        let (bond_pubkey, bump_seed) = Pubkey::find_program_address(
+            &[
+                b"bond",
+                escrow_account.key.as_ref(),
+                bidder_account.key.as_ref(),
+            ],
+            program_id,
+        );
+        if bond_pubkey != *bond_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !bond_account.data_is_empty() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        msg!("Transferring the bond to the Bond Account...");
+        Self::create_pda_account(
+            bidder_account,
+            bond_account,
+            system_program,
+            rent.minimum_balance(Bond::LEN)
+                .checked_add(auction_info.bond_amount)
+                .ok_or(AuctionError::AmountOverflow)?,
+            Bond::LEN,
+            program_id,
+            &[&[
+                &b"bond"[..],
+                escrow_account.key.as_ref(),
+                bidder_account.key.as_ref(),
+                &[bump_seed],
+            ]],
+        )?;
+
+        let bond_info = Bond {
+            is_initialized: true,
+            auction_pubkey: *escrow_account.key,
+            bidder_pubkey: *bidder_account.key,
+            amount: auction_info.bond_amount,
+        };
+        Bond::pack(bond_info, &mut bond_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    fn process_refund_bond(accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let bidder_account = next_account_info(account_info_iter)?;
+
+        if !bidder_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let escrow_account = next_account_info(account_info_iter)?;
+        let bond_account = next_account_info(account_info_iter)?;
+        let bond_info = Bond::unpack(&bond_account.try_borrow_data()?)?;
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if bond_info.bidder_pubkey != *bidder_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if bond_info.auction_pubkey != *escrow_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // The escrow account is closed once the auction is settled or cancelled.
+        if !escrow_account.data_is_empty() {
+            let auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+            if auction_info.end_at > clock.unix_timestamp
+                || auction_info.highest_bidder_pubkey == *bidder_account.key
+                || auction_info.runner_up_pubkey == *bidder_account.key
+            {
+                return Err(AuctionError::BondLocked.into());
+            }
+        }
+
+        msg!("Returning the bond to the bidder...");
+        Self::close_state_account(bond_account, bidder_account)
+    }
+
+    fn process_forfeit_bond(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let exhibitor_account = next_account_info(account_info_iter)?;
+
+        if !exhibitor_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let exhibiting_nft_temp_account = next_account_info(account_info_iter)?;
+        let exhibiting_nft_returning_account = next_account_info(account_info_iter)?;
+        let escrow_account = next_account_info(account_info_iter)?;
+        let auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+        let bond_account = next_account_info(account_info_iter)?;
+        let bond_info = Bond::unpack(&bond_account.try_borrow_data()?)?;
+        let highest_bidder_ft_temp_account = next_account_info(account_info_iter)?;
+        let highest_bidder_ft_returning_account = next_account_info(account_info_iter)?;
+        let highest_bidder_account = next_account_info(account_info_iter)?;
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if auction_info.exhibitor_pubkey != *exhibitor_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.exhibiting_nft_temp_pubkey != *exhibiting_nft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.highest_bidder_pubkey == Pubkey::default() {
+            return Err(AuctionError::NoBidderFound.into());
+        }
+        if auction_info.highest_bidder_pubkey != *highest_bidder_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.highest_bidder_ft_temp_pubkey != *highest_bidder_ft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.highest_bidder_ft_returning_pubkey
+            != *highest_bidder_ft_returning_account.key
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if bond_info.auction_pubkey != *escrow_account.key
+            || bond_info.bidder_pubkey != auction_info.highest_bidder_pubkey
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        if auction_info.second_chance_until != 0 {
+            msg!("The runner-up offered a second chance keeps their bond");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+
+        let settle_by = auction_info
+            .end_at
+            .checked_add(SETTLEMENT_GRACE_PERIOD_SEC)
+            .ok_or(AuctionError::AmountOverflow)?;
+        if settle_by > clock.unix_timestamp {
+            msg!(
+                "The winner can settle for {} more seconds",
+                (settle_by - clock.unix_timestamp)
+            );
+            return Err(AuctionError::ActiveAuction.into());
+        }
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+        let vault_bump_seed = Self::vault_bump_seed(escrow_account.key, program_id);
+        let vault_signers_seeds: &[&[&[u8]]] = &[&[
+            &b"vault"[..],
+            escrow_account.key.as_ref(),
+            &[vault_bump_seed],
+        ]];
+        let (bid_authority, bid_authority_account, bid_signers_seeds) = Self::bid_authority(
+            &auction_info,
+            highest_bidder_ft_temp_account,
+            pda,
+            pda_account,
+            signers_seeds,
+            vault_signers_seeds,
+        );
+
+        let exhibiting_nft_temp_account_data =
+            TokenAccount::unpack(&exhibiting_nft_temp_account.try_borrow_data()?)?;
+        msg!("Transferring NFT to the Exhibitor...");
+        Self::transfer_from_escrow(
+            token_program,
+            exhibiting_nft_temp_account,
+            exhibiting_nft_returning_account,
+            pda,
+            pda_account,
+            signers_seeds,
+            exhibiting_nft_temp_account_data.amount,
+        )?;
+
+        Self::refund_runner_up(
+            &auction_info,
+            account_info_iter,
+            token_program,
+            pda,
+            pda_account,
+            signers_seeds,
+            vault_signers_seeds,
+        )?;
+
+        msg!("Returning the winning bid to the winner...");
+        if auction_info.pays_in_lamports {
+            let vault_account = highest_bidder_ft_temp_account;
+            Self::transfer_lamports(
+                vault_account,
+                highest_bidder_ft_returning_account,
+                auction_info.price,
+            )?;
+            msg!("Closing the Vault Account...");
+            Self::close_state_account(vault_account, exhibitor_account)?;
+        } else {
+            let escrowed_amount = Self::escrowed_amount(
+                &auction_info,
+                highest_bidder_ft_temp_account,
+                auction_info.price,
+            )?;
+            Self::transfer_from_escrow(
+                token_program,
+                highest_bidder_ft_temp_account,
+                highest_bidder_ft_returning_account,
+                bid_authority,
+                bid_authority_account,
+                bid_signers_seeds,
+                escrowed_amount,
+            )?;
+            if auction_info.holds_ft_in_vault() {
+                Self::close_vault(
+                    &auction_info,
+                    account_info_iter,
+                    token_program,
+                    highest_bidder_ft_temp_account,
+                    exhibitor_account,
+                    None,
+                    vault_signers_seeds,
+                )?;
+            } else {
+                let bid_rent_payer_account = Self::rent_payer_account(
+                    &auction_info.highest_bidder_rent_payer_pubkey,
+                    highest_bidder_account,
+                    account_info_iter,
+                )?;
+                Self::close_temporary_ft(
+                    token_program,
+                    highest_bidder_ft_temp_account,
+                    bid_rent_payer_account,
+                    pda,
+                    pda_account,
+                    signers_seeds,
+                )?;
+            }
+        }
+
+        msg!("Transferring the winner's bond to the Exhibitor...");
+        Self::close_state_account(bond_account, exhibitor_account)?;
+
+        let rent_payer_account = Self::rent_payer_account(
+            &auction_info.rent_payer_pubkey,
+            exhibitor_account,
+            account_info_iter,
+        )?;
+        Self::close_escrow(
+            token_program,
+            exhibiting_nft_temp_account,
+            rent_payer_account,
+            pda,
+            pda_account,
+            escrow_account,
+            signers_seeds,
+        )
+    }
+
+    fn process_cancel_with_penalty(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let exhibitor_account = next_account_info(account_info_iter)?;
+
+        if !exhibitor_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let exhibiting_nft_temp_account = next_account_info(account_info_iter)?;
+        let exhibiting_nft_returning_account = next_account_info(account_info_iter)?;
+        let escrow_account = next_account_info(account_info_iter)?;
+        let auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+        let highest_bidder_ft_temp_account = next_account_info(account_info_iter)?;
+        let highest_bidder_ft_returning_account = next_account_info(account_info_iter)?;
+        let highest_bidder_account = next_account_info(account_info_iter)?;
+        let exhibitor_penalty_account = next_account_info(account_info_iter)?;
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if auction_info.kind != AuctionKind::English {
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if auction_info.exhibitor_pubkey != *exhibitor_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.exhibiting_nft_temp_pubkey != *exhibiting_nft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.end_at <= clock.unix_timestamp {
+            return Err(AuctionError::InactiveAuction.into());
+        }
+        if auction_info.highest_bidder_pubkey == Pubkey::default() {
+            return Err(AuctionError::NoBidderFound.into());
+        }
+        if auction_info.highest_bidder_pubkey != *highest_bidder_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.highest_bidder_ft_temp_pubkey != *highest_bidder_ft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.highest_bidder_ft_returning_pubkey
+            != *highest_bidder_ft_returning_account.key
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+        let vault_bump_seed = Self::vault_bump_seed(escrow_account.key, program_id);
+        let vault_signers_seeds: &[&[&[u8]]] = &[&[
+            &b"vault"[..],
+            escrow_account.key.as_ref(),
+            &[vault_bump_seed],
+        ]];
+        let (bid_authority, bid_authority_account, bid_signers_seeds) = Self::bid_authority(
+            &auction_info,
+            highest_bidder_ft_temp_account,
+            pda,
+            pda_account,
+            signers_seeds,
+            vault_signers_seeds,
+        );
+
+        Self::refund_runner_up(
+            &auction_info,
+            account_info_iter,
+            token_program,
+            pda,
+            pda_account,
+            signers_seeds,
+            vault_signers_seeds,
+        )?;
+
+        msg!("Returning the highest bid to the highest bidder...");
+        let highest_bid = if auction_info.pays_in_lamports {
+            let vault_account = highest_bidder_ft_temp_account;
+            Self::transfer_lamports(
+                vault_account,
+                highest_bidder_ft_returning_account,
+                auction_info.price,
+            )?;
+            msg!("Closing the Vault Account...");
+            Self::close_state_account(vault_account, exhibitor_account)?;
+            auction_info.price
+        } else {
+            let escrowed_amount = Self::escrowed_amount(
+                &auction_info,
+                highest_bidder_ft_temp_account,
+                auction_info.price,
+            )?;
+            Self::transfer_from_escrow(
+                token_program,
+                highest_bidder_ft_temp_account,
+                highest_bidder_ft_returning_account,
+                bid_authority,
+                bid_authority_account,
+                bid_signers_seeds,
+                escrowed_amount,
+            )?;
+            if auction_info.holds_ft_in_vault() {
+                Self::close_vault(
+                    &auction_info,
+                    account_info_iter,
+                    token_program,
+                    highest_bidder_ft_temp_account,
+                    exhibitor_account,
+                    None,
+                    vault_signers_seeds,
+                )?;
+            } else {
+                let bid_rent_payer_account = Self::rent_payer_account(
+                    &auction_info.highest_bidder_rent_payer_pubkey,
+                    highest_bidder_account,
+                    account_info_iter,
+                )?;
+                Self::close_temporary_ft(
+                    token_program,
+                    highest_bidder_ft_temp_account,
+                    bid_rent_payer_account,
+                    pda,
+                    pda_account,
+                    signers_seeds,
+                )?;
+            }
+            escrowed_amount
+        };
+
+        let penalty = (highest_bid as u128)
+            .checked_mul(auction_info.cancel_penalty_bps as u128)
+            .map(|penalty| penalty / BASIS_POINTS as u128)
+            .and_then(|penalty| u64::try_from(penalty).ok())
+            .ok_or(AuctionError::AmountOverflow)?;
+        if penalty > 0 {
+            msg!("Paying the cancellation penalty to the highest bidder...");
+            if auction_info.pays_in_lamports {
+                let system_program = exhibitor_penalty_account;
+                let transfer_penalty_ix = system_instruction::transfer(
+                    exhibitor_account.key,
+                    highest_bidder_ft_returning_account.key,
+                    penalty,
+                );
+                invoke(
+                    &transfer_penalty_ix,
+                    &[
+                        exhibitor_account.clone(),
+                        highest_bidder_ft_returning_account.clone(),
+                        system_program.clone(),
+                    ],
+                )?;
+            } else {
+                Self::deposit_tokens(
+                    token_program,
+                    exhibitor_penalty_account,
+                    highest_bidder_ft_returning_account,
+                    exhibitor_account,
+                    penalty,
+                )?;
+            }
+        }
+
+        let exhibiting_nft_temp_account_data =
+            TokenAccount::unpack(&exhibiting_nft_temp_account.try_borrow_data()?)?;
+        msg!("Transferring NFT to the Exhibitor...");
+        Self::transfer_from_escrow(
+            token_program,
+            exhibiting_nft_temp_account,
+            exhibiting_nft_returning_account,
+            pda,
+            pda_account,
+            signers_seeds,
+            exhibiting_nft_temp_account_data.amount,
+        )?;
+
+        let rent_payer_account = Self::rent_payer_account(
+            &auction_info.rent_payer_pubkey,
+            exhibitor_account,
+            account_info_iter,
+        )?;
+        Self::close_escrow(
+            token_program,
+            exhibiting_nft_temp_account,
+            rent_payer_account,
+            pda,
+            pda_account,
+            escrow_account,
+            signers_seeds,
+        )
+    }
+
+    fn process_offer_second_chance(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let exhibitor_account = next_account_info(account_info_iter)?;
+
+        if !exhibitor_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let escrow_account = next_account_info(account_info_iter)?;
+        let mut auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+        let highest_bidder_ft_temp_account = next_account_info(account_info_iter)?;
+        let highest_bidder_ft_returning_account = next_account_info(account_info_iter)?;
+        let highest_bidder_account = next_account_info(account_info_iter)?;
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if auction_info.exhibitor_pubkey != *exhibitor_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.runner_up_pubkey == Pubkey::default() {
+            return Err(AuctionError::NoBidderFound.into());
+        }
+        if auction_info.highest_bidder_pubkey != *highest_bidder_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.highest_bidder_ft_temp_pubkey != *highest_bidder_ft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.highest_bidder_ft_returning_pubkey
+            != *highest_bidder_ft_returning_account.key
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.end_at > clock.unix_timestamp {
+            return Err(AuctionError::ActiveAuction.into());
+        }
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+        let vault_bump_seed = Self::vault_bump_seed(escrow_account.key, program_id);
+        let vault_signers_seeds: &[&[&[u8]]] = &[&[
+            &b"vault"[..],
+            escrow_account.key.as_ref(),
+            &[vault_bump_seed],
+        ]];
+        let (bid_authority, bid_authority_account, bid_signers_seeds) = Self::bid_authority(
+            &auction_info,
+            highest_bidder_ft_temp_account,
+            pda,
+            pda_account,
+            signers_seeds,
+            vault_signers_seeds,
+        );
+
+        // Within the grace period the winner can still settle, unless the admin disqualifies them.
+        let settle_by = auction_info
+            .end_at
+            .checked_add(SETTLEMENT_GRACE_PERIOD_SEC)
+            .ok_or(AuctionError::AmountOverflow)?;
+        if settle_by > clock.unix_timestamp {
+            let admin_account = next_account_info(account_info_iter)?;
+            let config_account = next_account_info(account_info_iter)?;
+            let config_info = Self::unpack_config(config_account, program_id)?;
+            Self::check_admin(&config_info, admin_account)?;
+            msg!("The admin disqualified the winner");
+        }
+
+        if auction_info.bond_amount > 0 {
+            let bond_account = next_account_info(account_info_iter)?;
+            let bond_info = Bond::unpack(&bond_account.try_borrow_data()?)?;
+            if bond_info.auction_pubkey != *escrow_account.key
+                || bond_info.bidder_pubkey != auction_info.highest_bidder_pubkey
+            {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            msg!("Transferring the winner's bond to the Exhibitor...");
+            Self::close_state_account(bond_account, exhibitor_account)?;
+        }
+
+        msg!("Returning the winning bid to the winner...");
+        let escrowed_amount = Self::escrowed_amount(
+            &auction_info,
+            highest_bidder_ft_temp_account,
+            auction_info.price,
+        )?;
+        Self::transfer_from_escrow(
+            token_program,
+            highest_bidder_ft_temp_account,
+            highest_bidder_ft_returning_account,
+            bid_authority,
+            bid_authority_account,
+            bid_signers_seeds,
+            escrowed_amount,
+        )?;
+        // The vault of the auction still holds the runner-up's bid.
+        if !auction_info.holds_ft_in_vault() {
+            let bid_rent_payer_account = Self::rent_payer_account(
+                &auction_info.highest_bidder_rent_payer_pubkey,
+                highest_bidder_account,
+                account_info_iter,
+            )?;
+            Self::close_temporary_ft(
+                token_program,
+                highest_bidder_ft_temp_account,
+                bid_rent_payer_account,
+                pda,
+                pda_account,
+                signers_seeds,
+            )?;
+        }
+
+        msg!("Making the runner-up the highest bidder...");
+        auction_info.promote_runner_up();
+        auction_info.second_chance_until = clock
+            .unix_timestamp
+            .checked_add(SECOND_CHANCE_WINDOW_SEC)
+            .ok_or(AuctionError::AmountOverflow)?;
+        Auction::pack(auction_info, &mut escrow_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    fn process_expire_second_chance(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let exhibitor_account = next_account_info(account_info_iter)?;
+
+        if !exhibitor_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let exhibiting_nft_temp_account = next_account_info(account_info_iter)?;
+        let exhibiting_nft_returning_account = next_account_info(account_info_iter)?;
+        let escrow_account = next_account_info(account_info_iter)?;
+        let auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+        let highest_bidder_ft_temp_account = next_account_info(account_info_iter)?;
+        let highest_bidder_ft_returning_account = next_account_info(account_info_iter)?;
+        let highest_bidder_account = next_account_info(account_info_iter)?;
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if auction_info.exhibitor_pubkey != *exhibitor_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.exhibiting_nft_temp_pubkey != *exhibiting_nft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.highest_bidder_pubkey != *highest_bidder_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.highest_bidder_ft_temp_pubkey != *highest_bidder_ft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.highest_bidder_ft_returning_pubkey
+            != *highest_bidder_ft_returning_account.key
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.second_chance_until == 0 {
+            msg!("No second chance was offered");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if auction_info.second_chance_until >= clock.unix_timestamp {
+            msg!(
+                "The runner-up can accept for {} more seconds",
+                (auction_info.second_chance_until - clock.unix_timestamp)
+            );
+            return Err(AuctionError::ActiveAuction.into());
+        }
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+        let vault_bump_seed = Self::vault_bump_seed(escrow_account.key, program_id);
+        let vault_signers_seeds: &[&[&[u8]]] = &[&[
+            &b"vault"[..],
+            escrow_account.key.as_ref(),
+            &[vault_bump_seed],
+        ]];
+        let (bid_authority, bid_authority_account, bid_signers_seeds) = Self::bid_authority(
+            &auction_info,
+            highest_bidder_ft_temp_account,
+            pda,
+            pda_account,
+            signers_seeds,
+            vault_signers_seeds,
+        );
+
+        let exhibiting_nft_temp_account_data =
+            TokenAccount::unpack(&exhibiting_nft_temp_account.try_borrow_data()?)?;
+        msg!("Transferring NFT to the Exhibitor...");
+        Self::transfer_from_escrow(
+            token_program,
+            exhibiting_nft_temp_account,
+            exhibiting_nft_returning_account,
+            pda,
+            pda_account,
+            signers_seeds,
+            exhibiting_nft_temp_account_data.amount,
+        )?;
+
+        msg!("Returning the bid to the runner-up...");
+        let escrowed_amount = Self::escrowed_amount(
+            &auction_info,
+            highest_bidder_ft_temp_account,
+            auction_info.price,
+        )?;
+        Self::transfer_from_escrow(
+            token_program,
+            highest_bidder_ft_temp_account,
+            highest_bidder_ft_returning_account,
+            bid_authority,
+            bid_authority_account,
+            bid_signers_seeds,
+            escrowed_amount,
+        )?;
+        if auction_info.holds_ft_in_vault() {
+            Self::close_vault(
+                &auction_info,
+                account_info_iter,
+                token_program,
+                highest_bidder_ft_temp_account,
+                exhibitor_account,
+                None,
+                vault_signers_seeds,
+            )?;
+        } else {
+            let bid_rent_payer_account = Self::rent_payer_account(
+                &auction_info.highest_bidder_rent_payer_pubkey,
+                highest_bidder_account,
+                account_info_iter,
+            )?;
+            Self::close_temporary_ft(
+                token_program,
+                highest_bidder_ft_temp_account,
+                bid_rent_payer_account,
+                pda,
+                pda_account,
+                signers_seeds,
+            )?;
+        }
+
+        let rent_payer_account = Self::rent_payer_account(
+            &auction_info.rent_payer_pubkey,
+            exhibitor_account,
+            account_info_iter,
+        )?;
+        Self::close_escrow(
+            token_program,
+            exhibiting_nft_temp_account,
+            rent_payer_account,
+            pda,
+            pda_account,
+            escrow_account,
+            signers_seeds,
+        )
+    }
+
+    fn process_refund_runner_up(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let escrow_account = next_account_info(account_info_iter)?;
+        if escrow_account.owner != program_id {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+
+        if auction_info.runner_up_pubkey == Pubkey::default() {
+            return Err(AuctionError::NoBidderFound.into());
+        }
+        // The exhibitor can offer the runner-up a second chance until the grace period and the offer window ran out.
+        let refund_after = auction_info
+            .end_at
+            .checked_add(SETTLEMENT_GRACE_PERIOD_SEC)
+            .and_then(|settle_by| settle_by.checked_add(SECOND_CHANCE_WINDOW_SEC))
+            .ok_or(AuctionError::AmountOverflow)?;
+        if refund_after >= clock.unix_timestamp {
+            msg!(
+                "The runner-up can be refunded in {} seconds",
+                (refund_after - clock.unix_timestamp)
+            );
+            return Err(AuctionError::ActiveAuction.into());
+        }
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+        let vault_bump_seed = Self::vault_bump_seed(escrow_account.key, program_id);
+        let vault_signers_seeds: &[&[&[u8]]] = &[&[
+            &b"vault"[..],
+            escrow_account.key.as_ref(),
+            &[vault_bump_seed],
+        ]];
+
+        Self::refund_runner_up(
+            &auction_info,
+            account_info_iter,
+            token_program,
+            pda,
+            pda_account,
+            signers_seeds,
+            vault_signers_seeds,
+        )?;
+        auction_info.clear_runner_up();
+        Auction::pack(auction_info, &mut escrow_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    fn process_retract_bid(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let highest_bidder_account = next_account_info(account_info_iter)?;
+
+        if !highest_bidder_account.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let highest_bidder_ft_temp_account = next_account_info(account_info_iter)?;
+        let highest_bidder_ft_returning_account = next_account_info(account_info_iter)?;
+        let escrow_account = next_account_info(account_info_iter)?;
+        let mut auction_info = Auction::unpack(&escrow_account.try_borrow_data()?)?;
+        let exhibitor_ft_receiving_account = next_account_info(account_info_iter)?;
+
+        let sys_var_clock_account = next_account_info(account_info_iter)?;
+        let clock = &Clock::from_account_info(sys_var_clock_account)?;
+        let config_account = next_account_info(account_info_iter)?;
+        let config_info = Self::unpack_config(config_account, program_id)?;
+
+        // Only English auctions paid in FT keep the runner-up's bid to restore.
+        if auction_info.kind != AuctionKind::English || auction_info.pays_in_lamports {
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        if auction_info.highest_bidder_pubkey != *highest_bidder_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.highest_bidder_ft_temp_pubkey != *highest_bidder_ft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.highest_bidder_ft_returning_pubkey
+            != *highest_bidder_ft_returning_account.key
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.prices_in_reference {
+            let exhibitor_ft_receiving_account_data =
+                TokenAccount::unpack(&exhibitor_ft_receiving_account.try_borrow_data()?)?;
+            if exhibitor_ft_receiving_account_data.owner != auction_info.exhibitor_pubkey {
+                return Err(ProgramError::InvalidAccountData);
+            }
+        } else if auction_info.exhibitor_ft_receiving_pubkey != *exhibitor_ft_receiving_account.key
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let retract_by = auction_info
+            .end_at
+            .checked_sub(RETRACTION_CUTOFF_SEC)
+            .ok_or(AuctionError::AmountOverflow)?;
+        if retract_by <= clock.unix_timestamp {
+            msg!(
+                "Bids can't be retracted in the final {} seconds",
+                RETRACTION_CUTOFF_SEC
+            );
+            return Err(AuctionError::InactiveAuction.into());
+        }
+
+        let (pda, bump_seed) = Pubkey::find_program_address(&[b"escrow"], program_id);
+        let token_program = next_account_info(account_info_iter)?;
+        let pda_account = next_account_info(account_info_iter)?;
+        let signers_seeds: &[&[&[u8]]] = &[&[&b"escrow"[..], &[bump_seed]]];
+        let vault_bump_seed = Self::vault_bump_seed(escrow_account.key, program_id);
+        let vault_signers_seeds: &[&[&[u8]]] = &[&[
+            &b"vault"[..],
+            escrow_account.key.as_ref(),
+            &[vault_bump_seed],
+        ]];
+        let (bid_authority, bid_authority_account, bid_signers_seeds) = Self::bid_authority(
+            &auction_info,
+            highest_bidder_ft_temp_account,
+            pda,
+            pda_account,
+            signers_seeds,
+            vault_signers_seeds,
+        );
+
+        let escrowed_amount = Self::escrowed_amount(
+            &auction_info,
+            highest_bidder_ft_temp_account,
+            auction_info.price,
+        )?;
+        let penalty = (escrowed_amount as u128)
+            .checked_mul(config_info.retraction_penalty_bps as u128)
+            .map(|penalty| penalty / BASIS_POINTS as u128)
+            .and_then(|penalty| u64::try_from(penalty).ok())
+            .ok_or(AuctionError::AmountOverflow)?;
+        // A free retraction would let bidders push the price up and walk away at no cost.
+        if penalty == 0 {
+            msg!("Bids can't be retracted without a penalty");
+            return Err(AuctionError::InvalidInstruction.into());
+        }
+        msg!("Paying the retraction penalty to the Exhibitor...");
+        Self::transfer_from_escrow(
+            token_program,
+            highest_bidder_ft_temp_account,
+            exhibitor_ft_receiving_account,
+            bid_authority,
+            bid_authority_account,
+            bid_signers_seeds,
+            penalty,
+        )?;
+
+        msg!("Returning the retracted bid to the bidder...");
+        Self::transfer_from_escrow(
+            token_program,
+            highest_bidder_ft_temp_account,
+            highest_bidder_ft_returning_account,
+            bid_authority,
+            bid_authority_account,
+            bid_signers_seeds,
+            escrowed_amount - penalty,
+        )?;
+        // The vault of the auction still holds the runner-up's bid.
+        if !auction_info.holds_ft_in_vault() {
+            let bid_rent_payer_account = Self::rent_payer_account(
+                &auction_info.highest_bidder_rent_payer_pubkey,
+                highest_bidder_account,
+                account_info_iter,
+            )?;
+            Self::close_temporary_ft(
+                token_program,
+                highest_bidder_ft_temp_account,
+                bid_rent_payer_account,
+                pda,
+                pda_account,
+                signers_seeds,
+            )?;
+        }
+
+        if auction_info.runner_up_pubkey != Pubkey::default() {
+            msg!("Restoring the runner-up's bid...");
+            auction_info.promote_runner_up();
+        } else {
+            msg!("Restoring the initial price...");
+            auction_info.price = auction_info.initial_price;
+            auction_info.highest_bidder_pubkey = Pubkey::default();
+            auction_info.highest_bidder_ft_temp_pubkey = Pubkey::default();
+            auction_info.highest_bidder_ft_returning_pubkey = Pubkey::default();
+            auction_info.highest_bidder_referrer_pubkey = Pubkey::default();
+            auction_info.referral_bps = 0;
+            auction_info.highest_bidder_rent_payer_pubkey = Pubkey::default();
+        }
+        Auction::pack(auction_info, &mut escrow_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+
+    /// Checks the bidder posted the bond a bonded auction requires.
+    fn check_bond<'a, 'b: 'a>(
+        auction_info: &Auction,
+        escrow_account: &AccountInfo,
+        bidder_account: &AccountInfo,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        if auction_info.bond_amount == 0 {
+            return Ok(());
+        }
+        let bond_account = next_account_info(account_info_iter)?;
+        let (bond_pubkey, _bump_seed) = Pubkey::find_program_address(
+            &[
+                b"bond",
+                escrow_account.key.as_ref(),
+                bidder_account.key.as_ref(),
+            ],
+            program_id,
+        );
+        if bond_pubkey != *bond_account.key || bond_account.owner != program_id {
+            return Err(AuctionError::BondRequired.into());
+        }
+        Bond::unpack(&bond_account.try_borrow_data()?).map_err(|_| AuctionError::BondRequired)?;
+        Ok(())
+    }
+
+    /// Checks the bidder of a token-gated auction holds an NFT of the gate collection, or enough of the gate mint.
+    fn check_gate<'a, 'b: 'a>(
+        auction_info: &Auction,
+        bidder_account: &AccountInfo,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    ) -> ProgramResult {
+        if auction_info.gate_pubkey == Pubkey::default() {
+            return Ok(());
+        }
+        let gate_token_account = next_account_info(account_info_iter)?;
+        if *gate_token_account.owner != spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let gate_token_account_data = TokenAccount::unpack(&gate_token_account.try_borrow_data()?)?;
+        if gate_token_account_data.owner != *bidder_account.key {
+            return Err(AuctionError::NotGateHolder.into());
+        }
+
+        let holds_gate = if auction_info.gate_amount == 0 {
+            let metadata_account = next_account_info(account_info_iter)?;
+            let metadata =
+                Metadata::from_account_info(metadata_account, &gate_token_account_data.mint)?;
+            gate_token_account_data.amount > 0
+                && metadata.is_verified_member_of(&auction_info.gate_pubkey)
+        } else {
+            gate_token_account_data.mint == auction_info.gate_pubkey
+                && gate_token_account_data.amount >= auction_info.gate_amount
+        };
+        if !holds_gate {
+            return Err(AuctionError::NotGateHolder.into());
+        }
+        Ok(())
+    }
+
+    /// Checks `wallet` is a leaf of the Merkle allowlist `root`, a zeroed root allowing anyone.
+    fn check_allowlist(root: &[u8; 32], wallet: &Pubkey, proof: &[[u8; 32]]) -> ProgramResult {
+        if *root == [0; 32] {
+            return Ok(());
+        }
+        let computed_root =
+            proof
+                .iter()
+                .fold(keccak::hash(wallet.as_ref()).to_bytes(), |node, sibling| {
+                    if node <= *sibling {
+                        keccak::hashv(&[&node, sibling]).to_bytes()
+                    } else {
+                        keccak::hashv(&[sibling, &node]).to_bytes()
+                    }
+                });
+        if computed_root != *root {
+            return Err(AuctionError::NotAllowlisted.into());
+        }
+        Ok(())
+    }
+
+    /// Reads the referral share of a bid from the config when the bid is referred.
+    /// Only a referrer naming the bidder's own wallet is rejected: a bidder referring another wallet they control
+    /// can't be told apart from a genuine referral on-chain.
+    fn referral_bps(
+        referrer: Option<Pubkey>,
+        bidder_account: &AccountInfo,
+        config_info: &Config,
+    ) -> Result<u16, ProgramError> {
+        match referrer {
+            Some(referrer_pubkey) if referrer_pubkey == *bidder_account.key => {
+                msg!("Bidders can't refer themselves");
+                Err(AuctionError::InvalidInstruction.into())
+            }
+            Some(_) => Ok(config_info.referral_bps),
+            None => Ok(0),
+        }
+    }
+
+    /// Protocol fee owed on `amount`.
+    fn protocol_fee(fee_bps: u16, amount: u64) -> u64 {
+        (amount as u128 * fee_bps as u128 / BASIS_POINTS as u128) as u64
+    }
+
+    /// Takes the treasury's account off the list, only passed when a fee is taken.
+    fn next_treasury_account<'a, 'b>(
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        fee_bps: u16,
+    ) -> Result<Option<&'a AccountInfo<'b>>, ProgramError> {
+        if fee_bps == 0 {
+            return Ok(None);
+        }
+        next_account_info(account_info_iter).map(Some)
+    }
+
+    /// Pays `amount` out of escrow to the seller, minus the protocol fee sent to the treasury.
+    fn transfer_proceeds<'a, 'b>(
+        proceeds: &Proceeds<'a, 'b, '_>,
+        seller_receiving_account: &'a AccountInfo<'b>,
+        treasury_account: Option<&'a AccountInfo<'b>>,
+        fee_bps: u16,
+        treasury_pubkey: &Pubkey,
+        amount: u64,
+    ) -> ProgramResult {
+        let fee =
+            Self::pay_protocol_fee(proceeds, treasury_account, fee_bps, treasury_pubkey, amount)?;
+        proceeds.transfer(seller_receiving_account, amount - fee)
+    }
+
+    /// Sends the protocol fee on `amount` out of escrow to the treasury, returning the fee.
+    /// The treasury's account is only missing when `fee_bps` is zero.
+    fn pay_protocol_fee<'a, 'b>(
+        proceeds: &Proceeds<'a, 'b, '_>,
+        treasury_account: Option<&'a AccountInfo<'b>>,
+        fee_bps: u16,
+        treasury_pubkey: &Pubkey,
+        amount: u64,
+    ) -> Result<u64, ProgramError> {
+        let treasury_account = match treasury_account {
+            Some(treasury_account) => treasury_account,
+            None => return Ok(0),
+        };
+        proceeds.check_recipient(treasury_account, treasury_pubkey)?;
+        let fee = Self::protocol_fee(fee_bps, amount);
+        if fee > 0 {
+            msg!("Transferring the protocol fee to the Treasury...");
+            proceeds.transfer(treasury_account, fee)?;
+        }
+        Ok(fee)
+    }
+
+    /// Pays the referrer of the winning bid their share of `amount` out of escrow, returning the share.
+    fn pay_referral<'a, 'b>(
+        proceeds: &Proceeds<'a, 'b, '_>,
+        auction_info: &Auction,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        amount: u64,
+    ) -> Result<u64, ProgramError> {
+        if auction_info.highest_bidder_referrer_pubkey == Pubkey::default() {
+            return Ok(0);
+        }
+        let referrer_receiving_account = next_account_info(account_info_iter)?;
+        proceeds.check_recipient(
+            referrer_receiving_account,
+            &auction_info.highest_bidder_referrer_pubkey,
+        )?;
+
+        let referral =
+            (amount as u128 * auction_info.referral_bps as u128 / BASIS_POINTS as u128) as u64;
+        if referral > 0 {
+            msg!("Transferring the referral share to the Referrer...");
+            proceeds.transfer(referrer_receiving_account, referral)?;
+        }
+        Ok(referral)
+    }
+
+    /// Splits `amount` out of escrow among the payees of the auction, the rounding dust going to
+    /// the designated payee. Without payees everything goes to the exhibitor's receiving account.
+    fn pay_payees<'a, 'b>(
+        proceeds: &Proceeds<'a, 'b, '_>,
+        auction_info: &Auction,
+        exhibitor_receiving_account: &'a AccountInfo<'b>,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        amount: u64,
+    ) -> ProgramResult {
+        if auction_info.payee_count == 0 {
+            msg!("Transferring the proceeds to the Exhibitor...");
+            return proceeds.transfer(exhibitor_receiving_account, amount);
+        }
+
+        let payee_count = auction_info.payee_count as usize;
+        let mut payouts: Vec<u64> = auction_info.payee_shares_bps[..payee_count]
+            .iter()
+            .map(|share_bps| (amount as u128 * *share_bps as u128 / BASIS_POINTS as u128) as u64)
+            .collect();
+        let dust = amount
+            .checked_sub(payouts.iter().sum())
+            .ok_or(AuctionError::ExpectedAmountMismatch)?;
+        payouts[auction_info.dust_payee_index as usize] += dust;
+
+        for (payout, payee_receiving_pubkey) in payouts
+            .iter()
+            .zip(auction_info.payee_receiving_pubkeys.iter())
+        {
+            let payee_receiving_account = next_account_info(account_info_iter)?;
+            if *payee_receiving_pubkey != *payee_receiving_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if *payout > 0 {
+                msg!(
+                    "Transferring the proceeds to the Payee {}...",
+                    payee_receiving_pubkey
+                );
+                proceeds.transfer(payee_receiving_account, *payout)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Pays the verified creators their share of the royalty on `amount` out of escrow,
+    /// returning the total paid. Unverified creators' shares stay with the seller.
+    fn pay_royalties<'a, 'b>(
+        proceeds: &Proceeds<'a, 'b, '_>,
+        metadata: &Metadata,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        amount: u64,
+    ) -> Result<u64, ProgramError> {
+        let royalty =
+            amount as u128 * metadata.seller_fee_basis_points as u128 / BASIS_POINTS as u128;
+        let mut paid: u64 = 0;
+        for creator in metadata.creators.iter().filter(|creator| creator.verified) {
+            let creator_receiving_account = next_account_info(account_info_iter)?;
+            proceeds.check_recipient(creator_receiving_account, &creator.address)?;
+
+            let share = (royalty * creator.share as u128 / 100) as u64;
+            if share > 0 {
+                msg!(
+                    "Transferring the royalty to the Creator {}...",
+                    creator.address
+                );
+                proceeds.transfer(creator_receiving_account, share)?;
+                paid = paid
+                    .checked_add(share)
+                    .ok_or(AuctionError::AmountOverflow)?;
+            }
+        }
+        if paid > amount {
+            return Err(AuctionError::AmountOverflow.into());
+        }
+        Ok(paid)
+    }
+
+    fn transfer_lamports(
+        source_account: &AccountInfo,
+        destination_account: &AccountInfo,
+        amount: u64,
+    ) -> ProgramResult {
+        **source_account.try_borrow_mut_lamports()? = source_account
+            .lamports()
+            .checked_sub(amount)
+            .ok_or(AuctionError::AmountOverflow)?;
+        **destination_account.try_borrow_mut_lamports()? = destination_account
+            .lamports()
+            .checked_add(amount)
+            .ok_or(AuctionError::AmountOverflow)?;
+        Ok(())
+    }
+
+    /// Creates the PDA `new_account` with `space` bytes owned by `owner`, funded up to `lamports` by `payer`.
+    /// `create_account` fails on an address already holding lamports, so anyone could block a predictable PDA
+    /// by funding it first: such an account is topped up, allocated and assigned instead.
+    fn create_pda_account<'a, 'b>(
+        payer_account: &'a AccountInfo<'b>,
+        new_account: &'a AccountInfo<'b>,
+        system_program: &'a AccountInfo<'b>,
+        lamports: u64,
+        space: usize,
+        owner: &Pubkey,
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if new_account.lamports() == 0 {
+            let create_account_ix = system_instruction::create_account(
+                payer_account.key,
+                new_account.key,
+                lamports,
+                space as u64,
+                owner,
+            );
+            return invoke_signed(
+                &create_account_ix,
+                &[
+                    payer_account.clone(),
+                    new_account.clone(),
+                    system_program.clone(),
+                ],
+                signers_seeds,
+            );
+        }
+
+        let top_up = lamports.saturating_sub(new_account.lamports());
+        if top_up > 0 {
+            invoke(
+                &system_instruction::transfer(payer_account.key, new_account.key, top_up),
+                &[
+                    payer_account.clone(),
+                    new_account.clone(),
+                    system_program.clone(),
+                ],
+            )?;
+        }
+        invoke_signed(
+            &system_instruction::allocate(new_account.key, space as u64),
+            &[new_account.clone(), system_program.clone()],
+            signers_seeds,
+        )?;
+        invoke_signed(
+            &system_instruction::assign(new_account.key, owner),
+            &[new_account.clone(), system_program.clone()],
+            signers_seeds,
+        )
+    }
+
+    fn escrow_tokens<'a, 'b>(
+        token_program: &'a AccountInfo<'b>,
+        source_account: &'a AccountInfo<'b>,
+        temp_account: &'a AccountInfo<'b>,
+        owner_account: &'a AccountInfo<'b>,
+        pda: Pubkey,
+        amount: u64,
+    ) -> ProgramResult {
+        Self::deposit_tokens(
+            token_program,
+            source_account,
+            temp_account,
+            owner_account,
+            amount,
+        )?;
+
+        Self::assign_to_pda(token_program, temp_account, owner_account, pda)
+    }
+
+    fn deposit_tokens<'a, 'b>(
+        token_program: &'a AccountInfo<'b>,
+        source_account: &'a AccountInfo<'b>,
+        destination_account: &'a AccountInfo<'b>,
+        owner_account: &'a AccountInfo<'b>,
+        amount: u64,
+    ) -> ProgramResult {
+        let transfer_ix = spl_token::instruction::transfer(
+            token_program.key,
+            source_account.key,
+            destination_account.key,
+            owner_account.key,
+            &[], // authority_pubkey is default signer when the signer_pubkeys is empty.
+            amount,
+        )?;
+        invoke(
+            &transfer_ix,
+            &[
+                source_account.clone(),
+                destination_account.clone(),
+                owner_account.clone(),
+                token_program.clone(),
+            ],
+        )
+    }
+
+    fn assign_to_pda<'a, 'b>(
+        token_program: &'a AccountInfo<'b>,
+        temp_account: &'a AccountInfo<'b>,
+        owner_account: &'a AccountInfo<'b>,
+        pda: Pubkey,
+    ) -> ProgramResult {
+        let owner_change_ix = spl_token::instruction::set_authority(
+            token_program.key,
+            temp_account.key,
+            Some(&pda),
+            spl_token::instruction::AuthorityType::AccountOwner,
+            owner_account.key,
+            &[], // owner_pubkey is default signer when the signer_pubkeys is empty.
+        )?;
+        msg!("Changing ownership of the token account...");
+        invoke(
+            &owner_change_ix,
+            &[
+                temp_account.clone(),
+                owner_account.clone(),
+                token_program.clone(),
+            ],
+        )
+    }
+
+    fn transfer_from_escrow<'a, 'b>(
+        token_program: &'a AccountInfo<'b>,
+        temp_account: &'a AccountInfo<'b>,
+        destination_account: &'a AccountInfo<'b>,
+        pda: Pubkey,
+        pda_account: &'a AccountInfo<'b>,
+        signers_seeds: &[&[&[u8]]],
+        amount: u64,
+    ) -> ProgramResult {
+        let transfer_ix = spl_token::instruction::transfer(
+            token_program.key,
+            temp_account.key,
+            destination_account.key,
+            &pda,
+            &[], // authority_pubkey is default signer when the signer_pubkeys is empty.
+            amount,
+        )?;
+        invoke_signed(
+            &transfer_ix,
+            &[
+                temp_account.clone(),
+                destination_account.clone(),
+                pda_account.clone(),
+                token_program.clone(),
+            ],
+            signers_seeds,
+        )
+    }
+
+    /// Amount of a bid of `price` held in escrow: the price itself when bids are held in the vault of the auction,
+    /// otherwise the balance of the bid's temporary account, which may be in another mint for reference priced auctions.
+    fn escrowed_amount(
+        auction_info: &Auction,
+        temp_account: &AccountInfo,
+        price: u64,
+    ) -> Result<u64, ProgramError> {
+        if auction_info.pays_in_lamports || auction_info.holds_ft_in_vault() {
+            return Ok(price);
+        }
+        Ok(TokenAccount::unpack(&temp_account.try_borrow_data()?)?.amount)
+    }
+
+    /// Bump seed of the vault of the auction held in `escrow_pubkey`.
+    fn vault_bump_seed(escrow_pubkey: &Pubkey, program_id: &Pubkey) -> u8 {
+        Pubkey::find_program_address(&[b"vault", escrow_pubkey.as_ref()], program_id).1
+    }
+
+    /// Authority of a bid held in `temp_account` and the seeds it signs with. The vault of an auction is the
+    /// authority of its own FT, so its seeds can't move the bids of any other auction, while temporary accounts
+    /// are held by the PDA.
+    fn bid_authority<'a, 'b, 'c>(
+        auction_info: &Auction,
+        temp_account: &'a AccountInfo<'b>,
+        pda: Pubkey,
+        pda_account: &'a AccountInfo<'b>,
+        signers_seeds: &'c [&'c [&'c [u8]]],
+        vault_signers_seeds: &'c [&'c [&'c [u8]]],
+    ) -> (Pubkey, &'a AccountInfo<'b>, &'c [&'c [&'c [u8]]]) {
+        if auction_info.holds_ft_in_vault() {
+            (*temp_account.key, temp_account, vault_signers_seeds)
+        } else {
+            (pda, pda_account, signers_seeds)
+        }
+    }
+
+    /// Closes the FT vault of an auction once every bid in it was paid out.
+    /// FT transferred into the vault outside of a bid would keep it from closing, so whatever is left goes to
+    /// the exhibitor's FT account first, taken from the next account unless given.
+    fn close_vault<'a, 'b: 'a>(
+        auction_info: &Auction,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        token_program: &'a AccountInfo<'b>,
+        vault_account: &'a AccountInfo<'b>,
+        exhibitor_account: &'a AccountInfo<'b>,
+        exhibitor_ft_receiving_account: Option<&'a AccountInfo<'b>>,
+        vault_signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let vault_account_data = TokenAccount::unpack(&vault_account.try_borrow_data()?)?;
+        if vault_account_data.amount > 0 {
+            let exhibitor_ft_receiving_account = match exhibitor_ft_receiving_account {
+                Some(exhibitor_ft_receiving_account) => exhibitor_ft_receiving_account,
+                None => next_account_info(account_info_iter)?,
+            };
+            if auction_info.exhibitor_ft_receiving_pubkey != *exhibitor_ft_receiving_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            msg!("Transferring the FT left in the Vault to the Exhibitor...");
+            Self::transfer_from_escrow(
+                token_program,
+                vault_account,
+                exhibitor_ft_receiving_account,
+                *vault_account.key,
+                vault_account,
+                vault_signers_seeds,
+                vault_account_data.amount,
+            )?;
+        }
+        msg!("Closing the Vault Account...");
+        Self::close_temporary_ft(
+            token_program,
+            vault_account,
+            exhibitor_account,
+            *vault_account.key,
+            vault_account,
+            vault_signers_seeds,
+        )
+    }
+
+    /// Refunds the runner-up's bid still held in escrow, if any, out of the next accounts.
+    fn refund_runner_up<'a, 'b: 'a>(
+        auction_info: &Auction,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        token_program: &'a AccountInfo<'b>,
+        pda: Pubkey,
+        pda_account: &'a AccountInfo<'b>,
+        signers_seeds: &[&[&[u8]]],
+        vault_signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        if auction_info.runner_up_pubkey == Pubkey::default() {
+            return Ok(());
+        }
+        let runner_up_account = next_account_info(account_info_iter)?;
+        let runner_up_ft_temp_account = next_account_info(account_info_iter)?;
+        let runner_up_ft_returning_account = next_account_info(account_info_iter)?;
+
+        if auction_info.runner_up_pubkey != *runner_up_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.runner_up_ft_temp_pubkey != *runner_up_ft_temp_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if auction_info.runner_up_ft_returning_pubkey != *runner_up_ft_returning_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let escrowed_amount = Self::escrowed_amount(
+            auction_info,
+            runner_up_ft_temp_account,
+            auction_info.runner_up_price,
+        )?;
+        let (bid_authority, bid_authority_account, bid_signers_seeds) = Self::bid_authority(
+            auction_info,
+            runner_up_ft_temp_account,
+            pda,
+            pda_account,
+            signers_seeds,
+            vault_signers_seeds,
+        );
+        msg!("Returning the runner-up's bid from the escrow account...");
+        Self::transfer_from_escrow(
+            token_program,
+            runner_up_ft_temp_account,
+            runner_up_ft_returning_account,
+            bid_authority,
+            bid_authority_account,
+            bid_signers_seeds,
+            escrowed_amount,
+        )?;
+        if auction_info.holds_ft_in_vault() {
+            return Ok(());
+        }
+
+        let rent_payer_account = Self::rent_payer_account(
+            &auction_info.runner_up_rent_payer_pubkey,
+            runner_up_account,
+            account_info_iter,
+        )?;
+        Self::close_temporary_ft(
+            token_program,
+            runner_up_ft_temp_account,
+            rent_payer_account,
+            pda,
+            pda_account,
+            signers_seeds,
+        )
+    }
+
+    /// Checks the rent payer named by an instruction signed it, taking their account off the list unless they are
+    /// `signer_account`, and returns their wallet pubkey.
+    fn check_rent_payer<'a, 'b: 'a>(
+        rent_payer: Option<Pubkey>,
+        signer_account: &AccountInfo,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    ) -> Result<Pubkey, ProgramError> {
+        let rent_payer_pubkey = match rent_payer {
+            Some(rent_payer_pubkey) if rent_payer_pubkey != *signer_account.key => {
+                rent_payer_pubkey
+            }
+            _ => return Ok(*signer_account.key),
+        };
+        let rent_payer_account = next_account_info(account_info_iter)?;
+        if rent_payer_pubkey != *rent_payer_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !rent_payer_account.is_signer || !rent_payer_account.is_writable {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        Ok(rent_payer_pubkey)
+    }
+
+    /// Returns the account the rent of an auction's account is returned to: `default_account` when it paid the
+    /// rent, otherwise the next account, which must be the recorded rent payer.
+    fn rent_payer_account<'a, 'b: 'a>(
+        rent_payer: &Pubkey,
+        default_account: &'a AccountInfo<'b>,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    ) -> Result<&'a AccountInfo<'b>, ProgramError> {
+        if *rent_payer == *default_account.key {
+            return Ok(default_account);
+        }
+        let rent_payer_account = next_account_info(account_info_iter)?;
+        if *rent_payer != *rent_payer_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(rent_payer_account)
+    }
+
+    fn close_escrow<'a, 'b>(
+        token_program: &'a AccountInfo<'b>,
+        exhibiting_nft_temp_account: &'a AccountInfo<'b>,
+        rent_payer_account: &'a AccountInfo<'b>,
+        pda: Pubkey,
+        pda_account: &'a AccountInfo<'b>,
+        escrow_account: &'a AccountInfo<'b>,
+        signers_seed: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let close_pdas_temp_acc_ix = spl_token::instruction::close_account(
+            token_program.key,
+            exhibiting_nft_temp_account.key,
+            rent_payer_account.key,
+            &pda,
+            &[], // owner_pubkey is default signer when the signer_pubkeys is empty.
+        )?;
+        msg!("Closing the exhibitor's NFT temporary account...");
+        invoke_signed(
+            &close_pdas_temp_acc_ix,
+            &[
+                exhibiting_nft_temp_account.clone(),
+                rent_payer_account.clone(),
+                pda_account.clone(),
+                token_program.clone(),
+            ],
+            signers_seed,
+        )?;
+
+        msg!("Closing the Escrow Account...");
+        Self::close_state_account(escrow_account, rent_payer_account)
+    }
+
+    fn close_state_account<'a, 'b>(
+        state_account: &'a AccountInfo<'b>,
+        destination_account: &'a AccountInfo<'b>,
+    ) -> ProgramResult {
+        **destination_account.try_borrow_mut_lamports()? = destination_account
+            .lamports()
+            .checked_add(state_account.lamports())
+            .ok_or(AuctionError::AmountOverflow)?;
+        **state_account.try_borrow_mut_lamports()? = 0;
+        *state_account.try_borrow_mut_data()? = &mut [];
+
+        Ok(())
+    }
+
+    fn close_temporary_ft<'a, 'b>(
+        token_program: &'a AccountInfo<'b>,
+        highest_bidder_ft_temp_account: &'a AccountInfo<'b>,
+        highest_bidder_account: &'a AccountInfo<'b>,
+        pda: Pubkey,
+        pda_account: &'a AccountInfo<'b>,
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let close_highest_bidder_ft_temp_acc_ix = spl_token::instruction::close_account(
+            token_program.key,
+            highest_bidder_ft_temp_account.key,
+            highest_bidder_account.key,
+            &pda,
+            &[],
+        )?;
+        msg!("Closing the Highest Bidder's FT temporary account...");
+        invoke_signed(
+            &close_highest_bidder_ft_temp_acc_ix,
+            &[
+                highest_bidder_ft_temp_account.clone(),
+                highest_bidder_account.clone(),
+                pda_account.clone(),
+                token_program.clone(),
+            ],
+            signers_seeds,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::TOKEN_METADATA_PROGRAM_ID;
+    use solana_program::entrypoint::{MAX_PERMITTED_DATA_INCREASE, SUCCESS};
+    use solana_program::instruction::Instruction;
+    use solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+    use solana_program::system_program;
+    use spl_token::state::{AccountState, Mint};
+    use std::cell::RefCell;
+    use std::sync::Once;
+
+    const PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+    const NOW: i64 = 1_700_000_000;
+    const SEED: [u8; 32] = [9; 32];
+
+    /// Stands in for the runtime, running the system and token program instructions the processor invokes
+    /// against the accounts passed along, with the signatures of the PDAs of the program.
+    struct Runtime;
+
+    impl SyscallStubs for Runtime {
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            let pda_signers = signers_seeds
+                .iter()
+                .map(|seeds| Pubkey::create_program_address(seeds, &PROGRAM_ID))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| ProgramError::InvalidSeeds)?;
+            let accounts = instruction
+                .accounts
+                .iter()
+                .map(|meta| {
+                    let mut account_info = account_infos
+                        .iter()
+                        .find(|account_info| *account_info.key == meta.pubkey)
+                        .ok_or(ProgramError::NotEnoughAccountKeys)?
+                        .clone();
+                    if meta.is_signer
+                        && !account_info.is_signer
+                        && !pda_signers.contains(&meta.pubkey)
+                    {
+                        return Err(ProgramError::MissingRequiredSignature);
+                    }
+                    account_info.is_signer = meta.is_signer;
+                    account_info.is_writable = meta.is_writable;
+                    Ok(account_info)
+                })
+                .collect::<Result<Vec<_>, ProgramError>>()?;
+
+            if instruction.program_id == spl_token::id() {
+                spl_token::processor::Processor::process(
+                    &instruction.program_id,
+                    &accounts,
+                    &instruction.data,
+                )
+            } else if instruction.program_id == system_program::id() {
+                process_system_instruction(&accounts, &instruction.data)
+            } else {
+                panic!("Unexpected invocation of {}", instruction.program_id)
+            }
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            SUCCESS
+        }
+    }
+
+    /// Runs the system program instructions the processor invokes: create account, assign, transfer and allocate.
+    fn process_system_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+        let u64_at = |start: usize| u64::from_le_bytes(*array_ref![data, start, 8]);
+        let check_signer = |account_info: &AccountInfo| match account_info.is_signer {
+            true => Ok(()),
+            false => Err(ProgramError::MissingRequiredSignature),
+        };
+        let allocate = |account_info: &AccountInfo, space: u64| {
+            check_signer(account_info)?;
+            if !account_info.data_is_empty() || *account_info.owner != system_program::id() {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            account_info.realloc(space as usize, true)
+        };
+        let transfer = |from: &AccountInfo, to: &AccountInfo, lamports: u64| {
+            check_signer(from)?;
+            Processor::transfer_lamports(from, to, lamports)
+        };
+
+        match u32::from_le_bytes(*array_ref![data, 0, 4]) {
+            0 => {
+                // The system program refuses to create an account already holding lamports.
+                if accounts[1].lamports() > 0 {
+                    return Err(ProgramError::Custom(0));
+                }
+                transfer(&accounts[0], &accounts[1], u64_at(4))?;
+                allocate(&accounts[1], u64_at(12))?;
+                accounts[1].assign(&Pubkey::new_from_array(*array_ref![data, 20, 32]));
+                Ok(())
+            }
+            1 => {
+                check_signer(&accounts[0])?;
+                accounts[0].assign(&Pubkey::new_from_array(*array_ref![data, 4, 32]));
+                Ok(())
+            }
+            2 => transfer(&accounts[0], &accounts[1], u64_at(4)),
+            8 => allocate(&accounts[0], u64_at(4)),
+            _ => panic!("Unexpected system instruction"),
+        }
+    }
+
+    /// Lays the account out the way the runtime serializes it, so that `realloc` can grow it in place.
+    fn account(key: Pubkey, owner: Pubkey, lamports: u64, data: &[u8]) -> AccountInfo<'static> {
+        static RUNTIME: Once = Once::new();
+        RUNTIME.call_once(|| {
+            set_syscall_stubs(Box::new(Runtime));
+        });
+
+        // The original data length, the key, the owner, the lamports and the data length precede the data.
+        const DATA_START: usize = 88;
+        let buffer_len = DATA_START + data.len() + MAX_PERMITTED_DATA_INCREASE;
+        let buffer = Box::leak(vec![0u64; buffer_len / 8 + 1].into_boxed_slice());
+        let base = buffer.as_mut_ptr() as *mut u8;
+        unsafe {
+            let bytes = std::slice::from_raw_parts_mut(base, buffer_len);
+            bytes[4..8].copy_from_slice(&(data.len() as u32).to_le_bytes());
+            bytes[8..40].copy_from_slice(key.as_ref());
+            bytes[40..72].copy_from_slice(owner.as_ref());
+            bytes[72..80].copy_from_slice(&lamports.to_le_bytes());
+            bytes[80..88].copy_from_slice(&(data.len() as u64).to_le_bytes());
+            bytes[DATA_START..DATA_START + data.len()].copy_from_slice(data);
+
+            AccountInfo::new(
+                &*(base.add(8) as *const Pubkey),
+                false,
+                true,
+                &mut *(base.add(72) as *mut u64),
+                std::slice::from_raw_parts_mut(base.add(DATA_START), data.len()),
+                &*(base.add(40) as *const Pubkey),
+                false,
+                0,
+            )
+        }
+    }
+
+    fn wallet() -> AccountInfo<'static> {
+        let mut wallet = account(
+            Pubkey::new_unique(),
+            system_program::id(),
+            1_000_000_000,
+            &[],
+        );
+        wallet.is_signer = true;
+        wallet
+    }
+
+    fn program(program_id: Pubkey) -> AccountInfo<'static> {
+        let mut program = account(program_id, Pubkey::default(), 1, &[]);
+        program.executable = true;
+        program
+    }
+
+    fn token_program() -> AccountInfo<'static> {
+        program(spl_token::id())
+    }
+
+    fn pda_account() -> AccountInfo<'static> {
+        let (pda, _bump_seed) = Pubkey::find_program_address(&[b"escrow"], &PROGRAM_ID);
+        account(pda, system_program::id(), 0, &[])
+    }
+
+    /// Account of the program of `len` zeroed bytes, funded for rent exemption.
+    fn state_account(len: usize) -> AccountInfo<'static> {
+        account(
+            Pubkey::new_unique(),
+            PROGRAM_ID,
+            Rent::default().minimum_balance(len),
+            &vec![0; len],
+        )
+    }
+
+    fn rent_sysvar() -> AccountInfo<'static> {
+        let mut rent_sysvar = account(
+            solana_program::sysvar::rent::id(),
+            solana_program::sysvar::id(),
+            1,
+            &vec![0; Rent::size_of()],
+        );
+        Rent::default().to_account_info(&mut rent_sysvar).unwrap();
+        rent_sysvar
+    }
+
+    fn clock_sysvar(unix_timestamp: i64, slot: u64) -> AccountInfo<'static> {
+        let mut clock_sysvar = account(
+            solana_program::sysvar::clock::id(),
+            solana_program::sysvar::id(),
+            1,
+            &vec![0; Clock::size_of()],
+        );
+        let clock = Clock {
+            slot,
+            unix_timestamp,
+            ..Clock::default()
+        };
+        clock.to_account_info(&mut clock_sysvar).unwrap();
+        clock_sysvar
+    }
+
+    /// Slot hashes sysvar holding `entries`, most recent slot first.
+    fn slot_hashes_sysvar(entries: &[(u64, [u8; 32])]) -> AccountInfo<'static> {
+        let mut data = (entries.len() as u64).to_le_bytes().to_vec();
+        for (slot, hash) in entries {
+            data.extend(slot.to_le_bytes());
+            data.extend(hash);
+        }
+        account(slot_hashes::id(), solana_program::sysvar::id(), 1, &data)
+    }
+
+    fn config() -> Config {
+        Config {
+            is_initialized: true,
+            admin_pubkey: Pubkey::new_unique(),
+            treasury_pubkey: Pubkey::new_unique(),
+            fee_bps: 0,
+            referral_bps: 0,
+            price_authority_pubkey: Pubkey::new_unique(),
+            cancel_penalty_bps: 0,
+            retraction_penalty_bps: 0,
+            paused: false,
+            min_duration_sec: 0,
+            max_duration_sec: 0,
+        }
+    }
+
+    fn config_account(config_info: Config) -> AccountInfo<'static> {
+        let (config_pubkey, _bump_seed) = Pubkey::find_program_address(&[b"config"], &PROGRAM_ID);
+        let mut data = [0; Config::LEN];
+        Config::pack(config_info, &mut data).unwrap();
+        account(
+            config_pubkey,
+            PROGRAM_ID,
+            Rent::default().minimum_balance(Config::LEN),
+            &data,
+        )
+    }
+
+    fn mint() -> AccountInfo<'static> {
+        mint_with_authority(&Pubkey::new_unique())
+    }
+
+    fn mint_with_authority(mint_authority: &Pubkey) -> AccountInfo<'static> {
+        let mut data = [0; Mint::LEN];
+        Mint::pack(
+            Mint {
+                mint_authority: Some(*mint_authority).into(),
+                supply: 1_000_000_000,
+                decimals: 0,
+                is_initialized: true,
+                freeze_authority: None.into(),
+            },
+            &mut data,
+        )
+        .unwrap();
+        account(
+            Pubkey::new_unique(),
+            spl_token::id(),
+            Rent::default().minimum_balance(Mint::LEN),
+            &data,
+        )
+    }
+
+    fn token_account(mint: &AccountInfo, owner: &Pubkey, amount: u64) -> AccountInfo<'static> {
+        let mut data = [0; TokenAccount::LEN];
+        TokenAccount::pack(
+            TokenAccount {
+                mint: *mint.key,
+                owner: *owner,
+                amount,
+                state: AccountState::Initialized,
+                ..TokenAccount::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        account(
+            Pubkey::new_unique(),
+            spl_token::id(),
+            Rent::default().minimum_balance(TokenAccount::LEN),
+            &data,
+        )
+    }
+
+    fn token_balance(token_account: &AccountInfo) -> u64 {
+        TokenAccount::unpack(&token_account.try_borrow_data().unwrap())
+            .unwrap()
+            .amount
+    }
+
+    /// A raffle of an NFT whose tickets are paid in `ft_mint`, exhibited at `NOW` for an hour.
+    struct RaffleSale {
+        exhibitor: AccountInfo<'static>,
+        nft_temp: AccountInfo<'static>,
+        exhibitor_ft: AccountInfo<'static>,
+        proceeds: AccountInfo<'static>,
+        raffle: AccountInfo<'static>,
+        nft_mint: AccountInfo<'static>,
+        ft_mint: AccountInfo<'static>,
+    }
+
+    impl RaffleSale {
+        const END_AT: i64 = NOW + 3_600;
+
+        fn exhibit(ticket_price: u64) -> RaffleSale {
+            let exhibitor = wallet();
+            let nft_mint = mint();
+            let ft_mint = mint();
+            let exhibitor_nft = token_account(&nft_mint, exhibitor.key, 1);
+            let sale = RaffleSale {
+                nft_temp: token_account(&nft_mint, exhibitor.key, 0),
+                exhibitor_ft: token_account(&ft_mint, exhibitor.key, 0),
+                proceeds: token_account(&ft_mint, exhibitor.key, 0),
+                raffle: state_account(Raffle::range_span(1).end),
+                exhibitor,
+                nft_mint,
+                ft_mint,
+            };
+            Processor::process_exhibit_raffle(
+                &[
+                    sale.exhibitor.clone(),
+                    exhibitor_nft,
+                    sale.nft_temp.clone(),
+                    sale.exhibitor_ft.clone(),
+                    sale.proceeds.clone(),
+                    sale.raffle.clone(),
+                    rent_sysvar(),
+                    clock_sysvar(NOW, 1),
+                    token_program(),
+                    config_account(config()),
+                ],
+                ticket_price,
+                3_600,
+                hash(&SEED).to_bytes(),
+                &PROGRAM_ID,
+            )
+            .unwrap();
+            sale
+        }
+
+        fn info(&self) -> Raffle {
+            Raffle::unpack_header(&self.raffle.try_borrow_data().unwrap()).unwrap()
+        }
+
+        /// Buys `count` tickets for `buyer`, paying from `buyer_ft`.
+        fn buy(
+            &self,
+            buyer: &AccountInfo<'static>,
+            buyer_ft: &AccountInfo<'static>,
+            count: u64,
+        ) -> ProgramResult {
+            Processor::process_buy_tickets(
+                &[
+                    buyer.clone(),
+                    buyer_ft.clone(),
+                    self.proceeds.clone(),
+                    self.raffle.clone(),
+                    clock_sysvar(NOW + 60, 10),
+                    token_program(),
+                    config_account(config()),
+                ],
+                count,
+                &PROGRAM_ID,
+            )
+        }
+
+        fn record_draw_slot(&self, unix_timestamp: i64, slot: u64) -> ProgramResult {
+            Processor::process_record_draw_slot(&[
+                self.exhibitor.clone(),
+                self.raffle.clone(),
+                clock_sysvar(unix_timestamp, slot),
+            ])
+        }
+
+        fn draw(&self, slot_hashes: &[(u64, [u8; 32])]) -> ProgramResult {
+            Processor::process_draw(
+                &[
+                    self.raffle.clone(),
+                    clock_sysvar(RaffleSale::END_AT + 60, 1_000),
+                    slot_hashes_sysvar(slot_hashes),
+                ],
+                SEED,
+            )
+        }
+    }
+
+    #[test]
+    fn raffle_tickets_are_escrowed_in_ranges() {
+        let sale = RaffleSale::exhibit(10);
+        assert_eq!(token_balance(&sale.nft_temp), 1);
+
+        let (alice, bob) = (wallet(), wallet());
+        let alice_ft = token_account(&sale.ft_mint, alice.key, 100);
+        let bob_ft = token_account(&sale.ft_mint, bob.key, 100);
+        sale.buy(&alice, &alice_ft, 2).unwrap();
+        // Consecutive purchases of the same buyer extend their range.
+        sale.buy(&alice, &alice_ft, 1).unwrap();
+        sale.buy(&bob, &bob_ft, 4).unwrap();
+
+        let raffle_info = sale.info();
+        assert_eq!(raffle_info.tickets_sold, 7);
+        assert_eq!(raffle_info.range_count, 2);
+        assert_eq!(token_balance(&alice_ft), 70);
+        assert_eq!(token_balance(&bob_ft), 60);
+        assert_eq!(token_balance(&sale.proceeds), 70);
+
+        // The raffle account only has room for two ranges.
+        let carol = wallet();
+        let carol_ft = token_account(&sale.ft_mint, carol.key, 100);
+        assert_eq!(
+            sale.buy(&carol, &carol_ft, 1),
+            Err(AuctionError::TicketLedgerFull.into())
+        );
+        assert_eq!(
+            sale.buy(&sale.exhibitor, &sale.exhibitor_ft, 1),
+            Err(AuctionError::InvalidInstruction.into())
+        );
+    }
+
+    #[test]
+    fn raffle_draw_needs_a_recorded_slot_hash() {
+        let sale = RaffleSale::exhibit(10);
+        let buyer = wallet();
+        let buyer_ft = token_account(&sale.ft_mint, buyer.key, 100);
+        sale.buy(&buyer, &buyer_ft, 1).unwrap();
+
+        assert_eq!(
+            sale.draw(&[(500, [1; 32])]),
+            Err(AuctionError::SlotHashUnavailable.into())
+        );
+        assert_eq!(
+            sale.record_draw_slot(RaffleSale::END_AT - 1, 500),
+            Err(AuctionError::ActiveAuction.into())
+        );
+        sale.record_draw_slot(RaffleSale::END_AT, 500).unwrap();
+        assert_eq!(
+            sale.record_draw_slot(RaffleSale::END_AT + 1, 600),
+            Err(AuctionError::InvalidInstruction.into())
+        );
+        assert_eq!(sale.info().draw_slot, 500);
+
+        // The sysvar only keeps the most recent slots.
+        assert_eq!(
+            sale.draw(&[(1_000, [1; 32]), (999, [2; 32])]),
+            Err(AuctionError::SlotHashUnavailable.into())
+        );
+        sale.draw(&[(501, [1; 32]), (500, [2; 32])]).unwrap();
+        assert_eq!(sale.info().winner_pubkey, *buyer.key);
+        assert_eq!(
+            sale.draw(&[(500, [2; 32])]),
+            Err(AuctionError::AlreadyDrawn.into())
+        );
+    }
+
+    #[test]
+    fn raffle_winner_follows_the_slot_hash() {
+        // Both buyers can win, depending on the hash of the draw slot only.
+        let mut winners = Vec::new();
+        for slot_hash_byte in 0..=u8::MAX {
+            let sale = RaffleSale::exhibit(10);
+            let (alice, bob) = (wallet(), wallet());
+            let alice_ft = token_account(&sale.ft_mint, alice.key, 100);
+            let bob_ft = token_account(&sale.ft_mint, bob.key, 100);
+            sale.buy(&alice, &alice_ft, 1).unwrap();
+            sale.buy(&bob, &bob_ft, 3).unwrap();
+            sale.record_draw_slot(RaffleSale::END_AT, 500).unwrap();
+            sale.draw(&[(500, [slot_hash_byte; 32])]).unwrap();
+
+            let randomness = hashv(&[&SEED, &[slot_hash_byte; 32], sale.raffle.key.as_ref()]);
+            let winning_ticket = u64::from_le_bytes(*array_ref![randomness.as_ref(), 0, 8]) % 4;
+            let expected_winner = if winning_ticket < 1 { &alice } else { &bob };
+            assert_eq!(sale.info().winner_pubkey, *expected_winner.key);
+            winners.push(winning_ticket < 1);
+            if winners.contains(&true) && winners.contains(&false) {
+                return;
+            }
+        }
+        panic!("The same buyer won every draw");
+    }
+
+    #[test]
+    fn raffle_winner_settles_for_the_nft() {
+        let sale = RaffleSale::exhibit(10);
+        let buyer = wallet();
+        let buyer_ft = token_account(&sale.ft_mint, buyer.key, 100);
+        sale.buy(&buyer, &buyer_ft, 5).unwrap();
+        sale.record_draw_slot(RaffleSale::END_AT, 500).unwrap();
+        sale.draw(&[(500, [1; 32])]).unwrap();
+
+        let buyer_nft = token_account(&sale.nft_mint, buyer.key, 0);
+        let exhibitor_lamports = sale.exhibitor.lamports();
+        Processor::process_settle_raffle(
+            &[
+                buyer.clone(),
+                sale.exhibitor.clone(),
+                sale.nft_temp.clone(),
+                sale.exhibitor_ft.clone(),
+                sale.proceeds.clone(),
+                buyer_nft.clone(),
+                sale.raffle.clone(),
+                token_program(),
+                pda_account(),
+            ],
+            &PROGRAM_ID,
+        )
+        .unwrap();
+
+        assert_eq!(token_balance(&buyer_nft), 1);
+        assert_eq!(token_balance(&sale.exhibitor_ft), 50);
+        assert_eq!(sale.raffle.lamports(), 0);
+        assert!(sale.exhibitor.lamports() > exhibitor_lamports);
+    }
+
+    #[test]
+    fn raffle_tickets_are_refunded_after_a_missed_reveal() {
+        let sale = RaffleSale::exhibit(10);
+        let buyer = wallet();
+        let buyer_ft = token_account(&sale.ft_mint, buyer.key, 100);
+        sale.buy(&buyer, &buyer_ft, 3).unwrap();
+
+        let refund = |unix_timestamp: i64| {
+            Processor::process_refund_tickets(
+                &[
+                    buyer.clone(),
+                    buyer_ft.clone(),
+                    sale.proceeds.clone(),
+                    sale.raffle.clone(),
+                    clock_sysvar(unix_timestamp, 2_000),
+                    token_program(),
+                    pda_account(),
+                ],
+                &PROGRAM_ID,
+            )
+        };
+        let reveal_by = RaffleSale::END_AT + RAFFLE_REVEAL_PERIOD_SEC;
+        assert_eq!(refund(reveal_by), Err(AuctionError::ActiveAuction.into()));
+        refund(reveal_by + 1).unwrap();
+        assert_eq!(token_balance(&buyer_ft), 100);
+        assert_eq!(sale.info().tickets_refunded, 3);
+        assert_eq!(
+            refund(reveal_by + 2),
+            Err(AuctionError::NoBidderFound.into())
+        );
+
+        // Once the reveal is missed, the draw is closed for good.
+        sale.record_draw_slot(reveal_by + 1, 500).unwrap();
+        assert_eq!(
+            Processor::process_draw(
+                &[
+                    sale.raffle.clone(),
+                    clock_sysvar(reveal_by + 1, 501),
+                    slot_hashes_sysvar(&[(500, [1; 32])]),
+                ],
+                SEED,
+            ),
+            Err(AuctionError::InactiveAuction.into())
+        );
+    }
+
+    fn gda(
+        initial_price: u64,
+        price_scale: u64,
+        decay_rate: u64,
+        sold: u64,
+    ) -> GradualDutchAuction {
+        GradualDutchAuction {
+            is_initialized: true,
+            exhibitor_pubkey: Pubkey::default(),
+            supply_pubkey: Pubkey::default(),
+            mints_on_purchase: false,
+            exhibitor_ft_receiving_pubkey: Pubkey::default(),
+            initial_price,
+            price_scale,
+            decay_rate,
+            start_at: 0,
+            sold,
+            max_supply: u64::MAX,
+            fee_bps: 0,
+            treasury_pubkey: Pubkey::default(),
+        }
+    }
+
+    #[test]
+    fn gda_price_follows_the_curve() {
+        let one = FIXED_POINT_ONE;
+
+        // A flat curve charges the initial price for every item.
+        let flat = gda(1_000, one, one, 0);
+        assert_eq!(Processor::gda_purchase_price(&flat, 5, 100), Ok(5_000));
+
+        // Every item sold doubles the price of the next one.
+        let doubling = gda(1_000, 2 * one, one, 0);
+        assert_eq!(Processor::gda_purchase_price(&doubling, 1, 0), Ok(1_000));
+        assert_eq!(Processor::gda_purchase_price(&doubling, 3, 0), Ok(7_000));
+        let doubling_after_sales = gda(1_000, 2 * one, one, 2);
+        assert_eq!(
+            Processor::gda_purchase_price(&doubling_after_sales, 2, 0),
+            Ok(12_000)
+        );
+
+        // Every elapsed second halves the price.
+        let halving = gda(1_000, one, one / 2, 0);
+        assert_eq!(Processor::gda_purchase_price(&halving, 1, 3), Ok(125));
+    }
+
+    #[test]
+    fn gda_price_rounds_up() {
+        let one = FIXED_POINT_ONE;
+        let halving = gda(1, one, one / 2, 0);
+        assert_eq!(Processor::gda_purchase_price(&halving, 1, 1), Ok(1));
+
+        // 1 + 1.5 + 2.25 items of the initial price.
+        let scaling = gda(3, one + one / 2, one, 0);
+        assert_eq!(Processor::gda_purchase_price(&scaling, 3, 0), Ok(15));
+    }
+
+    #[test]
+    fn gda_price_overflow_is_an_error() {
+        let one = FIXED_POINT_ONE;
+        let overflow = Err(AuctionError::AmountOverflow.into());
+
+        let doubling = gda(u64::MAX, 2 * one, one, 0);
+        assert_eq!(Processor::gda_purchase_price(&doubling, 2, 0), overflow);
+        let sold_out_curve = gda(1, 2 * one, one, 200);
+        assert_eq!(
+            Processor::gda_purchase_price(&sold_out_curve, 1, 0),
+            overflow
+        );
+    }
+
+    /// A GDA of `supply` escrowed items, or of up to 10 minted ones when `supply` is zero.
+    struct GdaSale {
+        exhibitor_ft: AccountInfo<'static>,
+        supply: AccountInfo<'static>,
+        gda: AccountInfo<'static>,
+        item_mint: AccountInfo<'static>,
+        ft_mint: AccountInfo<'static>,
+        treasury_ft: AccountInfo<'static>,
+    }
+
+    impl GdaSale {
+        fn exhibit(initial_price: u64, price_scale: u64, supply: u64, fee_bps: u16) -> GdaSale {
+            let exhibitor = wallet();
+            let item_mint = mint_with_authority(exhibitor.key);
+            let ft_mint = mint();
+            let config_info = Config {
+                fee_bps,
+                ..config()
+            };
+            let sale = GdaSale {
+                exhibitor_ft: token_account(&ft_mint, exhibitor.key, 0),
+                supply: match supply {
+                    0 => item_mint.clone(),
+                    _ => token_account(&item_mint, exhibitor.key, supply),
+                },
+                gda: state_account(GradualDutchAuction::LEN),
+                treasury_ft: token_account(&ft_mint, &config_info.treasury_pubkey, 0),
+                item_mint,
+                ft_mint,
+            };
+            Processor::process_exhibit_gda(
+                &[
+                    exhibitor,
+                    sale.supply.clone(),
+                    sale.exhibitor_ft.clone(),
+                    sale.gda.clone(),
+                    rent_sysvar(),
+                    clock_sysvar(NOW, 1),
+                    token_program(),
+                    config_account(config_info),
+                ],
+                initial_price,
+                price_scale,
+                FIXED_POINT_ONE,
+                10,
+                supply == 0,
+                &PROGRAM_ID,
+            )
+            .unwrap();
+            sale
+        }
+
+        fn info(&self) -> GradualDutchAuction {
+            GradualDutchAuction::unpack(&self.gda.try_borrow_data().unwrap()).unwrap()
+        }
+
+        fn purchase(
+            &self,
+            buyer_ft: &AccountInfo<'static>,
+            buyer_items: &AccountInfo<'static>,
+            quantity: u64,
+            max_total_price: u64,
+        ) -> ProgramResult {
+            let buyer_pubkey = TokenAccount::unpack(&buyer_ft.try_borrow_data()?)?.owner;
+            let mut buyer = account(buyer_pubkey, system_program::id(), 1, &[]);
+            buyer.is_signer = true;
+            let mut accounts = vec![
+                buyer,
+                buyer_ft.clone(),
+                self.exhibitor_ft.clone(),
+                buyer_items.clone(),
+                self.supply.clone(),
+                self.gda.clone(),
+                clock_sysvar(NOW, 2),
+                token_program(),
+                pda_account(),
+                config_account(config()),
+            ];
+            if self.info().fee_bps > 0 {
+                accounts.push(self.treasury_ft.clone());
+            }
+            Processor::process_purchase_gda(&accounts, quantity, max_total_price, &PROGRAM_ID)
+        }
+    }
+
+    #[test]
+    fn gda_purchases_pay_the_curve_price_and_the_fee() {
+        let sale = GdaSale::exhibit(1_000, 2 * FIXED_POINT_ONE, 5, 250);
+        let buyer = Pubkey::new_unique();
+        let buyer_ft = token_account(&sale.ft_mint, &buyer, 100_000);
+        let buyer_items = token_account(&sale.item_mint, &buyer, 0);
+
+        assert_eq!(
+            sale.purchase(&buyer_ft, &buyer_items, 2, 2_999),
+            Err(AuctionError::PriceLimitExceeded.into())
+        );
+        sale.purchase(&buyer_ft, &buyer_items, 2, 3_000).unwrap();
+        assert_eq!(token_balance(&buyer_items), 2);
+        assert_eq!(token_balance(&buyer_ft), 97_000);
+        assert_eq!(token_balance(&sale.treasury_ft), 75);
+        assert_eq!(token_balance(&sale.exhibitor_ft), 2_925);
+
+        // The next items are priced after the ones sold.
+        sale.purchase(&buyer_ft, &buyer_items, 1, 4_000).unwrap();
+        assert_eq!(token_balance(&buyer_ft), 93_000);
+        assert_eq!(sale.info().sold, 3);
+        assert_eq!(
+            sale.purchase(&buyer_ft, &buyer_items, 3, u64::MAX),
+            Err(AuctionError::InsufficientSupply.into())
+        );
+    }
+
+    #[test]
+    fn gda_mints_items_on_purchase() {
+        let sale = GdaSale::exhibit(1_000, FIXED_POINT_ONE, 0, 0);
+        let buyer = Pubkey::new_unique();
+        let buyer_ft = token_account(&sale.ft_mint, &buyer, 10_000);
+        let buyer_items = token_account(&sale.item_mint, &buyer, 0);
+
+        sale.purchase(&buyer_ft, &buyer_items, 4, 4_000).unwrap();
+        assert_eq!(token_balance(&buyer_items), 4);
+        assert_eq!(token_balance(&sale.exhibitor_ft), 4_000);
+        assert_eq!(
+            sale.purchase(&buyer_ft, &buyer_items, 7, u64::MAX),
+            Err(AuctionError::InsufficientSupply.into())
+        );
+    }
+
+    /// Token Metadata account of `mint` carrying the royalty, the creators and the collection given.
+    fn metadata_account(
+        mint: &Pubkey,
+        seller_fee_basis_points: u16,
+        creators: &[(Pubkey, bool, u8)],
+        collection: Option<(bool, Pubkey)>,
+    ) -> AccountInfo<'static> {
+        let mut data = vec![4];
+        data.extend(Pubkey::new_unique().to_bytes());
+        data.extend(mint.to_bytes());
+        for field in [&b"name"[..], b"SYM", b"https://example.com"] {
+            data.extend((field.len() as u32).to_le_bytes());
+            data.extend(field);
+        }
+        data.extend(seller_fee_basis_points.to_le_bytes());
+        data.push(1);
+        data.extend((creators.len() as u32).to_le_bytes());
+        for (address, verified, share) in creators {
+            data.extend(address.to_bytes());
+            data.extend([*verified as u8, *share]);
+        }
+        // Primary sale happened, is mutable, no edition nonce and no token standard.
+        data.extend([1, 1, 0, 0]);
+        match collection {
+            Some((verified, key)) => {
+                data.extend([1, verified as u8]);
+                data.extend(key.to_bytes());
+            }
+            None => data.push(0),
+        }
+        let (metadata_pubkey, _bump_seed) = Pubkey::find_program_address(
+            &[
+                b"metadata",
+                TOKEN_METADATA_PROGRAM_ID.as_ref(),
+                mint.as_ref(),
+            ],
+            &TOKEN_METADATA_PROGRAM_ID,
+        );
+        account(metadata_pubkey, TOKEN_METADATA_PROGRAM_ID, 1, &data)
+    }
+
+    /// A call auction of a collection, opened at `NOW` with an epoch window of `EPOCH_DURATION`.
+    struct CallMarket {
+        call_auction: AccountInfo<'static>,
+        collection_pubkey: Pubkey,
+        ft_mint: AccountInfo<'static>,
+    }
+
+    struct Order {
+        owner: AccountInfo<'static>,
+        temp: AccountInfo<'static>,
+        /// The seller's FT account of an ask, the buyer's of a bid
+        ft: AccountInfo<'static>,
+        order: AccountInfo<'static>,
+        /// Mint of the NFT of an ask
+        nft_mint: Option<AccountInfo<'static>>,
+    }
+
+    impl CallMarket {
+        const EPOCH_DURATION: u64 = 600;
+
+        fn open(book_capacity: usize) -> CallMarket {
+            let market = CallMarket {
+                call_auction: state_account(CallAuction::entry_span(book_capacity).start),
+                collection_pubkey: Pubkey::new_unique(),
+                ft_mint: mint(),
+            };
+            Processor::process_open_call_auction(
+                &[
+                    wallet(),
+                    account(market.collection_pubkey, spl_token::id(), 1, &[]),
+                    market.ft_mint.clone(),
+                    market.call_auction.clone(),
+                    rent_sysvar(),
+                    clock_sysvar(NOW, 1),
+                ],
+                Self::EPOCH_DURATION,
+            )
+            .unwrap();
+            market
+        }
+
+        fn info(&self) -> CallAuction {
+            CallAuction::unpack_header(&self.call_auction.try_borrow_data().unwrap()).unwrap()
+        }
+
+        fn post_ask(&self, price: u64, now: i64) -> Order {
+            let seller = wallet();
+            let nft_mint = mint();
+            let seller_nft = token_account(&nft_mint, seller.key, 1);
+            let ask = Order {
+                temp: token_account(&nft_mint, seller.key, 0),
+                ft: token_account(&self.ft_mint, seller.key, 0),
+                order: state_account(CallOrder::LEN),
+                nft_mint: Some(nft_mint),
+                owner: seller,
+            };
+            Processor::process_post_ask(
+                &[
+                    ask.owner.clone(),
+                    seller_nft,
+                    ask.temp.clone(),
+                    ask.ft.clone(),
+                    metadata_account(
+                        ask.nft_mint.as_ref().unwrap().key,
+                        0,
+                        &[],
+                        Some((true, self.collection_pubkey)),
+                    ),
+                    self.call_auction.clone(),
+                    ask.order.clone(),
+                    rent_sysvar(),
+                    clock_sysvar(now, 2),
+                    token_program(),
+                    config_account(config()),
+                ],
+                price,
+                &PROGRAM_ID,
+            )
+            .unwrap();
+            ask
+        }
+
+        fn post_bid(&self, price: u64, now: i64) -> Order {
+            let buyer = wallet();
+            let bid = Order {
+                temp: token_account(&self.ft_mint, buyer.key, 0),
+                ft: token_account(&self.ft_mint, buyer.key, 1_000),
+                order: state_account(CallOrder::LEN),
+                nft_mint: None,
+                owner: buyer,
+            };
+            Processor::process_post_bid(
+                &[
+                    bid.owner.clone(),
+                    bid.ft.clone(),
+                    bid.temp.clone(),
+                    self.call_auction.clone(),
+                    bid.order.clone(),
+                    rent_sysvar(),
+                    clock_sysvar(now, 2),
+                    token_program(),
+                    config_account(config()),
+                ],
+                price,
+                &PROGRAM_ID,
+            )
+            .unwrap();
+            bid
+        }
+
+        fn clear(&self, now: i64) -> ProgramResult {
+            Processor::process_clear(
+                &[self.call_auction.clone(), clock_sysvar(now, 3)],
+                &PROGRAM_ID,
+            )
+        }
+
+        /// Settles `bid` with `ask`, returning the buyer's account receiving the NFT.
+        fn settle(
+            &self,
+            bid: &Order,
+            ask: &Order,
+            config_info: Config,
+        ) -> Result<AccountInfo<'static>, ProgramError> {
+            let buyer_nft = token_account(ask.nft_mint.as_ref().unwrap(), bid.owner.key, 0);
+            let mut accounts = vec![
+                bid.owner.clone(),
+                buyer_nft.clone(),
+                bid.order.clone(),
+                bid.temp.clone(),
+                bid.ft.clone(),
+                ask.order.clone(),
+                ask.temp.clone(),
+                ask.ft.clone(),
+                ask.owner.clone(),
+                self.call_auction.clone(),
+                token_program(),
+                pda_account(),
+            ];
+            let treasury_ft = token_account(&self.ft_mint, &config_info.treasury_pubkey, 0);
+            let fee_bps = config_info.fee_bps;
+            accounts.push(config_account(config_info));
+            if fee_bps > 0 {
+                accounts.push(treasury_ft);
+            }
+            Processor::process_settle_fill(&accounts, &PROGRAM_ID)?;
+            Ok(buyer_nft)
+        }
+    }
+
+    #[test]
+    fn call_auction_clears_at_a_uniform_price() {
+        let market = CallMarket::open(4);
+        let cheap_ask = market.post_ask(100, NOW);
+        let dear_ask = market.post_ask(300, NOW);
+        let high_bid = market.post_bid(400, NOW);
+        let low_bid = market.post_bid(200, NOW);
+        assert_eq!(token_balance(&high_bid.temp), 400);
+
+        let end_at = NOW + CallMarket::EPOCH_DURATION as i64;
+        assert_eq!(
+            market.clear(end_at - 1),
+            Err(AuctionError::ActiveAuction.into())
+        );
+        market.clear(end_at).unwrap();
+        let call_auction_info = market.info();
+        assert_eq!(call_auction_info.epoch, 1);
+        assert_eq!(call_auction_info.epoch_end_at, end_at + 600);
+        assert_eq!(call_auction_info.open_asks, 1);
+        assert_eq!(call_auction_info.open_bids, 1);
+        assert_eq!(call_auction_info.entry_count, 4);
+
+        // Only the 100 ask and the 400 bid cross, at the midpoint of 200 and 300.
+        assert_eq!(
+            market.settle(&high_bid, &dear_ask, config()).err(),
+            Some(AuctionError::OrderSetMismatch.into())
+        );
+        assert_eq!(
+            market.settle(&low_bid, &cheap_ask, config()).err(),
+            Some(AuctionError::OrderSetMismatch.into())
+        );
+        let buyer_nft = market
+            .settle(
+                &high_bid,
+                &cheap_ask,
+                Config {
+                    fee_bps: 1_000,
+                    ..config()
+                },
+            )
+            .unwrap();
+        assert_eq!(token_balance(&buyer_nft), 1);
+        assert_eq!(token_balance(&cheap_ask.ft), 225);
+        assert_eq!(token_balance(&high_bid.ft), 750);
+        assert_eq!(high_bid.temp.lamports(), 0);
+        assert_eq!(high_bid.order.lamports(), 0);
+        assert_eq!(market.info().entry_count, 2);
+    }
+
+    #[test]
+    fn call_auction_rolls_unfilled_orders_over() {
+        let market = CallMarket::open(3);
+        let ask = market.post_ask(300, NOW);
+        let low_bid = market.post_bid(200, NOW);
+
+        let end_at = NOW + CallMarket::EPOCH_DURATION as i64;
+        market.clear(end_at).unwrap();
+        assert_eq!(market.info().open_asks, 1);
+        assert_eq!(market.info().open_bids, 1);
+
+        // The ask rests in the book and crosses a bid of the next epoch.
+        let bid = market.post_bid(350, end_at);
+        market.clear(end_at + 600).unwrap();
+        assert_eq!(market.info().epoch, 2);
+        assert_eq!(market.info().open_asks, 0);
+        assert_eq!(market.info().open_bids, 1);
+
+        market.settle(&bid, &ask, config()).unwrap();
+        assert_eq!(token_balance(&ask.ft), 325);
+        assert_eq!(token_balance(&bid.ft), 675);
+        assert_eq!(token_balance(&low_bid.temp), 200);
+        assert_eq!(market.info().entry_count, 1);
+    }
+
+    #[test]
+    fn call_auction_takes_asks_of_its_collection_only() {
+        let market = CallMarket::open(2);
+        let seller = wallet();
+        let nft_mint = mint();
+        let post_ask = |collection| {
+            Processor::process_post_ask(
+                &[
+                    seller.clone(),
+                    token_account(&nft_mint, seller.key, 1),
+                    token_account(&nft_mint, seller.key, 0),
+                    token_account(&market.ft_mint, seller.key, 0),
+                    metadata_account(nft_mint.key, 0, &[], collection),
+                    market.call_auction.clone(),
+                    state_account(CallOrder::LEN),
+                    rent_sysvar(),
+                    clock_sysvar(NOW, 2),
+                    token_program(),
+                    config_account(config()),
+                ],
+                100,
+                &PROGRAM_ID,
+            )
+        };
+
+        for collection in [
+            None,
+            Some((false, market.collection_pubkey)),
+            Some((true, Pubkey::new_unique())),
+        ] {
+            assert_eq!(
+                post_ask(collection),
+                Err(AuctionError::CollectionMismatch.into())
+            );
+        }
+        assert_eq!(post_ask(Some((true, market.collection_pubkey))), Ok(()));
+        assert_eq!(market.info().open_asks, 1);
+    }
+
+    fn system_program() -> AccountInfo<'static> {
+        program(system_program::id())
+    }
+
+    /// The not yet created denylist page of `wallet`.
+    fn denylist_page(wallet: &Pubkey) -> AccountInfo<'static> {
+        let (denylist_page_pubkey, _bump_seed) =
+            Pubkey::find_program_address(&[b"denylist", &wallet.as_ref()[..1]], &PROGRAM_ID);
+        account(denylist_page_pubkey, system_program::id(), 0, &[])
+    }
+
+    /// Stands in for the highest bidder and their accounts before the first bid.
+    fn nobody() -> AccountInfo<'static> {
+        account(Pubkey::default(), system_program::id(), 0, &[])
+    }
+
+    #[derive(Clone)]
+    struct Bidder {
+        wallet: AccountInfo<'static>,
+        ft: AccountInfo<'static>,
+    }
+
+    /// An English auction of an NFT whose bids are held in the FT vault of the auction, exhibited at `NOW`
+    /// for a day at a price of 100.
+    struct EnglishAuction {
+        exhibitor: AccountInfo<'static>,
+        nft_temp: AccountInfo<'static>,
+        exhibitor_ft: AccountInfo<'static>,
+        escrow: AccountInfo<'static>,
+        vault: AccountInfo<'static>,
+        nft_mint: AccountInfo<'static>,
+        ft_mint: AccountInfo<'static>,
+        treasury_ft: AccountInfo<'static>,
+        payees_ft: Vec<AccountInfo<'static>>,
+        bidders: RefCell<Vec<Bidder>>,
+    }
+
+    impl EnglishAuction {
+        const END_AT: i64 = NOW + 86_400;
+
+        fn exhibit(config_info: Config) -> EnglishAuction {
+            Self::exhibit_with(config_info, &[], 0).unwrap()
+        }
+
+        fn exhibit_with(
+            config_info: Config,
+            payee_shares_bps: &[u16],
+            bond_amount: u64,
+        ) -> Result<EnglishAuction, ProgramError> {
+            let exhibitor = wallet();
+            let nft_mint = mint();
+            let ft_mint = mint();
+            let exhibitor_nft = token_account(&nft_mint, exhibitor.key, 1);
+            let escrow = state_account(Auction::LEN);
+            let (vault_pubkey, _bump_seed) =
+                Pubkey::find_program_address(&[b"vault", escrow.key.as_ref()], &PROGRAM_ID);
+            let auction = EnglishAuction {
+                nft_temp: token_account(&nft_mint, exhibitor.key, 0),
+                exhibitor_ft: token_account(&ft_mint, exhibitor.key, 0),
+                vault: account(vault_pubkey, system_program::id(), 0, &[]),
+                treasury_ft: token_account(&ft_mint, &config_info.treasury_pubkey, 0),
+                payees_ft: payee_shares_bps
+                    .iter()
+                    .map(|_| token_account(&ft_mint, &Pubkey::new_unique(), 0))
+                    .collect(),
+                bidders: RefCell::new(Vec::new()),
+                escrow,
+                exhibitor,
+                nft_mint,
+                ft_mint,
+            };
+            let mut accounts = vec![
+                auction.exhibitor.clone(),
+                exhibitor_nft,
+                auction.nft_temp.clone(),
+                auction.exhibitor_ft.clone(),
+                auction.escrow.clone(),
+                rent_sysvar(),
+                clock_sysvar(NOW, 1),
+                token_program(),
+                config_account(config_info),
+                denylist_page(auction.exhibitor.key),
+                auction.vault.clone(),
+                system_program(),
+                auction.ft_mint.clone(),
+            ];
+            accounts.extend(auction.payees_ft.iter().cloned());
+            Processor::process_exhibit(
+                &accounts,
+                100,
+                86_400,
+                AuctionKind::English,
+                false,
+                false,
+                bond_amount,
+                payee_shares_bps,
+                0,
+                None,
+                None,
+                None,
+                0,
+                None,
+                &[],
+                0,
+                0,
+                &PROGRAM_ID,
+            )?;
+            Ok(auction)
+        }
+
+        fn info(&self) -> Auction {
+            Auction::unpack(&self.escrow.try_borrow_data().unwrap()).unwrap()
+        }
+
+        fn bidder(&self, balance: u64) -> Bidder {
+            let wallet = wallet();
+            let bidder = Bidder {
+                ft: token_account(&self.ft_mint, wallet.key, balance),
+                wallet,
+            };
+            self.bidders.borrow_mut().push(bidder.clone());
+            bidder
+        }
+
+        /// The bidder whose wallet is `pubkey`, or nobody.
+        fn bidder_of(&self, pubkey: &Pubkey) -> Bidder {
+            self.bidders
+                .borrow()
+                .iter()
+                .find(|bidder| bidder.wallet.key == pubkey)
+                .cloned()
+                .unwrap_or_else(|| Bidder {
+                    wallet: nobody(),
+                    ft: nobody(),
+                })
+        }
+
+        /// The wallet, the vault and the returning account of the runner-up, when the auction has one.
+        fn runner_up_accounts(&self) -> Vec<AccountInfo<'static>> {
+            let runner_up_pubkey = self.info().runner_up_pubkey;
+            if runner_up_pubkey == Pubkey::default() {
+                return Vec::new();
+            }
+            let runner_up = self.bidder_of(&runner_up_pubkey);
+            vec![runner_up.wallet, self.vault.clone(), runner_up.ft]
+        }
+
+        fn bid(&self, bidder: &Bidder, price: u64, referrer: Option<Pubkey>) -> ProgramResult {
+            self.bid_with(bidder, price, referrer, config(), &[])
+        }
+
+        /// Bids with the config and the accounts of the bond and the gate given.
+        fn bid_with(
+            &self,
+            bidder: &Bidder,
+            price: u64,
+            referrer: Option<Pubkey>,
+            config_info: Config,
+            checked_accounts: &[AccountInfo<'static>],
+        ) -> ProgramResult {
+            let highest_bidder = self.bidder_of(&self.info().highest_bidder_pubkey);
+            let highest_bidder_temp = match highest_bidder.wallet.key == &Pubkey::default() {
+                true => nobody(),
+                false => self.vault.clone(),
+            };
+            let mut accounts = vec![
+                bidder.wallet.clone(),
+                highest_bidder.wallet,
+                highest_bidder_temp,
+                highest_bidder.ft,
+                self.vault.clone(),
+                bidder.ft.clone(),
+                self.escrow.clone(),
+                clock_sysvar(NOW + 60, 2),
+                token_program(),
+                pda_account(),
+                config_account(config_info),
+                denylist_page(bidder.wallet.key),
+            ];
+            accounts.extend(checked_accounts.iter().cloned());
+            accounts.extend(self.runner_up_accounts());
+            Processor::process_bid(&accounts, price, referrer, None, &[], &PROGRAM_ID)
+        }
+
+        /// Closes the auction for the highest bidder, followed by the metadata account and the accounts receiving
+        /// the royalties, the referral share and the payouts of the payees, returning the winner's NFT account.
+        fn close(
+            &self,
+            metadata: AccountInfo<'static>,
+            receiving_accounts: &[AccountInfo<'static>],
+        ) -> Result<AccountInfo<'static>, ProgramError> {
+            let auction_info = self.info();
+            let winner = self.bidder_of(&auction_info.highest_bidder_pubkey);
+            let winner_nft = token_account(&self.nft_mint, winner.wallet.key, 0);
+            let mut accounts = vec![
+                winner.wallet,
+                self.exhibitor.clone(),
+                self.nft_temp.clone(),
+                self.exhibitor_ft.clone(),
+                self.vault.clone(),
+                winner_nft.clone(),
+                self.escrow.clone(),
+                clock_sysvar(Self::END_AT, 3),
+                token_program(),
+                pda_account(),
+            ];
+            if auction_info.fee_bps > 0 {
+                accounts.push(self.treasury_ft.clone());
+            }
+            accounts.push(metadata);
+            accounts.extend(receiving_accounts.iter().cloned());
+            accounts.extend(self.runner_up_accounts());
+            Processor::process_close(&accounts, &PROGRAM_ID)?;
+            Ok(winner_nft)
+        }
+
+        /// The not yet posted bond account of `bidder`.
+        fn bond(&self, bidder: &Bidder) -> AccountInfo<'static> {
+            let (bond_pubkey, _bump_seed) = Pubkey::find_program_address(
+                &[
+                    b"bond",
+                    self.escrow.key.as_ref(),
+                    bidder.wallet.key.as_ref(),
+                ],
+                &PROGRAM_ID,
+            );
+            account(bond_pubkey, system_program::id(), 0, &[])
+        }
+
+        fn post_bond(&self, bidder: &Bidder, bond: &AccountInfo<'static>) -> ProgramResult {
+            Processor::process_post_bond(
+                &[
+                    bidder.wallet.clone(),
+                    self.escrow.clone(),
+                    bond.clone(),
+                    rent_sysvar(),
+                    clock_sysvar(NOW + 30, 2),
+                    system_program(),
+                    config_account(config()),
+                ],
+                &PROGRAM_ID,
+            )
+        }
+
+        fn refund_bond(
+            &self,
+            bidder: &Bidder,
+            bond: &AccountInfo<'static>,
+            now: i64,
+        ) -> ProgramResult {
+            Processor::process_refund_bond(&[
+                bidder.wallet.clone(),
+                self.escrow.clone(),
+                bond.clone(),
+                clock_sysvar(now, 4),
+            ])
+        }
+
+        /// Offers the NFT to the runner-up, followed by the admin and config accounts disqualifying the winner.
+        fn offer_second_chance(
+            &self,
+            now: i64,
+            disqualifying_accounts: &[AccountInfo<'static>],
+        ) -> ProgramResult {
+            let winner = self.bidder_of(&self.info().highest_bidder_pubkey);
+            let mut accounts = vec![
+                self.exhibitor.clone(),
+                self.escrow.clone(),
+                self.vault.clone(),
+                winner.ft,
+                winner.wallet,
+                clock_sysvar(now, 5),
+                token_program(),
+                pda_account(),
+            ];
+            accounts.extend(disqualifying_accounts.iter().cloned());
+            Processor::process_offer_second_chance(&accounts, &PROGRAM_ID)
+        }
+
+        fn retract_bid(&self, now: i64, retraction_penalty_bps: u16) -> ProgramResult {
+            let highest_bidder = self.bidder_of(&self.info().highest_bidder_pubkey);
+            Processor::process_retract_bid(
+                &[
+                    highest_bidder.wallet,
+                    self.vault.clone(),
+                    highest_bidder.ft,
+                    self.escrow.clone(),
+                    self.exhibitor_ft.clone(),
+                    clock_sysvar(now, 4),
+                    config_account(Config {
+                        retraction_penalty_bps,
+                        ..config()
+                    }),
+                    token_program(),
+                    pda_account(),
+                ],
+                &PROGRAM_ID,
+            )
+        }
+
+        /// Metadata PDA of the NFT, never created by the Token Metadata program.
+        fn no_metadata(&self) -> AccountInfo<'static> {
+            let metadata = metadata_account(self.nft_mint.key, 0, &[], None);
+            account(*metadata.key, system_program::id(), 0, &[])
+        }
+    }
+
+    #[test]
+    fn english_auction_close_pays_the_protocol_fee() {
+        let auction = EnglishAuction::exhibit(Config {
+            fee_bps: 250,
+            ..config()
+        });
+        let first = auction.bidder(2_000);
+        let second = auction.bidder(2_000);
+        auction.bid(&first, 500, None).unwrap();
+        auction.bid(&second, 1_000, None).unwrap();
+        assert_eq!(token_balance(&auction.vault), 1_500);
+
+        let winner_nft = auction.close(auction.no_metadata(), &[]).unwrap();
+        assert_eq!(token_balance(&winner_nft), 1);
+        assert_eq!(token_balance(&auction.treasury_ft), 25);
+        assert_eq!(token_balance(&auction.exhibitor_ft), 975);
+        assert_eq!(token_balance(&first.ft), 2_000);
+        assert_eq!(token_balance(&second.ft), 1_000);
+        assert_eq!(auction.vault.lamports(), 0);
+        assert_eq!(auction.escrow.lamports(), 0);
+    }
+
+    #[test]
+    fn english_auction_fee_is_fixed_at_exhibit() {
+        let auction = EnglishAuction::exhibit(Config {
+            fee_bps: 250,
+            ..config()
+        });
+        let bidder = auction.bidder(1_000);
+        // The bid reads a config whose fee has since been raised.
+        auction
+            .bid_with(
+                &bidder,
+                1_000,
+                None,
+                Config {
+                    fee_bps: 1_000,
+                    ..config()
+                },
+                &[],
+            )
+            .unwrap();
+        assert_eq!(auction.info().fee_bps, 250);
+
+        auction.close(auction.no_metadata(), &[]).unwrap();
+        assert_eq!(token_balance(&auction.treasury_ft), 25);
+        assert_eq!(token_balance(&auction.exhibitor_ft), 975);
+    }
+
+    /// Program data account of the program, upgradeable by `upgrade_authority`.
+    fn program_data_account(upgrade_authority: &Pubkey) -> AccountInfo<'static> {
+        let (program_data_pubkey, _bump_seed) =
+            Pubkey::find_program_address(&[PROGRAM_ID.as_ref()], &bpf_loader_upgradeable::id());
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend(0u64.to_le_bytes());
+        data.push(1);
+        data.extend(upgrade_authority.to_bytes());
+        account(program_data_pubkey, bpf_loader_upgradeable::id(), 1, &data)
+    }
+
+    #[test]
+    fn init_config_takes_over_a_funded_config_address() {
+        let admin = wallet();
+        let treasury = account(Pubkey::new_unique(), system_program::id(), 0, &[]);
+        let (config_pubkey, _bump_seed) = Pubkey::find_program_address(&[b"config"], &PROGRAM_ID);
+        // Someone sent lamports to the config address before it was created.
+        let config_account = account(config_pubkey, system_program::id(), 1_000, &[]);
+        let init_config = |upgrade_authority: &Pubkey| {
+            Processor::process_init_config(
+                &[
+                    admin.clone(),
+                    treasury.clone(),
+                    config_account.clone(),
+                    rent_sysvar(),
+                    system_program(),
+                    program_data_account(upgrade_authority),
+                ],
+                250,
+                100,
+                0,
+                0,
+                &PROGRAM_ID,
+            )
+        };
+
+        assert_eq!(
+            init_config(&Pubkey::new_unique()),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+        init_config(admin.key).unwrap();
+        assert_eq!(config_account.owner, &PROGRAM_ID);
+        assert_eq!(
+            config_account.lamports(),
+            Rent::default().minimum_balance(Config::LEN)
+        );
+        let config_info = Processor::unpack_config(&config_account, &PROGRAM_ID).unwrap();
+        assert_eq!(config_info.admin_pubkey, *admin.key);
+        assert_eq!(config_info.treasury_pubkey, *treasury.key);
+        assert_eq!(config_info.fee_bps, 250);
+        assert_eq!(config_info.referral_bps, 100);
+        assert_eq!(
+            init_config(admin.key),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+    }
+
+    /// The metadata of the auctioned NFT, with a royalty of 500 basis points shared by a verified creator and
+    /// an unverified one.
+    fn royalty_metadata(
+        auction: &EnglishAuction,
+        verified_creator: &Pubkey,
+    ) -> AccountInfo<'static> {
+        metadata_account(
+            auction.nft_mint.key,
+            500,
+            &[
+                (*verified_creator, true, 70),
+                (Pubkey::new_unique(), false, 30),
+            ],
+            None,
+        )
+    }
+
+    #[test]
+    fn english_auction_close_pays_verified_creators_first() {
+        let auction = EnglishAuction::exhibit(Config {
+            fee_bps: 1_000,
+            ..config()
+        });
+        let bidder = auction.bidder(1_000);
+        auction.bid(&bidder, 1_000, None).unwrap();
+
+        let verified_creator = Pubkey::new_unique();
+        let creator_ft = token_account(&auction.ft_mint, &verified_creator, 0);
+        let winner_nft = auction
+            .close(
+                royalty_metadata(&auction, &verified_creator),
+                std::slice::from_ref(&creator_ft),
+            )
+            .unwrap();
+        assert_eq!(token_balance(&winner_nft), 1);
+
+        // The unverified creator's share stays with the seller, and the fee is taken from what is left after the royalty.
+        assert_eq!(token_balance(&creator_ft), 35);
+        assert_eq!(token_balance(&auction.treasury_ft), 96);
+        assert_eq!(token_balance(&auction.exhibitor_ft), 869);
+    }
+
+    #[test]
+    fn english_auction_close_pays_royalties_to_the_creators_only() {
+        let auction = EnglishAuction::exhibit(config());
+        let bidder = auction.bidder(1_000);
+        auction.bid(&bidder, 1_000, None).unwrap();
+
+        let verified_creator = Pubkey::new_unique();
+        assert_eq!(
+            auction
+                .close(
+                    royalty_metadata(&auction, &verified_creator),
+                    &[token_account(&auction.ft_mint, &Pubkey::new_unique(), 0)]
+                )
+                .err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn english_auction_close_splits_the_proceeds_among_payees() {
+        let auction = EnglishAuction::exhibit_with(
+            Config {
+                fee_bps: 250,
+                ..config()
+            },
+            &[6_000, 4_000],
+            0,
+        )
+        .unwrap();
+        let bidder = auction.bidder(1_001);
+        auction.bid(&bidder, 1_001, None).unwrap();
+
+        auction
+            .close(auction.no_metadata(), &auction.payees_ft)
+            .unwrap();
+
+        // The 976 left after the fee is split, the rounding dust going to the first payee.
+        assert_eq!(token_balance(&auction.treasury_ft), 25);
+        assert_eq!(token_balance(&auction.payees_ft[0]), 586);
+        assert_eq!(token_balance(&auction.payees_ft[1]), 390);
+        assert_eq!(token_balance(&auction.exhibitor_ft), 0);
+    }
+
+    #[test]
+    fn english_auction_payee_shares_must_cover_the_proceeds() {
+        for payee_shares_bps in [&[6_000, 3_000][..], &[10_000, 0, 0, 0, 0, 0]] {
+            assert_eq!(
+                EnglishAuction::exhibit_with(config(), payee_shares_bps, 0).err(),
+                Some(AuctionError::InvalidPayees.into())
+            );
+        }
+    }
+
+    #[test]
+    fn english_auction_close_pays_the_referrer_of_the_winning_bid() {
+        let auction = EnglishAuction::exhibit(Config {
+            fee_bps: 250,
+            ..config()
+        });
+        let referral_config = || Config {
+            referral_bps: 1_000,
+            ..config()
+        };
+        let bidder = auction.bidder(1_000);
+        assert_eq!(
+            auction.bid_with(
+                &bidder,
+                1_000,
+                Some(*bidder.wallet.key),
+                referral_config(),
+                &[]
+            ),
+            Err(AuctionError::InvalidInstruction.into())
+        );
+
+        let referrer = Pubkey::new_unique();
+        auction
+            .bid_with(&bidder, 1_000, Some(referrer), referral_config(), &[])
+            .unwrap();
+        assert_eq!(auction.info().referral_bps, 1_000);
+
+        let referrer_ft = token_account(&auction.ft_mint, &referrer, 0);
+        auction
+            .close(auction.no_metadata(), std::slice::from_ref(&referrer_ft))
+            .unwrap();
+        assert_eq!(token_balance(&referrer_ft), 100);
+        assert_eq!(token_balance(&auction.treasury_ft), 25);
+        assert_eq!(token_balance(&auction.exhibitor_ft), 875);
+    }
+
+    #[test]
+    fn bonded_auction_takes_bids_of_bonded_bidders_only() {
+        let auction = EnglishAuction::exhibit_with(config(), &[], 50_000).unwrap();
+        let bidder = auction.bidder(1_000);
+        let bond = auction.bond(&bidder);
+        assert_eq!(
+            auction.bid_with(&bidder, 500, None, config(), std::slice::from_ref(&bond)),
+            Err(AuctionError::BondRequired.into())
+        );
+
+        // A rival funding the bond address first doesn't keep the bidder from posting their bond.
+        **bond.try_borrow_mut_lamports().unwrap() = 1;
+        let bidder_lamports = bidder.wallet.lamports();
+        auction.post_bond(&bidder, &bond).unwrap();
+        let bond_lamports = Rent::default().minimum_balance(Bond::LEN) + 50_000;
+        assert_eq!(bond.owner, &PROGRAM_ID);
+        assert_eq!(bond.lamports(), bond_lamports);
+        assert_eq!(
+            bidder.wallet.lamports(),
+            bidder_lamports - bond_lamports + 1
+        );
+        assert_eq!(
+            auction.post_bond(&bidder, &bond),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+
+        auction
+            .bid_with(&bidder, 500, None, config(), std::slice::from_ref(&bond))
+            .unwrap();
+        assert_eq!(auction.info().highest_bidder_pubkey, *bidder.wallet.key);
+    }
+
+    #[test]
+    fn bonds_are_refunded_to_bidders_who_did_not_win() {
+        let auction = EnglishAuction::exhibit_with(config(), &[], 50_000).unwrap();
+        let (winner, loser) = (auction.bidder(1_000), auction.bidder(1_000));
+        let (winner_bond, loser_bond) = (auction.bond(&winner), auction.bond(&loser));
+        for (bidder, bond, price) in [(&loser, &loser_bond, 500), (&winner, &winner_bond, 600)] {
+            auction.post_bond(bidder, bond).unwrap();
+            auction
+                .bid_with(bidder, price, None, config(), std::slice::from_ref(bond))
+                .unwrap();
+        }
+        let outbid = auction.bidder(0);
+        let outbid_bond = auction.bond(&outbid);
+        auction.post_bond(&outbid, &outbid_bond).unwrap();
+
+        assert_eq!(
+            auction.refund_bond(&outbid, &outbid_bond, EnglishAuction::END_AT - 1),
+            Err(AuctionError::BondLocked.into())
+        );
+        let outbid_lamports = outbid.wallet.lamports();
+        let bond_lamports = outbid_bond.lamports();
+        auction
+            .refund_bond(&outbid, &outbid_bond, EnglishAuction::END_AT)
+            .unwrap();
+        assert_eq!(outbid.wallet.lamports(), outbid_lamports + bond_lamports);
+        assert_eq!(outbid_bond.lamports(), 0);
+
+        // The winner and the runner-up keep their bonds posted until the auction is settled.
+        for (bidder, bond) in [(&winner, &winner_bond), (&loser, &loser_bond)] {
+            assert_eq!(
+                auction.refund_bond(bidder, bond, EnglishAuction::END_AT),
+                Err(AuctionError::BondLocked.into())
+            );
+        }
+        auction.close(auction.no_metadata(), &[]).unwrap();
+        for (bidder, bond) in [(&winner, &winner_bond), (&loser, &loser_bond)] {
+            auction
+                .refund_bond(bidder, bond, EnglishAuction::END_AT)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn unpaid_winner_forfeits_their_bond_to_the_exhibitor() {
+        let auction = EnglishAuction::exhibit_with(config(), &[], 50_000).unwrap();
+        let (runner_up, winner) = (auction.bidder(1_000), auction.bidder(1_000));
+        let (runner_up_bond, winner_bond) = (auction.bond(&runner_up), auction.bond(&winner));
+        for (bidder, bond, price) in [
+            (&runner_up, &runner_up_bond, 500),
+            (&winner, &winner_bond, 600),
+        ] {
+            auction.post_bond(bidder, bond).unwrap();
+            auction
+                .bid_with(bidder, price, None, config(), std::slice::from_ref(bond))
+                .unwrap();
+        }
+
+        let exhibitor_nft = token_account(&auction.nft_mint, auction.exhibitor.key, 0);
+        let forfeit_bond = |now| {
+            let mut accounts = vec![
+                auction.exhibitor.clone(),
+                auction.nft_temp.clone(),
+                exhibitor_nft.clone(),
+                auction.escrow.clone(),
+                winner_bond.clone(),
+                auction.vault.clone(),
+                winner.ft.clone(),
+                winner.wallet.clone(),
+                clock_sysvar(now, 5),
+                token_program(),
+                pda_account(),
+            ];
+            accounts.extend(auction.runner_up_accounts());
+            Processor::process_forfeit_bond(&accounts, &PROGRAM_ID)
+        };
+        let settle_by = EnglishAuction::END_AT + SETTLEMENT_GRACE_PERIOD_SEC;
+        assert_eq!(
+            forfeit_bond(settle_by - 1),
+            Err(AuctionError::ActiveAuction.into())
+        );
+
+        let exhibitor_lamports = auction.exhibitor.lamports();
+        let bond_lamports = winner_bond.lamports();
+        forfeit_bond(settle_by).unwrap();
+        assert_eq!(token_balance(&exhibitor_nft), 1);
+        assert_eq!(token_balance(&winner.ft), 1_000);
+        assert_eq!(token_balance(&runner_up.ft), 1_000);
+        assert!(auction.exhibitor.lamports() > exhibitor_lamports + bond_lamports);
+        assert_eq!(winner_bond.lamports(), 0);
+        assert_eq!(auction.escrow.lamports(), 0);
+    }
+
+    /// An English auction the runner-up bid 500 in, and the winner 600.
+    fn auction_with_runner_up() -> (EnglishAuction, Bidder, Bidder) {
+        let auction = EnglishAuction::exhibit(config());
+        let (runner_up, winner) = (auction.bidder(1_000), auction.bidder(1_000));
+        auction.bid(&runner_up, 500, None).unwrap();
+        auction.bid(&winner, 600, None).unwrap();
+        (auction, runner_up, winner)
+    }
+
+    #[test]
+    fn second_chance_is_offered_once_the_winner_did_not_settle() {
+        let (auction, runner_up, winner) = auction_with_runner_up();
+        let settle_by = EnglishAuction::END_AT + SETTLEMENT_GRACE_PERIOD_SEC;
+        assert_eq!(
+            auction.offer_second_chance(settle_by - 1, &[]),
+            Err(ProgramError::NotEnoughAccountKeys)
+        );
+
+        auction.offer_second_chance(settle_by, &[]).unwrap();
+        let auction_info = auction.info();
+        assert_eq!(auction_info.highest_bidder_pubkey, *runner_up.wallet.key);
+        assert_eq!(auction_info.price, 500);
+        assert_eq!(auction_info.runner_up_pubkey, Pubkey::default());
+        assert_eq!(
+            auction_info.second_chance_until,
+            settle_by + SECOND_CHANCE_WINDOW_SEC
+        );
+        assert_eq!(token_balance(&winner.ft), 1_000);
+        assert_eq!(token_balance(&auction.vault), 500);
+
+        // The runner-up accepts by closing the auction at their bid.
+        let runner_up_nft = auction.close(auction.no_metadata(), &[]).unwrap();
+        assert_eq!(token_balance(&runner_up_nft), 1);
+        assert_eq!(token_balance(&runner_up.ft), 500);
+        assert_eq!(token_balance(&auction.exhibitor_ft), 500);
+    }
+
+    #[test]
+    fn admin_disqualifies_the_winner_within_the_grace_period() {
+        let (auction, runner_up, _winner) = auction_with_runner_up();
+        let admin = wallet();
+        let config_info = || Config {
+            admin_pubkey: *admin.key,
+            ..config()
+        };
+        assert_eq!(
+            auction.offer_second_chance(
+                EnglishAuction::END_AT,
+                &[wallet(), config_account(config_info())]
+            ),
+            Err(AuctionError::NotAdmin.into())
+        );
+        auction
+            .offer_second_chance(
+                EnglishAuction::END_AT,
+                &[admin.clone(), config_account(config_info())],
+            )
+            .unwrap();
+        assert_eq!(auction.info().highest_bidder_pubkey, *runner_up.wallet.key);
+    }
+
+    #[test]
+    fn unaccepted_second_chance_returns_the_nft_and_the_bid() {
+        let (auction, runner_up, _winner) = auction_with_runner_up();
+        let settle_by = EnglishAuction::END_AT + SETTLEMENT_GRACE_PERIOD_SEC;
+        auction.offer_second_chance(settle_by, &[]).unwrap();
+
+        let exhibitor_nft = token_account(&auction.nft_mint, auction.exhibitor.key, 0);
+        let expire_second_chance = |now| {
+            Processor::process_expire_second_chance(
+                &[
+                    auction.exhibitor.clone(),
+                    auction.nft_temp.clone(),
+                    exhibitor_nft.clone(),
+                    auction.escrow.clone(),
+                    auction.vault.clone(),
+                    runner_up.ft.clone(),
+                    runner_up.wallet.clone(),
+                    clock_sysvar(now, 6),
+                    token_program(),
+                    pda_account(),
+                ],
+                &PROGRAM_ID,
+            )
+        };
+        let second_chance_until = settle_by + SECOND_CHANCE_WINDOW_SEC;
+        assert_eq!(
+            expire_second_chance(second_chance_until),
+            Err(AuctionError::ActiveAuction.into())
+        );
+
+        expire_second_chance(second_chance_until + 1).unwrap();
+        assert_eq!(token_balance(&exhibitor_nft), 1);
+        assert_eq!(token_balance(&runner_up.ft), 1_000);
+        assert_eq!(auction.vault.lamports(), 0);
+        assert_eq!(auction.escrow.lamports(), 0);
+    }
+
+    #[test]
+    fn runner_up_is_refunded_once_no_second_chance_can_be_offered() {
+        let (auction, runner_up, winner) = auction_with_runner_up();
+        let refund_runner_up = |now| {
+            let mut accounts = vec![
+                auction.escrow.clone(),
+                clock_sysvar(now, 6),
+                token_program(),
+                pda_account(),
+            ];
+            accounts.extend(auction.runner_up_accounts());
+            Processor::process_refund_runner_up(&accounts, &PROGRAM_ID)
+        };
+        let refund_after =
+            EnglishAuction::END_AT + SETTLEMENT_GRACE_PERIOD_SEC + SECOND_CHANCE_WINDOW_SEC;
+        assert_eq!(
+            refund_runner_up(refund_after),
+            Err(AuctionError::ActiveAuction.into())
+        );
+
+        refund_runner_up(refund_after + 1).unwrap();
+        assert_eq!(token_balance(&runner_up.ft), 1_000);
+        assert_eq!(auction.info().runner_up_pubkey, Pubkey::default());
+        assert_eq!(
+            auction.offer_second_chance(refund_after + 1, &[]),
+            Err(AuctionError::NoBidderFound.into())
+        );
+
+        // The winner can still settle.
+        auction.close(auction.no_metadata(), &[]).unwrap();
+        assert_eq!(token_balance(&winner.ft), 400);
+        assert_eq!(token_balance(&auction.exhibitor_ft), 600);
+    }
+
+    #[test]
+    fn retracted_bid_restores_the_runner_up_for_a_penalty() {
+        let (auction, runner_up, winner) = auction_with_runner_up();
+        assert_eq!(
+            auction.retract_bid(NOW + 120, 0),
+            Err(AuctionError::InvalidInstruction.into())
+        );
+        let retract_by = EnglishAuction::END_AT - RETRACTION_CUTOFF_SEC;
+        assert_eq!(
+            auction.retract_bid(retract_by, 1_000),
+            Err(AuctionError::InactiveAuction.into())
+        );
+
+        auction.retract_bid(retract_by - 1, 1_000).unwrap();
+        assert_eq!(token_balance(&auction.exhibitor_ft), 60);
+        assert_eq!(token_balance(&winner.ft), 940);
+        let auction_info = auction.info();
+        assert_eq!(auction_info.highest_bidder_pubkey, *runner_up.wallet.key);
+        assert_eq!(auction_info.price, 500);
+        assert_eq!(auction_info.runner_up_pubkey, Pubkey::default());
+        assert_eq!(token_balance(&auction.vault), 500);
+
+        auction.close(auction.no_metadata(), &[]).unwrap();
+        assert_eq!(token_balance(&runner_up.ft), 500);
+        assert_eq!(token_balance(&auction.exhibitor_ft), 560);
+    }
+
+    #[test]
+    fn retracting_the_only_bid_restores_the_initial_price() {
+        let auction = EnglishAuction::exhibit(config());
+        let bidder = auction.bidder(1_000);
+        auction.bid(&bidder, 500, None).unwrap();
+
+        auction.retract_bid(NOW + 120, 1_000).unwrap();
+        assert_eq!(token_balance(&bidder.ft), 950);
+        let auction_info = auction.info();
+        assert_eq!(auction_info.price, 100);
+        assert_eq!(auction_info.highest_bidder_pubkey, Pubkey::default());
+
+        let next_bidder = auction.bidder(1_000);
+        auction.bid(&next_bidder, 150, None).unwrap();
+        assert_eq!(token_balance(&auction.vault), 150);
+    }
+
+    fn merkle_parent(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        if left <= right {
+            keccak::hashv(&[left, right]).to_bytes()
+        } else {
+            keccak::hashv(&[right, left]).to_bytes()
+        }
+    }
+
+    #[test]
+    fn allowlist_accepts_proofs_of_listed_wallets() {
+        let wallets: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = wallets
+            .iter()
+            .map(|wallet| keccak::hash(wallet.as_ref()).to_bytes())
+            .collect();
+        let left = merkle_parent(&leaves[0], &leaves[1]);
+        let right = merkle_parent(&leaves[2], &leaves[3]);
+        let root = merkle_parent(&left, &right);
+
+        assert_eq!(
+            Processor::check_allowlist(&root, &wallets[0], &[leaves[1], right]),
+            Ok(())
+        );
+        assert_eq!(
+            Processor::check_allowlist(&root, &wallets[3], &[leaves[2], left]),
+            Ok(())
+        );
+        // A single-wallet list has the leaf as root and an empty proof.
+        assert_eq!(
+            Processor::check_allowlist(&leaves[2], &wallets[2], &[]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn allowlist_rejects_invalid_proofs() {
+        let wallets: Vec<Pubkey> = (0..2).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = wallets
+            .iter()
+            .map(|wallet| keccak::hash(wallet.as_ref()).to_bytes())
+            .collect();
+        let root = merkle_parent(&leaves[0], &leaves[1]);
+        let not_allowlisted = Err(AuctionError::NotAllowlisted.into());
+
+        assert_eq!(
+            Processor::check_allowlist(&root, &Pubkey::new_unique(), &[leaves[1]]),
+            not_allowlisted
+        );
+        assert_eq!(
+            Processor::check_allowlist(&root, &wallets[0], &[]),
+            not_allowlisted
+        );
+        // A proof longer than the tree doesn't verify.
+        assert_eq!(
+            Processor::check_allowlist(&root, &wallets[0], &[leaves[1], leaves[1]]),
+            not_allowlisted
+        );
+    }
+
+    #[test]
+    fn empty_allowlist_root_accepts_anyone() {
+        assert_eq!(
+            Processor::check_allowlist(&[0; 32], &Pubkey::new_unique(), &[]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn denylist_pages_grow_with_every_denied_wallet() {
+        let admin = wallet();
+        let config_info = || Config {
+            admin_pubkey: *admin.key,
+            ..config()
+        };
+        let denied = Pubkey::new_unique();
+        let mut sharing_bytes = [7; 32];
+        sharing_bytes[0] = denied.as_ref()[0];
+        let sharing_page = Pubkey::new_from_array(sharing_bytes);
+        let denylist_page = denylist_page(&denied);
+        // Someone sent lamports to the page address before it was created.
+        **denylist_page.try_borrow_mut_lamports().unwrap() = 1;
+
+        let add_to_denylist = |admin: &AccountInfo<'static>, wallet| {
+            Processor::process_add_to_denylist(
+                &[
+                    admin.clone(),
+                    config_account(config_info()),
+                    denylist_page.clone(),
+                    rent_sysvar(),
+                    system_program(),
+                ],
+                wallet,
+                &PROGRAM_ID,
+            )
+        };
+        assert_eq!(
+            add_to_denylist(&wallet(), denied),
+            Err(AuctionError::NotAdmin.into())
+        );
+        add_to_denylist(&admin, denied).unwrap();
+        add_to_denylist(&admin, sharing_page).unwrap();
+        add_to_denylist(&admin, denied).unwrap();
+        assert_eq!(denylist_page.owner, &PROGRAM_ID);
+        assert_eq!(denylist_page.data_len(), DenylistPage::wallet_span(1).end);
+        assert_eq!(
+            denylist_page.lamports(),
+            Rent::default().minimum_balance(DenylistPage::wallet_span(1).end)
+        );
+        for wallet in [&denied, &sharing_page] {
+            assert_eq!(
+                Processor::check_not_denied(wallet, &denylist_page, &PROGRAM_ID),
+                Err(AuctionError::Denied.into())
+            );
+        }
+
+        Processor::process_remove_from_denylist(
+            &[
+                admin.clone(),
+                config_account(config_info()),
+                denylist_page.clone(),
+            ],
+            denied,
+            &PROGRAM_ID,
+        )
+        .unwrap();
+        assert_eq!(
+            Processor::check_not_denied(&denied, &denylist_page, &PROGRAM_ID),
+            Ok(())
+        );
+        assert_eq!(
+            Processor::check_not_denied(&sharing_page, &denylist_page, &PROGRAM_ID),
+            Err(AuctionError::Denied.into())
+        );
+    }
+
+    #[test]
+    fn english_auction_duration_must_be_within_the_config_bounds() {
+        let bounded_config = |min_duration_sec, max_duration_sec| Config {
+            min_duration_sec,
+            max_duration_sec,
+            ..config()
+        };
+        for (min_duration_sec, max_duration_sec) in [(0, 3_600), (86_401, 0)] {
+            assert_eq!(
+                EnglishAuction::exhibit_with(
+                    bounded_config(min_duration_sec, max_duration_sec),
+                    &[],
+                    0
+                )
+                .err(),
+                Some(AuctionError::InvalidDuration.into())
+            );
+        }
+        let auction = EnglishAuction::exhibit(bounded_config(3_600, 86_400));
+        assert_eq!(auction.info().end_at, EnglishAuction::END_AT);
+
+        let admin = wallet();
+        let config_account = config_account(Config {
+            admin_pubkey: *admin.key,
+            ..config()
+        });
+        assert_eq!(
+            Processor::process_set_duration_bounds(
+                &[admin.clone(), config_account.clone()],
+                86_400,
+                3_600,
+                &PROGRAM_ID,
+            ),
+            Err(AuctionError::InvalidDuration.into())
+        );
+        Processor::process_set_duration_bounds(
+            &[admin, config_account.clone()],
+            60,
+            0,
+            &PROGRAM_ID,
+        )
+        .unwrap();
+        let config_info = Config::unpack(&config_account.try_borrow_data().unwrap()).unwrap();
+        assert_eq!(
+            (config_info.min_duration_sec, config_info.max_duration_sec),
+            (60, 0)
+        );
     }
-}
\ No newline at end of file
+}
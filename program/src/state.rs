@@ -1,3 +1,4 @@
+use crate::error::AuctionError;
 use solana_program::{
     program_error::ProgramError,
     program_pack::{Pack, Sealed},
@@ -6,6 +7,36 @@ use solana_program::{
 
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use solana_program::program_pack::IsInitialized;
+use std::cmp::Ordering;
+use std::ops::Range;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AuctionKind {
+    /// The exhibitor sells an NFT to the highest bidder
+    #[default]
+    English,
+    /// The exhibitor buys an NFT of a collection from the lowest asking holder
+    Reverse,
+    /// The exhibitor picks the best of the NFT and FT offers once the auction ends
+    Barter,
+}
+
+impl AuctionKind {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(AuctionKind::English),
+            1 => Some(AuctionKind::Reverse),
+            2 => Some(AuctionKind::Barter),
+            _ => None,
+        }
+    }
+}
+
+/// Maximum number of payees the proceeds of an auction can be split among
+pub const MAX_PAYEES: usize = 5;
+
+/// Length of the escrow accounts of auctions exhibited before the auction kinds were introduced
+pub const LEGACY_AUCTION_LEN: usize = 209;
 
 pub struct Auction {
     pub is_initialized: bool,
@@ -13,7 +44,7 @@ pub struct Auction {
     pub exhibitor_pubkey: Pubkey,
     /// Exhibiting NFT pubkey of exhibitor in escrow
     pub exhibiting_nft_temp_pubkey: Pubkey,
-    /// FT pubkey of exhibitor to get token into
+    /// FT pubkey of exhibitor to get token into, or the account receiving the lamports of a lamport auction
     pub exhibitor_ft_receiving_pubkey: Pubkey,
     /// Current price
     pub price: u64,
@@ -21,10 +52,186 @@ pub struct Auction {
     pub end_at: i64,
     /// Wallet pubkey of highest bidder
     pub highest_bidder_pubkey: Pubkey,
-    /// FT pubkey of highest bidder in escrow
+    /// FT pubkey of highest bidder in escrow, or the vault of the auction holding the bids
     pub highest_bidder_ft_temp_pubkey: Pubkey,
     /// FT pubkey of highest bidder to return to when the auction cancel or other bidder become the highest bidder.
     pub highest_bidder_ft_returning_pubkey: Pubkey,
+    /// Wallet pubkey of the referrer of the highest bid, default when the bid wasn't referred
+    pub highest_bidder_referrer_pubkey: Pubkey,
+    /// Share of the proceeds paid to the referrer, in basis points, set from the config when the bid is placed
+    pub referral_bps: u16,
+    /// Whether bids are compared upwards or downwards.
+    /// In a reverse auction the exhibitor escrows the FT budget and the bidders escrow the NFT they ask to sell,
+    /// so the NFT and FT roles of the escrow and bidder accounts are swapped.
+    pub kind: AuctionKind,
+    /// Collection the NFT of a reverse auction must be a verified member of
+    pub collection_pubkey: Pubkey,
+    /// Number of payees splitting the proceeds, zero when they all go to `exhibitor_ft_receiving_pubkey`
+    pub payee_count: u8,
+    /// FT pubkeys of the payees to get token into, or their wallet pubkeys in a lamport auction
+    pub payee_receiving_pubkeys: [Pubkey; MAX_PAYEES],
+    /// Share of the proceeds of every payee, in basis points
+    pub payee_shares_bps: [u16; MAX_PAYEES],
+    /// Index of the payee getting the rounding dust
+    pub dust_payee_index: u8,
+    /// Whether bids are paid in lamports held in the vault PDA derived from `b"vault"` and the escrow account
+    pub pays_in_lamports: bool,
+    /// Whether `price` is in reference units, bids being made in any mint with a price feed
+    pub prices_in_reference: bool,
+    /// Lamports every bidder bonds before their first bid, zero when no bond is required
+    pub bond_amount: u64,
+    /// Wallet pubkey that paid the rent of the escrow and the NFT temporary account, getting it back on close
+    pub rent_payer_pubkey: Pubkey,
+    /// Wallet pubkey that paid the rent of the highest bidder's temporary account, getting it back on close
+    pub highest_bidder_rent_payer_pubkey: Pubkey,
+    /// Wallet pubkey of the runner-up, whose bid stays in escrow for a second chance offer.
+    /// Only English auctions paid in FT keep a runner-up.
+    pub runner_up_pubkey: Pubkey,
+    /// FT pubkey of the runner-up in escrow, or the vault of the auction holding the bids
+    pub runner_up_ft_temp_pubkey: Pubkey,
+    /// FT pubkey of the runner-up to return to when the runner-up is outbid or the auction is settled
+    pub runner_up_ft_returning_pubkey: Pubkey,
+    /// Price of the runner-up's bid
+    pub runner_up_price: u64,
+    /// Wallet pubkey of the referrer of the runner-up's bid, default when the bid wasn't referred
+    pub runner_up_referrer_pubkey: Pubkey,
+    /// Share of the proceeds paid to the referrer of the runner-up's bid, in basis points
+    pub runner_up_referral_bps: u16,
+    /// Wallet pubkey that paid the rent of the runner-up's temporary account
+    pub runner_up_rent_payer_pubkey: Pubkey,
+    /// Time until which the runner-up offered a second chance can settle, zero when no offer was made
+    pub second_chance_until: i64,
+    /// Price the auction started at, restored when the only bid is retracted
+    pub initial_price: u64,
+    /// Auction house the auction was exhibited in, default when it wasn't
+    pub house_pubkey: Pubkey,
+    /// Minimum raise over the highest bid of an English auction, in basis points, inherited from the house
+    pub min_increment_bps: u16,
+    /// Bids placed this many seconds before the end extend the auction, inherited from the house
+    pub anti_snipe_window_sec: u32,
+    /// Seconds left in the auction after a bid placed within the anti-snipe window
+    pub anti_snipe_extension_sec: u32,
+    /// Collection whose NFT holders may bid, or mint bidders must hold `gate_amount` of, default when anyone may bid
+    pub gate_pubkey: Pubkey,
+    /// Amount of the gate mint bidders must hold, zero when the gate is a collection
+    pub gate_amount: u64,
+    /// Merkle root of the wallets allowed to bid, zeroed when anyone may bid
+    pub bidder_allowlist_root: [u8; 32],
+    /// Duration of the auction, in seconds, restarted when an unsold auction is relisted
+    pub duration_sec: u64,
+    /// Number of times an unsold auction can still be relisted by anyone
+    pub auto_relist_cycles: u8,
+    /// Price drop of every automatic relisting, in basis points of the price
+    pub auto_relist_price_drop_bps: u16,
+    /// Whether bids are deposited into the vault of the auction, false for auctions migrated from the legacy layout
+    pub bids_in_vault: bool,
+    /// Protocol fee taken from the proceeds, in basis points, or the fee of the house the auction is exhibited in,
+    /// fixed when the auction was exhibited
+    pub fee_bps: u16,
+    /// Wallet pubkey owning the FT accounts the fee is paid to, fixed when the auction was exhibited
+    pub treasury_pubkey: Pubkey,
+    /// Penalty the exhibitor pays the highest bidder to cancel the auction after bids, in basis points
+    /// of the highest bid, fixed when the auction was exhibited
+    pub cancel_penalty_bps: u16,
+}
+
+impl Auction {
+    /// Whether FT bids are deposited into the vault token account of the auction, derived from `b"vault"` and the
+    /// escrow account and the authority of its own FT, rather than escrowed in temporary accounts of their own
+    pub fn holds_ft_in_vault(&self) -> bool {
+        self.bids_in_vault && !self.pays_in_lamports
+    }
+
+    /// Unpacks an auction of the legacy layout, an English auction paid in FT whose bids are escrowed in
+    /// temporary accounts of their own, filling the newer fields with the defaults matching its behavior.
+    pub fn unpack_legacy(src: &[u8]) -> Result<Auction, ProgramError> {
+        if src.len() != LEGACY_AUCTION_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let src = array_ref![src, 0, LEGACY_AUCTION_LEN];
+        let (
+            is_initialized,
+            exhibitor_pubkey,
+            exhibiting_nft_temp_pubkey,
+            exhibitor_ft_receiving_pubkey,
+            price,
+            end_at,
+            highest_bidder_pubkey,
+            highest_bidder_ft_temp_pubkey,
+            highest_bidder_ft_returning_pubkey,
+        ) = array_refs![src, 1, 32, 32, 32, 8, 8, 32, 32, 32];
+        if *is_initialized != [1] {
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        let mut auction = Auction::unpack_unchecked(&[0; Auction::LEN])?;
+        auction.is_initialized = true;
+        auction.exhibitor_pubkey = Pubkey::new_from_array(*exhibitor_pubkey);
+        auction.exhibiting_nft_temp_pubkey = Pubkey::new_from_array(*exhibiting_nft_temp_pubkey);
+        auction.exhibitor_ft_receiving_pubkey =
+            Pubkey::new_from_array(*exhibitor_ft_receiving_pubkey);
+        auction.price = u64::from_le_bytes(*price);
+        auction.initial_price = auction.price;
+        auction.end_at = i64::from_le_bytes(*end_at);
+        auction.highest_bidder_pubkey = Pubkey::new_from_array(*highest_bidder_pubkey);
+        auction.highest_bidder_ft_temp_pubkey =
+            Pubkey::new_from_array(*highest_bidder_ft_temp_pubkey);
+        auction.highest_bidder_ft_returning_pubkey =
+            Pubkey::new_from_array(*highest_bidder_ft_returning_pubkey);
+        // The exhibitor and the highest bidder paid for their own accounts.
+        auction.rent_payer_pubkey = auction.exhibitor_pubkey;
+        auction.highest_bidder_rent_payer_pubkey = auction.highest_bidder_pubkey;
+        Ok(auction)
+    }
+
+    /// Whether `bid_value` beats the highest bid of an English auction by the minimum increment.
+    /// The first bid only has to beat the initial price.
+    pub fn outbids(&self, bid_value: u64) -> bool {
+        if self.highest_bidder_pubkey == Pubkey::default() {
+            return bid_value > self.price;
+        }
+        let increment =
+            (self.price as u128 * self.min_increment_bps as u128 / BASIS_POINTS as u128) as u64;
+        bid_value > self.price && bid_value - self.price >= increment
+    }
+
+    /// Extends the auction so a bid placed within the anti-snipe window leaves the extension to respond.
+    pub fn extend_for_late_bid(&mut self, now: i64) -> Result<(), ProgramError> {
+        let remaining_sec = self
+            .end_at
+            .checked_sub(now)
+            .ok_or(AuctionError::AmountOverflow)?;
+        if remaining_sec < self.anti_snipe_window_sec as i64 {
+            let extended_end_at = now
+                .checked_add(self.anti_snipe_extension_sec as i64)
+                .ok_or(AuctionError::AmountOverflow)?;
+            self.end_at = self.end_at.max(extended_end_at);
+        }
+        Ok(())
+    }
+
+    /// Makes the runner-up the highest bidder, leaving the auction without a runner-up.
+    pub fn promote_runner_up(&mut self) {
+        self.price = self.runner_up_price;
+        self.highest_bidder_pubkey = self.runner_up_pubkey;
+        self.highest_bidder_ft_temp_pubkey = self.runner_up_ft_temp_pubkey;
+        self.highest_bidder_ft_returning_pubkey = self.runner_up_ft_returning_pubkey;
+        self.highest_bidder_referrer_pubkey = self.runner_up_referrer_pubkey;
+        self.referral_bps = self.runner_up_referral_bps;
+        self.highest_bidder_rent_payer_pubkey = self.runner_up_rent_payer_pubkey;
+        self.clear_runner_up();
+    }
+
+    /// Leaves the auction without a runner-up, once their bid is refunded or promoted.
+    pub fn clear_runner_up(&mut self) {
+        self.runner_up_pubkey = Pubkey::default();
+        self.runner_up_ft_temp_pubkey = Pubkey::default();
+        self.runner_up_ft_returning_pubkey = Pubkey::default();
+        self.runner_up_price = 0;
+        self.runner_up_referrer_pubkey = Pubkey::default();
+        self.runner_up_referral_bps = 0;
+        self.runner_up_rent_payer_pubkey = Pubkey::default();
+    }
 }
 
 impl Sealed for Auction {}
@@ -36,7 +243,7 @@ impl IsInitialized for Auction {
 }
 
 impl Pack for Auction {
-    const LEN: usize = 209;
+    const LEN: usize = 870;
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let dst = array_mut_ref![dst, 0, Auction::LEN];
@@ -50,7 +257,46 @@ impl Pack for Auction {
             highest_bidder_pubkey_dst,
             highest_bidder_ft_temp_pubkey_dst,
             highest_bidder_ft_returning_pubkey_dst,
-        ) = mut_array_refs![dst, 1, 32, 32, 32, 8, 8, 32, 32, 32];
+            highest_bidder_referrer_pubkey_dst,
+            referral_bps_dst,
+            kind_dst,
+            collection_pubkey_dst,
+            payee_count_dst,
+            payee_receiving_pubkeys_dst,
+            payee_shares_bps_dst,
+            dust_payee_index_dst,
+            pays_in_lamports_dst,
+            prices_in_reference_dst,
+            bond_amount_dst,
+            rent_payer_pubkey_dst,
+            highest_bidder_rent_payer_pubkey_dst,
+            runner_up_pubkey_dst,
+            runner_up_ft_temp_pubkey_dst,
+            runner_up_ft_returning_pubkey_dst,
+            runner_up_price_dst,
+            runner_up_referrer_pubkey_dst,
+            runner_up_referral_bps_dst,
+            runner_up_rent_payer_pubkey_dst,
+            second_chance_until_dst,
+            initial_price_dst,
+            house_pubkey_dst,
+            min_increment_bps_dst,
+            anti_snipe_window_sec_dst,
+            anti_snipe_extension_sec_dst,
+            gate_pubkey_dst,
+            gate_amount_dst,
+            bidder_allowlist_root_dst,
+            duration_sec_dst,
+            auto_relist_cycles_dst,
+            auto_relist_price_drop_bps_dst,
+            bids_in_vault_dst,
+            fee_bps_dst,
+            treasury_pubkey_dst,
+            cancel_penalty_bps_dst,
+        ) = mut_array_refs![
+            dst, 1, 32, 32, 32, 8, 8, 32, 32, 32, 32, 2, 1, 32, 1, 160, 10, 1, 1, 1, 8, 32, 32, 32,
+            32, 32, 8, 32, 2, 32, 8, 8, 32, 2, 4, 4, 32, 8, 32, 8, 1, 2, 1, 2, 32, 2
+        ];
 
         let Auction {
             is_initialized,
@@ -62,6 +308,42 @@ impl Pack for Auction {
             highest_bidder_pubkey,
             highest_bidder_ft_temp_pubkey,
             highest_bidder_ft_returning_pubkey,
+            highest_bidder_referrer_pubkey,
+            referral_bps,
+            kind,
+            collection_pubkey,
+            payee_count,
+            payee_receiving_pubkeys,
+            payee_shares_bps,
+            dust_payee_index,
+            pays_in_lamports,
+            prices_in_reference,
+            bond_amount,
+            rent_payer_pubkey,
+            highest_bidder_rent_payer_pubkey,
+            runner_up_pubkey,
+            runner_up_ft_temp_pubkey,
+            runner_up_ft_returning_pubkey,
+            runner_up_price,
+            runner_up_referrer_pubkey,
+            runner_up_referral_bps,
+            runner_up_rent_payer_pubkey,
+            second_chance_until,
+            initial_price,
+            house_pubkey,
+            min_increment_bps,
+            anti_snipe_window_sec,
+            anti_snipe_extension_sec,
+            gate_pubkey,
+            gate_amount,
+            bidder_allowlist_root,
+            duration_sec,
+            auto_relist_cycles,
+            auto_relist_price_drop_bps,
+            bids_in_vault,
+            fee_bps,
+            treasury_pubkey,
+            cancel_penalty_bps,
         } = self;
 
         is_initialized_dst[0] = *is_initialized as u8;
@@ -74,6 +356,53 @@ impl Pack for Auction {
         highest_bidder_ft_temp_pubkey_dst.copy_from_slice(highest_bidder_ft_temp_pubkey.as_ref());
         highest_bidder_ft_returning_pubkey_dst
             .copy_from_slice(highest_bidder_ft_returning_pubkey.as_ref());
+        highest_bidder_referrer_pubkey_dst.copy_from_slice(highest_bidder_referrer_pubkey.as_ref());
+        *referral_bps_dst = referral_bps.to_le_bytes();
+        kind_dst[0] = *kind as u8;
+        collection_pubkey_dst.copy_from_slice(collection_pubkey.as_ref());
+        payee_count_dst[0] = *payee_count;
+        for (dst, pubkey) in payee_receiving_pubkeys_dst
+            .chunks_exact_mut(32)
+            .zip(payee_receiving_pubkeys.iter())
+        {
+            dst.copy_from_slice(pubkey.as_ref());
+        }
+        for (dst, share_bps) in payee_shares_bps_dst
+            .chunks_exact_mut(2)
+            .zip(payee_shares_bps.iter())
+        {
+            dst.copy_from_slice(&share_bps.to_le_bytes());
+        }
+        dust_payee_index_dst[0] = *dust_payee_index;
+        pays_in_lamports_dst[0] = *pays_in_lamports as u8;
+        prices_in_reference_dst[0] = *prices_in_reference as u8;
+        *bond_amount_dst = bond_amount.to_le_bytes();
+        rent_payer_pubkey_dst.copy_from_slice(rent_payer_pubkey.as_ref());
+        highest_bidder_rent_payer_pubkey_dst
+            .copy_from_slice(highest_bidder_rent_payer_pubkey.as_ref());
+        runner_up_pubkey_dst.copy_from_slice(runner_up_pubkey.as_ref());
+        runner_up_ft_temp_pubkey_dst.copy_from_slice(runner_up_ft_temp_pubkey.as_ref());
+        runner_up_ft_returning_pubkey_dst.copy_from_slice(runner_up_ft_returning_pubkey.as_ref());
+        *runner_up_price_dst = runner_up_price.to_le_bytes();
+        runner_up_referrer_pubkey_dst.copy_from_slice(runner_up_referrer_pubkey.as_ref());
+        *runner_up_referral_bps_dst = runner_up_referral_bps.to_le_bytes();
+        runner_up_rent_payer_pubkey_dst.copy_from_slice(runner_up_rent_payer_pubkey.as_ref());
+        *second_chance_until_dst = second_chance_until.to_le_bytes();
+        *initial_price_dst = initial_price.to_le_bytes();
+        house_pubkey_dst.copy_from_slice(house_pubkey.as_ref());
+        *min_increment_bps_dst = min_increment_bps.to_le_bytes();
+        *anti_snipe_window_sec_dst = anti_snipe_window_sec.to_le_bytes();
+        *anti_snipe_extension_sec_dst = anti_snipe_extension_sec.to_le_bytes();
+        gate_pubkey_dst.copy_from_slice(gate_pubkey.as_ref());
+        *gate_amount_dst = gate_amount.to_le_bytes();
+        *bidder_allowlist_root_dst = *bidder_allowlist_root;
+        *duration_sec_dst = duration_sec.to_le_bytes();
+        auto_relist_cycles_dst[0] = *auto_relist_cycles;
+        *auto_relist_price_drop_bps_dst = auto_relist_price_drop_bps.to_le_bytes();
+        bids_in_vault_dst[0] = *bids_in_vault as u8;
+        *fee_bps_dst = fee_bps.to_le_bytes();
+        treasury_pubkey_dst.copy_from_slice(treasury_pubkey.as_ref());
+        *cancel_penalty_bps_dst = cancel_penalty_bps.to_le_bytes();
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
@@ -88,14 +417,54 @@ impl Pack for Auction {
             highest_bidder_pubkey,
             highest_bidder_ft_temp_pubkey,
             highest_bidder_ft_returning_pubkey,
-        ) = array_refs![src, 1, 32, 32, 32, 8, 8, 32, 32, 32];
+            highest_bidder_referrer_pubkey,
+            referral_bps,
+            kind,
+            collection_pubkey,
+            payee_count,
+            payee_receiving_pubkeys,
+            payee_shares_bps,
+            dust_payee_index,
+            pays_in_lamports,
+            prices_in_reference,
+            bond_amount,
+            rent_payer_pubkey,
+            highest_bidder_rent_payer_pubkey,
+            runner_up_pubkey,
+            runner_up_ft_temp_pubkey,
+            runner_up_ft_returning_pubkey,
+            runner_up_price,
+            runner_up_referrer_pubkey,
+            runner_up_referral_bps,
+            runner_up_rent_payer_pubkey,
+            second_chance_until,
+            initial_price,
+            house_pubkey,
+            min_increment_bps,
+            anti_snipe_window_sec,
+            anti_snipe_extension_sec,
+            gate_pubkey,
+            gate_amount,
+            bidder_allowlist_root,
+            duration_sec,
+            auto_relist_cycles,
+            auto_relist_price_drop_bps,
+            bids_in_vault,
+            fee_bps,
+            treasury_pubkey,
+            cancel_penalty_bps,
+        ) = array_refs![
+            src, 1, 32, 32, 32, 8, 8, 32, 32, 32, 32, 2, 1, 32, 1, 160, 10, 1, 1, 1, 8, 32, 32, 32,
+            32, 32, 8, 32, 2, 32, 8, 8, 32, 2, 4, 4, 32, 8, 32, 8, 1, 2, 1, 2, 32, 2
+        ];
         let is_initialized = match is_initialized {
             [0] => false,
             [1] => true,
             _ => return Err(ProgramError::InvalidAccountData),
         };
+        let kind = AuctionKind::from_u8(kind[0]).ok_or(ProgramError::InvalidAccountData)?;
 
-        Ok(Auction {
+        let mut auction = Auction {
             is_initialized,
             exhibitor_pubkey: Pubkey::new_from_array(*exhibitor_pubkey),
             exhibiting_nft_temp_pubkey: Pubkey::new_from_array(*exhibiting_nft_temp_pubkey),
@@ -107,6 +476,1598 @@ impl Pack for Auction {
             highest_bidder_ft_returning_pubkey: Pubkey::new_from_array(
                 *highest_bidder_ft_returning_pubkey,
             ),
+            highest_bidder_referrer_pubkey: Pubkey::new_from_array(*highest_bidder_referrer_pubkey),
+            referral_bps: u16::from_le_bytes(*referral_bps),
+            kind,
+            collection_pubkey: Pubkey::new_from_array(*collection_pubkey),
+            payee_count: payee_count[0],
+            payee_receiving_pubkeys: [Pubkey::default(); MAX_PAYEES],
+            payee_shares_bps: [0; MAX_PAYEES],
+            dust_payee_index: dust_payee_index[0],
+            pays_in_lamports: match pays_in_lamports {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            prices_in_reference: match prices_in_reference {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            bond_amount: u64::from_le_bytes(*bond_amount),
+            rent_payer_pubkey: Pubkey::new_from_array(*rent_payer_pubkey),
+            highest_bidder_rent_payer_pubkey: Pubkey::new_from_array(
+                *highest_bidder_rent_payer_pubkey,
+            ),
+            runner_up_pubkey: Pubkey::new_from_array(*runner_up_pubkey),
+            runner_up_ft_temp_pubkey: Pubkey::new_from_array(*runner_up_ft_temp_pubkey),
+            runner_up_ft_returning_pubkey: Pubkey::new_from_array(*runner_up_ft_returning_pubkey),
+            runner_up_price: u64::from_le_bytes(*runner_up_price),
+            runner_up_referrer_pubkey: Pubkey::new_from_array(*runner_up_referrer_pubkey),
+            runner_up_referral_bps: u16::from_le_bytes(*runner_up_referral_bps),
+            runner_up_rent_payer_pubkey: Pubkey::new_from_array(*runner_up_rent_payer_pubkey),
+            second_chance_until: i64::from_le_bytes(*second_chance_until),
+            initial_price: u64::from_le_bytes(*initial_price),
+            house_pubkey: Pubkey::new_from_array(*house_pubkey),
+            min_increment_bps: u16::from_le_bytes(*min_increment_bps),
+            anti_snipe_window_sec: u32::from_le_bytes(*anti_snipe_window_sec),
+            anti_snipe_extension_sec: u32::from_le_bytes(*anti_snipe_extension_sec),
+            gate_pubkey: Pubkey::new_from_array(*gate_pubkey),
+            gate_amount: u64::from_le_bytes(*gate_amount),
+            bidder_allowlist_root: *bidder_allowlist_root,
+            duration_sec: u64::from_le_bytes(*duration_sec),
+            auto_relist_cycles: auto_relist_cycles[0],
+            auto_relist_price_drop_bps: u16::from_le_bytes(*auto_relist_price_drop_bps),
+            bids_in_vault: match bids_in_vault {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            fee_bps: u16::from_le_bytes(*fee_bps),
+            treasury_pubkey: Pubkey::new_from_array(*treasury_pubkey),
+            cancel_penalty_bps: u16::from_le_bytes(*cancel_penalty_bps),
+        };
+        for (pubkey, src) in auction
+            .payee_receiving_pubkeys
+            .iter_mut()
+            .zip(payee_receiving_pubkeys.chunks_exact(32))
+        {
+            *pubkey = Pubkey::try_from(src).map_err(|_| ProgramError::InvalidAccountData)?;
+        }
+        for (share_bps, src) in auction
+            .payee_shares_bps
+            .iter_mut()
+            .zip(payee_shares_bps.chunks_exact(2))
+        {
+            *share_bps = u16::from_le_bytes([src[0], src[1]]);
+        }
+
+        Ok(auction)
+    }
+}
+
+pub struct Raffle {
+    pub is_initialized: bool,
+    /// Wallet pubkey of exhibitor
+    pub exhibitor_pubkey: Pubkey,
+    /// Exhibiting NFT pubkey of exhibitor in escrow
+    pub exhibiting_nft_temp_pubkey: Pubkey,
+    /// FT pubkey of exhibitor to get token into
+    pub exhibitor_ft_receiving_pubkey: Pubkey,
+    /// FT pubkey in escrow collecting the ticket proceeds
+    pub proceeds_ft_temp_pubkey: Pubkey,
+    /// Price of a single ticket
+    pub ticket_price: u64,
+    /// Ticket sale end time
+    pub end_at: i64,
+    /// SHA-256 hash of the exhibitor's secret seed, revealed at draw
+    pub seed_hash: [u8; 32],
+    /// Number of tickets sold so far
+    pub tickets_sold: u64,
+    /// Number of ticket ranges stored after the raffle header
+    pub range_count: u32,
+    /// Wallet pubkey of the drawn winner, default until the draw
+    pub winner_pubkey: Pubkey,
+    /// Time the seed must be revealed by, after which buyers can get their tickets refunded
+    pub reveal_by: i64,
+    /// Slot whose hash is mixed into the draw, recorded by the exhibitor once the sale has ended, zero until then
+    pub draw_slot: u64,
+    /// Number of tickets refunded after a missed reveal
+    pub tickets_refunded: u64,
+    /// Protocol fee taken from the proceeds, in basis points, fixed when the raffle was exhibited
+    pub fee_bps: u16,
+    /// Wallet pubkey owning the FT accounts the protocol fee is paid to, fixed when the raffle was exhibited
+    pub treasury_pubkey: Pubkey,
+}
+
+impl Raffle {
+    /// Byte span of the ticket range at `index` in the raffle account.
+    pub fn range_span(index: usize) -> Range<usize> {
+        let start = Raffle::LEN + index * TicketRange::LEN;
+        start..start + TicketRange::LEN
+    }
+
+    /// Number of ticket ranges an account of `data_len` bytes can hold.
+    pub fn range_capacity(data_len: usize) -> usize {
+        data_len.saturating_sub(Raffle::LEN) / TicketRange::LEN
+    }
+
+    /// Unpacks the raffle header at the start of the raffle account data.
+    pub fn unpack_header(src: &[u8]) -> Result<Raffle, ProgramError> {
+        let src = src
+            .get(..Raffle::LEN)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        Raffle::unpack_unchecked(src)
+    }
+
+    /// Packs the raffle header into the start of the raffle account data.
+    pub fn pack_header(raffle: Raffle, dst: &mut [u8]) -> Result<(), ProgramError> {
+        let dst = dst
+            .get_mut(..Raffle::LEN)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        Raffle::pack(raffle, dst)
+    }
+}
+
+impl Sealed for Raffle {}
+
+impl IsInitialized for Raffle {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Raffle {
+    const LEN: usize = 279;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, Raffle::LEN];
+        let (
+            is_initialized_dst,
+            exhibitor_pubkey_dst,
+            exhibiting_nft_temp_pubkey_dst,
+            exhibitor_ft_receiving_pubkey_dst,
+            proceeds_ft_temp_pubkey_dst,
+            ticket_price_dst,
+            end_at_dst,
+            seed_hash_dst,
+            tickets_sold_dst,
+            range_count_dst,
+            winner_pubkey_dst,
+            reveal_by_dst,
+            draw_slot_dst,
+            tickets_refunded_dst,
+            fee_bps_dst,
+            treasury_pubkey_dst,
+        ) = mut_array_refs![dst, 1, 32, 32, 32, 32, 8, 8, 32, 8, 4, 32, 8, 8, 8, 2, 32];
+
+        let Raffle {
+            is_initialized,
+            exhibitor_pubkey,
+            exhibiting_nft_temp_pubkey,
+            exhibitor_ft_receiving_pubkey,
+            proceeds_ft_temp_pubkey,
+            ticket_price,
+            end_at,
+            seed_hash,
+            tickets_sold,
+            range_count,
+            winner_pubkey,
+            reveal_by,
+            draw_slot,
+            tickets_refunded,
+            fee_bps,
+            treasury_pubkey,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        exhibitor_pubkey_dst.copy_from_slice(exhibitor_pubkey.as_ref());
+        exhibiting_nft_temp_pubkey_dst.copy_from_slice(exhibiting_nft_temp_pubkey.as_ref());
+        exhibitor_ft_receiving_pubkey_dst.copy_from_slice(exhibitor_ft_receiving_pubkey.as_ref());
+        proceeds_ft_temp_pubkey_dst.copy_from_slice(proceeds_ft_temp_pubkey.as_ref());
+        *ticket_price_dst = ticket_price.to_le_bytes();
+        *end_at_dst = end_at.to_le_bytes();
+        seed_hash_dst.copy_from_slice(seed_hash);
+        *tickets_sold_dst = tickets_sold.to_le_bytes();
+        *range_count_dst = range_count.to_le_bytes();
+        winner_pubkey_dst.copy_from_slice(winner_pubkey.as_ref());
+        *reveal_by_dst = reveal_by.to_le_bytes();
+        *draw_slot_dst = draw_slot.to_le_bytes();
+        *tickets_refunded_dst = tickets_refunded.to_le_bytes();
+        *fee_bps_dst = fee_bps.to_le_bytes();
+        treasury_pubkey_dst.copy_from_slice(treasury_pubkey.as_ref());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Raffle::LEN];
+        let (
+            is_initialized,
+            exhibitor_pubkey,
+            exhibiting_nft_temp_pubkey,
+            exhibitor_ft_receiving_pubkey,
+            proceeds_ft_temp_pubkey,
+            ticket_price,
+            end_at,
+            seed_hash,
+            tickets_sold,
+            range_count,
+            winner_pubkey,
+            reveal_by,
+            draw_slot,
+            tickets_refunded,
+            fee_bps,
+            treasury_pubkey,
+        ) = array_refs![src, 1, 32, 32, 32, 32, 8, 8, 32, 8, 4, 32, 8, 8, 8, 2, 32];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(Raffle {
+            is_initialized,
+            exhibitor_pubkey: Pubkey::new_from_array(*exhibitor_pubkey),
+            exhibiting_nft_temp_pubkey: Pubkey::new_from_array(*exhibiting_nft_temp_pubkey),
+            exhibitor_ft_receiving_pubkey: Pubkey::new_from_array(*exhibitor_ft_receiving_pubkey),
+            proceeds_ft_temp_pubkey: Pubkey::new_from_array(*proceeds_ft_temp_pubkey),
+            ticket_price: u64::from_le_bytes(*ticket_price),
+            end_at: i64::from_le_bytes(*end_at),
+            seed_hash: *seed_hash,
+            tickets_sold: u64::from_le_bytes(*tickets_sold),
+            range_count: u32::from_le_bytes(*range_count),
+            winner_pubkey: Pubkey::new_from_array(*winner_pubkey),
+            reveal_by: i64::from_le_bytes(*reveal_by),
+            draw_slot: u64::from_le_bytes(*draw_slot),
+            tickets_refunded: u64::from_le_bytes(*tickets_refunded),
+            fee_bps: u16::from_le_bytes(*fee_bps),
+            treasury_pubkey: Pubkey::new_from_array(*treasury_pubkey),
+        })
+    }
+}
+
+/// Period after the end of a raffle's ticket sale the exhibitor has to reveal the seed in
+pub const RAFFLE_REVEAL_PERIOD_SEC: i64 = 24 * 60 * 60;
+
+/// A run of consecutive raffle tickets owned by one buyer.
+/// Ranges are stored back to back after the `Raffle` header, so the owner of a ticket
+/// is found by binary searching on `end`.
+pub struct TicketRange {
+    /// Wallet pubkey of the ticket buyer, default once the range is refunded
+    pub buyer_pubkey: Pubkey,
+    /// One past the last ticket number of this range
+    pub end: u64,
+}
+
+impl Sealed for TicketRange {}
+
+impl Pack for TicketRange {
+    const LEN: usize = 40;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, TicketRange::LEN];
+        let (buyer_pubkey_dst, end_dst) = mut_array_refs![dst, 32, 8];
+        buyer_pubkey_dst.copy_from_slice(self.buyer_pubkey.as_ref());
+        *end_dst = self.end.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, TicketRange::LEN];
+        let (buyer_pubkey, end) = array_refs![src, 32, 8];
+        Ok(TicketRange {
+            buyer_pubkey: Pubkey::new_from_array(*buyer_pubkey),
+            end: u64::from_le_bytes(*end),
+        })
+    }
+}
+
+/// Fixed-point representation of 1.0 used by the gradual Dutch auction curve parameters.
+pub const FIXED_POINT_ONE: u64 = 1_000_000_000_000;
+
+pub struct GradualDutchAuction {
+    pub is_initialized: bool,
+    /// Wallet pubkey of exhibitor
+    pub exhibitor_pubkey: Pubkey,
+    /// Temporary token account holding the escrowed series, or the series mint when minting on purchase
+    pub supply_pubkey: Pubkey,
+    /// Whether purchases are minted by the PDA instead of transferred from escrow
+    pub mints_on_purchase: bool,
+    /// FT pubkey of exhibitor to get token into
+    pub exhibitor_ft_receiving_pubkey: Pubkey,
+    /// Price of the first item at the start of the sale
+    pub initial_price: u64,
+    /// Price multiplier applied for every item sold, in `FIXED_POINT_ONE` units
+    pub price_scale: u64,
+    /// Price multiplier applied for every elapsed second, in `FIXED_POINT_ONE` units
+    pub decay_rate: u64,
+    /// Sale start time
+    pub start_at: i64,
+    /// Number of items sold so far
+    pub sold: u64,
+    /// Number of items available for the whole sale
+    pub max_supply: u64,
+    /// Protocol fee taken from every purchase, in basis points, fixed when the auction was exhibited
+    pub fee_bps: u16,
+    /// Wallet pubkey owning the FT accounts the protocol fee is paid to, fixed when the auction was exhibited
+    pub treasury_pubkey: Pubkey,
+}
+
+impl Sealed for GradualDutchAuction {}
+
+impl IsInitialized for GradualDutchAuction {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for GradualDutchAuction {
+    const LEN: usize = 180;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, GradualDutchAuction::LEN];
+        let (
+            is_initialized_dst,
+            exhibitor_pubkey_dst,
+            supply_pubkey_dst,
+            mints_on_purchase_dst,
+            exhibitor_ft_receiving_pubkey_dst,
+            initial_price_dst,
+            price_scale_dst,
+            decay_rate_dst,
+            start_at_dst,
+            sold_dst,
+            max_supply_dst,
+            fee_bps_dst,
+            treasury_pubkey_dst,
+        ) = mut_array_refs![dst, 1, 32, 32, 1, 32, 8, 8, 8, 8, 8, 8, 2, 32];
+
+        let GradualDutchAuction {
+            is_initialized,
+            exhibitor_pubkey,
+            supply_pubkey,
+            mints_on_purchase,
+            exhibitor_ft_receiving_pubkey,
+            initial_price,
+            price_scale,
+            decay_rate,
+            start_at,
+            sold,
+            max_supply,
+            fee_bps,
+            treasury_pubkey,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        exhibitor_pubkey_dst.copy_from_slice(exhibitor_pubkey.as_ref());
+        supply_pubkey_dst.copy_from_slice(supply_pubkey.as_ref());
+        mints_on_purchase_dst[0] = *mints_on_purchase as u8;
+        exhibitor_ft_receiving_pubkey_dst.copy_from_slice(exhibitor_ft_receiving_pubkey.as_ref());
+        *initial_price_dst = initial_price.to_le_bytes();
+        *price_scale_dst = price_scale.to_le_bytes();
+        *decay_rate_dst = decay_rate.to_le_bytes();
+        *start_at_dst = start_at.to_le_bytes();
+        *sold_dst = sold.to_le_bytes();
+        *max_supply_dst = max_supply.to_le_bytes();
+        *fee_bps_dst = fee_bps.to_le_bytes();
+        treasury_pubkey_dst.copy_from_slice(treasury_pubkey.as_ref());
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, GradualDutchAuction::LEN];
+        let (
+            is_initialized,
+            exhibitor_pubkey,
+            supply_pubkey,
+            mints_on_purchase,
+            exhibitor_ft_receiving_pubkey,
+            initial_price,
+            price_scale,
+            decay_rate,
+            start_at,
+            sold,
+            max_supply,
+            fee_bps,
+            treasury_pubkey,
+        ) = array_refs![src, 1, 32, 32, 1, 32, 8, 8, 8, 8, 8, 8, 2, 32];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let mints_on_purchase = match mints_on_purchase {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(GradualDutchAuction {
+            is_initialized,
+            exhibitor_pubkey: Pubkey::new_from_array(*exhibitor_pubkey),
+            supply_pubkey: Pubkey::new_from_array(*supply_pubkey),
+            mints_on_purchase,
+            exhibitor_ft_receiving_pubkey: Pubkey::new_from_array(*exhibitor_ft_receiving_pubkey),
+            initial_price: u64::from_le_bytes(*initial_price),
+            price_scale: u64::from_le_bytes(*price_scale),
+            decay_rate: u64::from_le_bytes(*decay_rate),
+            start_at: i64::from_le_bytes(*start_at),
+            sold: u64::from_le_bytes(*sold),
+            max_supply: u64::from_le_bytes(*max_supply),
+            fee_bps: u16::from_le_bytes(*fee_bps),
+            treasury_pubkey: Pubkey::new_from_array(*treasury_pubkey),
+        })
+    }
+}
+
+pub struct CallAuction {
+    pub is_initialized: bool,
+    /// Wallet pubkey of the person who opened the call auction
+    pub authority_pubkey: Pubkey,
+    /// Mint of the collection every ask must be a verified member of
+    pub collection_pubkey: Pubkey,
+    /// Mint of the FT bids are paid in
+    pub ft_mint_pubkey: Pubkey,
+    /// Length of an epoch window
+    pub epoch_duration: u64,
+    /// Current epoch number
+    pub epoch: u64,
+    /// End time of the current epoch window
+    pub epoch_end_at: i64,
+    /// Number of asks waiting to be cleared
+    pub open_asks: u32,
+    /// Number of bids waiting to be cleared
+    pub open_bids: u32,
+    /// Number of book entries stored after the call auction header, open or filled
+    pub entry_count: u32,
+}
+
+impl CallAuction {
+    /// Byte span of the book entry at `index` in the call auction account.
+    pub fn entry_span(index: usize) -> Range<usize> {
+        let start = CallAuction::LEN + index * BookEntry::LEN;
+        start..start + BookEntry::LEN
+    }
+
+    /// Number of book entries an account of `data_len` bytes can hold.
+    pub fn entry_capacity(data_len: usize) -> usize {
+        data_len.saturating_sub(CallAuction::LEN) / BookEntry::LEN
+    }
+
+    /// Unpacks the call auction header at the start of the call auction account data.
+    pub fn unpack_header(src: &[u8]) -> Result<CallAuction, ProgramError> {
+        let src = src
+            .get(..CallAuction::LEN)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        CallAuction::unpack_unchecked(src)
+    }
+
+    /// Packs the call auction header into the start of the call auction account data.
+    pub fn pack_header(call_auction: CallAuction, dst: &mut [u8]) -> Result<(), ProgramError> {
+        let dst = dst
+            .get_mut(..CallAuction::LEN)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        CallAuction::pack(call_auction, dst)
+    }
+}
+
+impl Sealed for CallAuction {}
+
+impl IsInitialized for CallAuction {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for CallAuction {
+    const LEN: usize = 133;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, CallAuction::LEN];
+        let (
+            is_initialized_dst,
+            authority_pubkey_dst,
+            collection_pubkey_dst,
+            ft_mint_pubkey_dst,
+            epoch_duration_dst,
+            epoch_dst,
+            epoch_end_at_dst,
+            open_asks_dst,
+            open_bids_dst,
+            entry_count_dst,
+        ) = mut_array_refs![dst, 1, 32, 32, 32, 8, 8, 8, 4, 4, 4];
+
+        let CallAuction {
+            is_initialized,
+            authority_pubkey,
+            collection_pubkey,
+            ft_mint_pubkey,
+            epoch_duration,
+            epoch,
+            epoch_end_at,
+            open_asks,
+            open_bids,
+            entry_count,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        authority_pubkey_dst.copy_from_slice(authority_pubkey.as_ref());
+        collection_pubkey_dst.copy_from_slice(collection_pubkey.as_ref());
+        ft_mint_pubkey_dst.copy_from_slice(ft_mint_pubkey.as_ref());
+        *epoch_duration_dst = epoch_duration.to_le_bytes();
+        *epoch_dst = epoch.to_le_bytes();
+        *epoch_end_at_dst = epoch_end_at.to_le_bytes();
+        *open_asks_dst = open_asks.to_le_bytes();
+        *open_bids_dst = open_bids.to_le_bytes();
+        *entry_count_dst = entry_count.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, CallAuction::LEN];
+        let (
+            is_initialized,
+            authority_pubkey,
+            collection_pubkey,
+            ft_mint_pubkey,
+            epoch_duration,
+            epoch,
+            epoch_end_at,
+            open_asks,
+            open_bids,
+            entry_count,
+        ) = array_refs![src, 1, 32, 32, 32, 8, 8, 8, 4, 4, 4];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(CallAuction {
+            is_initialized,
+            authority_pubkey: Pubkey::new_from_array(*authority_pubkey),
+            collection_pubkey: Pubkey::new_from_array(*collection_pubkey),
+            ft_mint_pubkey: Pubkey::new_from_array(*ft_mint_pubkey),
+            epoch_duration: u64::from_le_bytes(*epoch_duration),
+            epoch: u64::from_le_bytes(*epoch),
+            epoch_end_at: i64::from_le_bytes(*epoch_end_at),
+            open_asks: u32::from_le_bytes(*open_asks),
+            open_bids: u32::from_le_bytes(*open_bids),
+            entry_count: u32::from_le_bytes(*entry_count),
+        })
+    }
+}
+
+/// An order in the book of a call auction.
+/// Entries are stored back to back after the `CallAuction` header, so an epoch can be cleared
+/// from the call auction account alone. Filled entries stay in the book until the fill is settled.
+pub struct BookEntry {
+    /// Order account of the entry
+    pub order_pubkey: Pubkey,
+    /// Whether the order sells or buys
+    pub side: OrderSide,
+    /// Lowest price of an ask or highest price of a bid
+    pub limit_price: u64,
+    /// Order account the order was matched with, default while the order is open
+    pub matched_order_pubkey: Pubkey,
+    /// Uniform price the order was filled at
+    pub fill_price: u64,
+    /// Time the order was filled at
+    pub filled_at: i64,
+}
+
+impl Sealed for BookEntry {}
+
+impl Pack for BookEntry {
+    const LEN: usize = 89;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, BookEntry::LEN];
+        let (
+            order_pubkey_dst,
+            side_dst,
+            limit_price_dst,
+            matched_order_pubkey_dst,
+            fill_price_dst,
+            filled_at_dst,
+        ) = mut_array_refs![dst, 32, 1, 8, 32, 8, 8];
+        order_pubkey_dst.copy_from_slice(self.order_pubkey.as_ref());
+        side_dst[0] = self.side as u8;
+        *limit_price_dst = self.limit_price.to_le_bytes();
+        matched_order_pubkey_dst.copy_from_slice(self.matched_order_pubkey.as_ref());
+        *fill_price_dst = self.fill_price.to_le_bytes();
+        *filled_at_dst = self.filled_at.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, BookEntry::LEN];
+        let (order_pubkey, side, limit_price, matched_order_pubkey, fill_price, filled_at) =
+            array_refs![src, 32, 1, 8, 32, 8, 8];
+        let side = match side {
+            [0] => OrderSide::Ask,
+            [1] => OrderSide::Bid,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        Ok(BookEntry {
+            order_pubkey: Pubkey::new_from_array(*order_pubkey),
+            side,
+            limit_price: u64::from_le_bytes(*limit_price),
+            matched_order_pubkey: Pubkey::new_from_array(*matched_order_pubkey),
+            fill_price: u64::from_le_bytes(*fill_price),
+            filled_at: i64::from_le_bytes(*filled_at),
         })
     }
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrderSide {
+    /// An NFT escrowed for sale
+    Ask,
+    /// FT escrowed to buy an NFT
+    Bid,
+}
+
+pub struct CallOrder {
+    pub is_initialized: bool,
+    /// Call auction the order was posted to
+    pub call_auction_pubkey: Pubkey,
+    /// Whether the order sells or buys
+    pub side: OrderSide,
+    /// Wallet pubkey of the person who posted the order
+    pub owner_pubkey: Pubkey,
+    /// Temporary account in escrow holding the NFT of an ask or the FT of a bid
+    pub escrow_temp_pubkey: Pubkey,
+    /// FT account receiving the proceeds of an ask, default for a bid, whose buyer names the NFT account when settling
+    pub receiving_pubkey: Pubkey,
+    /// Account to return the escrowed tokens to when the order is cancelled or partly spent
+    pub returning_pubkey: Pubkey,
+    /// Lowest price of an ask or highest price of a bid
+    pub limit_price: u64,
+}
+
+impl Sealed for CallOrder {}
+
+impl IsInitialized for CallOrder {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for CallOrder {
+    const LEN: usize = 170;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, CallOrder::LEN];
+        let (
+            is_initialized_dst,
+            call_auction_pubkey_dst,
+            side_dst,
+            owner_pubkey_dst,
+            escrow_temp_pubkey_dst,
+            receiving_pubkey_dst,
+            returning_pubkey_dst,
+            limit_price_dst,
+        ) = mut_array_refs![dst, 1, 32, 1, 32, 32, 32, 32, 8];
+
+        let CallOrder {
+            is_initialized,
+            call_auction_pubkey,
+            side,
+            owner_pubkey,
+            escrow_temp_pubkey,
+            receiving_pubkey,
+            returning_pubkey,
+            limit_price,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        call_auction_pubkey_dst.copy_from_slice(call_auction_pubkey.as_ref());
+        side_dst[0] = *side as u8;
+        owner_pubkey_dst.copy_from_slice(owner_pubkey.as_ref());
+        escrow_temp_pubkey_dst.copy_from_slice(escrow_temp_pubkey.as_ref());
+        receiving_pubkey_dst.copy_from_slice(receiving_pubkey.as_ref());
+        returning_pubkey_dst.copy_from_slice(returning_pubkey.as_ref());
+        *limit_price_dst = limit_price.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, CallOrder::LEN];
+        let (
+            is_initialized,
+            call_auction_pubkey,
+            side,
+            owner_pubkey,
+            escrow_temp_pubkey,
+            receiving_pubkey,
+            returning_pubkey,
+            limit_price,
+        ) = array_refs![src, 1, 32, 1, 32, 32, 32, 32, 8];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let side = match side {
+            [0] => OrderSide::Ask,
+            [1] => OrderSide::Bid,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(CallOrder {
+            is_initialized,
+            call_auction_pubkey: Pubkey::new_from_array(*call_auction_pubkey),
+            side,
+            owner_pubkey: Pubkey::new_from_array(*owner_pubkey),
+            escrow_temp_pubkey: Pubkey::new_from_array(*escrow_temp_pubkey),
+            receiving_pubkey: Pubkey::new_from_array(*receiving_pubkey),
+            returning_pubkey: Pubkey::new_from_array(*returning_pubkey),
+            limit_price: u64::from_le_bytes(*limit_price),
+        })
+    }
+}
+
+/// Maximum number of NFTs a single barter offer can bundle
+pub const MAX_OFFERED_NFTS: usize = 4;
+
+/// Time the exhibitor has after the end of a barter auction to accept an offer before
+/// the offers can be withdrawn again
+pub const OFFER_SELECTION_PERIOD_SEC: i64 = 3 * 24 * 60 * 60;
+
+pub struct Offer {
+    pub is_initialized: bool,
+    /// Barter auction the offer was made to
+    pub auction_pubkey: Pubkey,
+    /// Wallet pubkey of the bidder
+    pub bidder_pubkey: Pubkey,
+    /// NFT pubkey of the bidder to receive the exhibited NFT should the offer be accepted
+    pub bidder_nft_receiving_pubkey: Pubkey,
+    /// FT pubkey of the bidder in escrow, default when the offer has no FT
+    pub ft_temp_pubkey: Pubkey,
+    /// Amount of FT added to the offer
+    pub ft_amount: u64,
+    /// FT pubkey of the bidder to return to when the offer is withdrawn
+    pub ft_returning_pubkey: Pubkey,
+    /// Number of NFTs in the offer
+    pub nft_count: u8,
+    /// NFT pubkeys of the bidder in escrow
+    pub nft_temp_pubkeys: [Pubkey; MAX_OFFERED_NFTS],
+    /// NFT pubkeys of the bidder to return to when the offer is withdrawn
+    pub nft_returning_pubkeys: [Pubkey; MAX_OFFERED_NFTS],
+}
+
+impl Sealed for Offer {}
+
+impl IsInitialized for Offer {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Offer {
+    const LEN: usize = 426;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, Offer::LEN];
+        let (
+            is_initialized_dst,
+            auction_pubkey_dst,
+            bidder_pubkey_dst,
+            bidder_nft_receiving_pubkey_dst,
+            ft_temp_pubkey_dst,
+            ft_amount_dst,
+            ft_returning_pubkey_dst,
+            nft_count_dst,
+            nft_temp_pubkeys_dst,
+            nft_returning_pubkeys_dst,
+        ) = mut_array_refs![dst, 1, 32, 32, 32, 32, 8, 32, 1, 128, 128];
+
+        let Offer {
+            is_initialized,
+            auction_pubkey,
+            bidder_pubkey,
+            bidder_nft_receiving_pubkey,
+            ft_temp_pubkey,
+            ft_amount,
+            ft_returning_pubkey,
+            nft_count,
+            nft_temp_pubkeys,
+            nft_returning_pubkeys,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        auction_pubkey_dst.copy_from_slice(auction_pubkey.as_ref());
+        bidder_pubkey_dst.copy_from_slice(bidder_pubkey.as_ref());
+        bidder_nft_receiving_pubkey_dst.copy_from_slice(bidder_nft_receiving_pubkey.as_ref());
+        ft_temp_pubkey_dst.copy_from_slice(ft_temp_pubkey.as_ref());
+        *ft_amount_dst = ft_amount.to_le_bytes();
+        ft_returning_pubkey_dst.copy_from_slice(ft_returning_pubkey.as_ref());
+        nft_count_dst[0] = *nft_count;
+        for (dst, pubkey) in nft_temp_pubkeys_dst
+            .chunks_exact_mut(32)
+            .zip(nft_temp_pubkeys.iter())
+        {
+            dst.copy_from_slice(pubkey.as_ref());
+        }
+        for (dst, pubkey) in nft_returning_pubkeys_dst
+            .chunks_exact_mut(32)
+            .zip(nft_returning_pubkeys.iter())
+        {
+            dst.copy_from_slice(pubkey.as_ref());
+        }
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Offer::LEN];
+        let (
+            is_initialized,
+            auction_pubkey,
+            bidder_pubkey,
+            bidder_nft_receiving_pubkey,
+            ft_temp_pubkey,
+            ft_amount,
+            ft_returning_pubkey,
+            nft_count,
+            nft_temp_pubkeys,
+            nft_returning_pubkeys,
+        ) = array_refs![src, 1, 32, 32, 32, 32, 8, 32, 1, 128, 128];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        if nft_count[0] as usize > MAX_OFFERED_NFTS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let mut offer = Offer {
+            is_initialized,
+            auction_pubkey: Pubkey::new_from_array(*auction_pubkey),
+            bidder_pubkey: Pubkey::new_from_array(*bidder_pubkey),
+            bidder_nft_receiving_pubkey: Pubkey::new_from_array(*bidder_nft_receiving_pubkey),
+            ft_temp_pubkey: Pubkey::new_from_array(*ft_temp_pubkey),
+            ft_amount: u64::from_le_bytes(*ft_amount),
+            ft_returning_pubkey: Pubkey::new_from_array(*ft_returning_pubkey),
+            nft_count: nft_count[0],
+            nft_temp_pubkeys: [Pubkey::default(); MAX_OFFERED_NFTS],
+            nft_returning_pubkeys: [Pubkey::default(); MAX_OFFERED_NFTS],
+        };
+        for (pubkey, src) in offer
+            .nft_temp_pubkeys
+            .iter_mut()
+            .zip(nft_temp_pubkeys.chunks_exact(32))
+        {
+            *pubkey = Pubkey::try_from(src).map_err(|_| ProgramError::InvalidAccountData)?;
+        }
+        for (pubkey, src) in offer
+            .nft_returning_pubkeys
+            .iter_mut()
+            .zip(nft_returning_pubkeys.chunks_exact(32))
+        {
+            *pubkey = Pubkey::try_from(src).map_err(|_| ProgramError::InvalidAccountData)?;
+        }
+        Ok(offer)
+    }
+}
+
+/// Denominator of the basis point fees and shares
+pub const BASIS_POINTS: u64 = 10_000;
+
+/// Highest protocol or house fee, in basis points
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+/// Program wide settings, stored in the PDA derived from `b"config"`
+pub struct Config {
+    pub is_initialized: bool,
+    /// Wallet pubkey allowed to update the config
+    pub admin_pubkey: Pubkey,
+    /// Wallet pubkey owning the FT accounts the protocol fee is paid to
+    pub treasury_pubkey: Pubkey,
+    /// Protocol fee taken from the proceeds of every sale, in basis points
+    pub fee_bps: u16,
+    /// Share of the proceeds paid to the referrer of a winning bid, in basis points
+    pub referral_bps: u16,
+    /// Wallet pubkey allowed to update the price feeds
+    pub price_authority_pubkey: Pubkey,
+    /// Penalty the exhibitor pays the highest bidder to cancel an auction after bids, in basis points
+    /// of the highest bid
+    pub cancel_penalty_bps: u16,
+    /// Penalty a bidder retracting their bid pays the exhibitor, in basis points of the retracted bid
+    pub retraction_penalty_bps: u16,
+    /// Whether new auctions and bids are rejected, while cancellations, refunds and settlements keep working
+    pub paused: bool,
+    /// Shortest auction duration, in seconds
+    pub min_duration_sec: u64,
+    /// Longest auction duration, in seconds, zero when unbounded
+    pub max_duration_sec: u64,
+}
+
+impl Sealed for Config {}
+
+impl IsInitialized for Config {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Config {
+    const LEN: usize = 122;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, Config::LEN];
+        let (
+            is_initialized_dst,
+            admin_pubkey_dst,
+            treasury_pubkey_dst,
+            fee_bps_dst,
+            referral_bps_dst,
+            price_authority_pubkey_dst,
+            cancel_penalty_bps_dst,
+            retraction_penalty_bps_dst,
+            paused_dst,
+            min_duration_sec_dst,
+            max_duration_sec_dst,
+        ) = mut_array_refs![dst, 1, 32, 32, 2, 2, 32, 2, 2, 1, 8, 8];
+
+        let Config {
+            is_initialized,
+            admin_pubkey,
+            treasury_pubkey,
+            fee_bps,
+            referral_bps,
+            price_authority_pubkey,
+            cancel_penalty_bps,
+            retraction_penalty_bps,
+            paused,
+            min_duration_sec,
+            max_duration_sec,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        admin_pubkey_dst.copy_from_slice(admin_pubkey.as_ref());
+        treasury_pubkey_dst.copy_from_slice(treasury_pubkey.as_ref());
+        *fee_bps_dst = fee_bps.to_le_bytes();
+        *referral_bps_dst = referral_bps.to_le_bytes();
+        price_authority_pubkey_dst.copy_from_slice(price_authority_pubkey.as_ref());
+        *cancel_penalty_bps_dst = cancel_penalty_bps.to_le_bytes();
+        *retraction_penalty_bps_dst = retraction_penalty_bps.to_le_bytes();
+        paused_dst[0] = *paused as u8;
+        *min_duration_sec_dst = min_duration_sec.to_le_bytes();
+        *max_duration_sec_dst = max_duration_sec.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Config::LEN];
+        let (
+            is_initialized,
+            admin_pubkey,
+            treasury_pubkey,
+            fee_bps,
+            referral_bps,
+            price_authority_pubkey,
+            cancel_penalty_bps,
+            retraction_penalty_bps,
+            paused,
+            min_duration_sec,
+            max_duration_sec,
+        ) = array_refs![src, 1, 32, 32, 2, 2, 32, 2, 2, 1, 8, 8];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(Config {
+            is_initialized,
+            admin_pubkey: Pubkey::new_from_array(*admin_pubkey),
+            treasury_pubkey: Pubkey::new_from_array(*treasury_pubkey),
+            fee_bps: u16::from_le_bytes(*fee_bps),
+            referral_bps: u16::from_le_bytes(*referral_bps),
+            price_authority_pubkey: Pubkey::new_from_array(*price_authority_pubkey),
+            cancel_penalty_bps: u16::from_le_bytes(*cancel_penalty_bps),
+            retraction_penalty_bps: u16::from_le_bytes(*retraction_penalty_bps),
+            paused: match paused {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            min_duration_sec: u64::from_le_bytes(*min_duration_sec),
+            max_duration_sec: u64::from_le_bytes(*max_duration_sec),
+        })
+    }
+}
+
+/// Maximum number of mints an auction house can restrict payments to
+pub const MAX_HOUSE_MINTS: usize = 4;
+
+/// Policy shared by the auctions exhibited in a house, letting several storefronts run on one deployment
+pub struct AuctionHouse {
+    pub is_initialized: bool,
+    /// Wallet pubkey allowed to update the house
+    pub authority_pubkey: Pubkey,
+    /// Wallet pubkey owning the FT accounts the fee of the house is paid to, in place of the protocol treasury
+    pub treasury_pubkey: Pubkey,
+    /// Fee taken from the proceeds of every sale in the house, in place of the protocol fee, in basis points
+    pub fee_bps: u16,
+    /// Number of mints auctions of the house may be paid in, zero when any mint is allowed
+    pub payment_mint_count: u8,
+    /// Mints auctions of the house may be paid in, the native mint standing for lamports
+    pub payment_mints: [Pubkey; MAX_HOUSE_MINTS],
+    /// Shortest auction the house accepts, in seconds
+    pub min_duration_sec: u64,
+    /// Longest auction the house accepts, in seconds, zero when unbounded
+    pub max_duration_sec: u64,
+    /// Minimum raise over the highest bid of English auctions, in basis points
+    pub min_increment_bps: u16,
+    /// Bids placed this many seconds before the end extend the auction, zero to disable
+    pub anti_snipe_window_sec: u32,
+    /// Seconds left in the auction after a bid placed within the anti-snipe window
+    pub anti_snipe_extension_sec: u32,
+    /// Merkle root of the wallets allowed to exhibit in the house, zeroed when anyone may exhibit
+    pub exhibitor_allowlist_root: [u8; 32],
+}
+
+impl AuctionHouse {
+    /// Whether auctions of the house may be paid in `mint`.
+    pub fn allows_mint(&self, mint: &Pubkey) -> bool {
+        self.payment_mint_count == 0
+            || self.payment_mints[..self.payment_mint_count as usize].contains(mint)
+    }
+}
+
+impl Sealed for AuctionHouse {}
+
+impl IsInitialized for AuctionHouse {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for AuctionHouse {
+    const LEN: usize = 254;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, AuctionHouse::LEN];
+        let (
+            is_initialized_dst,
+            authority_pubkey_dst,
+            treasury_pubkey_dst,
+            fee_bps_dst,
+            payment_mint_count_dst,
+            payment_mints_dst,
+            min_duration_sec_dst,
+            max_duration_sec_dst,
+            min_increment_bps_dst,
+            anti_snipe_window_sec_dst,
+            anti_snipe_extension_sec_dst,
+            exhibitor_allowlist_root_dst,
+        ) = mut_array_refs![dst, 1, 32, 32, 2, 1, 128, 8, 8, 2, 4, 4, 32];
+
+        let AuctionHouse {
+            is_initialized,
+            authority_pubkey,
+            treasury_pubkey,
+            fee_bps,
+            payment_mint_count,
+            payment_mints,
+            min_duration_sec,
+            max_duration_sec,
+            min_increment_bps,
+            anti_snipe_window_sec,
+            anti_snipe_extension_sec,
+            exhibitor_allowlist_root,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        authority_pubkey_dst.copy_from_slice(authority_pubkey.as_ref());
+        treasury_pubkey_dst.copy_from_slice(treasury_pubkey.as_ref());
+        *fee_bps_dst = fee_bps.to_le_bytes();
+        payment_mint_count_dst[0] = *payment_mint_count;
+        for (dst, pubkey) in payment_mints_dst
+            .chunks_exact_mut(32)
+            .zip(payment_mints.iter())
+        {
+            dst.copy_from_slice(pubkey.as_ref());
+        }
+        *min_duration_sec_dst = min_duration_sec.to_le_bytes();
+        *max_duration_sec_dst = max_duration_sec.to_le_bytes();
+        *min_increment_bps_dst = min_increment_bps.to_le_bytes();
+        *anti_snipe_window_sec_dst = anti_snipe_window_sec.to_le_bytes();
+        *anti_snipe_extension_sec_dst = anti_snipe_extension_sec.to_le_bytes();
+        *exhibitor_allowlist_root_dst = *exhibitor_allowlist_root;
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, AuctionHouse::LEN];
+        let (
+            is_initialized,
+            authority_pubkey,
+            treasury_pubkey,
+            fee_bps,
+            payment_mint_count,
+            payment_mints,
+            min_duration_sec,
+            max_duration_sec,
+            min_increment_bps,
+            anti_snipe_window_sec,
+            anti_snipe_extension_sec,
+            exhibitor_allowlist_root,
+        ) = array_refs![src, 1, 32, 32, 2, 1, 128, 8, 8, 2, 4, 4, 32];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        let mut house = AuctionHouse {
+            is_initialized,
+            authority_pubkey: Pubkey::new_from_array(*authority_pubkey),
+            treasury_pubkey: Pubkey::new_from_array(*treasury_pubkey),
+            fee_bps: u16::from_le_bytes(*fee_bps),
+            payment_mint_count: payment_mint_count[0],
+            payment_mints: [Pubkey::default(); MAX_HOUSE_MINTS],
+            min_duration_sec: u64::from_le_bytes(*min_duration_sec),
+            max_duration_sec: u64::from_le_bytes(*max_duration_sec),
+            min_increment_bps: u16::from_le_bytes(*min_increment_bps),
+            anti_snipe_window_sec: u32::from_le_bytes(*anti_snipe_window_sec),
+            anti_snipe_extension_sec: u32::from_le_bytes(*anti_snipe_extension_sec),
+            exhibitor_allowlist_root: *exhibitor_allowlist_root,
+        };
+        for (pubkey, src) in house
+            .payment_mints
+            .iter_mut()
+            .zip(payment_mints.chunks_exact(32))
+        {
+            *pubkey = Pubkey::try_from(src).map_err(|_| ProgramError::InvalidAccountData)?;
+        }
+
+        Ok(house)
+    }
+}
+
+/// Sorted wallets of the denylist starting with the same byte, stored in the PDA derived from `b"denylist"` and that byte
+/// after the page header, the page growing by a wallet every time one is added
+pub struct DenylistPage {
+    pub is_initialized: bool,
+    /// Number of denied wallets in the page
+    pub count: u32,
+}
+
+impl DenylistPage {
+    /// Byte span of the wallet at `index` in the denylist page account.
+    pub fn wallet_span(index: usize) -> Range<usize> {
+        let start = DenylistPage::LEN + index * 32;
+        start..start + 32
+    }
+
+    /// Unpacks the page header at the start of the denylist page account data.
+    pub fn unpack_header(src: &[u8]) -> Result<DenylistPage, ProgramError> {
+        let src = src
+            .get(..DenylistPage::LEN)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        DenylistPage::unpack_unchecked(src)
+    }
+
+    /// Packs the page header into the start of the denylist page account data.
+    pub fn pack_header(page: DenylistPage, dst: &mut [u8]) -> Result<(), ProgramError> {
+        let dst = dst
+            .get_mut(..DenylistPage::LEN)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        DenylistPage::pack(page, dst)
+    }
+
+    /// Binary searches the wallets of the page held in `data` for `wallet`, returning its index if found,
+    /// or the index it would be inserted at.
+    fn search(&self, data: &[u8], wallet: &Pubkey) -> Result<Result<usize, usize>, ProgramError> {
+        if data.len() < DenylistPage::wallet_span(self.count as usize).start {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (mut low, mut high) = (0, self.count as usize);
+        while low < high {
+            let mid = (low + high) / 2;
+            match data[DenylistPage::wallet_span(mid)].cmp(wallet.as_ref()) {
+                Ordering::Less => low = mid + 1,
+                Ordering::Greater => high = mid,
+                Ordering::Equal => return Ok(Ok(mid)),
+            }
+        }
+        Ok(Err(low))
+    }
+
+    /// Whether `wallet` is denied by the page held in `data`.
+    pub fn contains(&self, data: &[u8], wallet: &Pubkey) -> Result<bool, ProgramError> {
+        Ok(self.search(data, wallet)?.is_ok())
+    }
+
+    /// Adds `wallet` in order to the page held in `data`, which must have room for one more wallet,
+    /// returning whether it wasn't denied yet.
+    pub fn insert(&mut self, data: &mut [u8], wallet: &Pubkey) -> Result<bool, ProgramError> {
+        let index = match self.search(data, wallet)? {
+            Ok(_) => return Ok(false),
+            Err(index) => index,
+        };
+        let count = self.count as usize;
+        if data.len() < DenylistPage::wallet_span(count).end {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        data.copy_within(
+            DenylistPage::wallet_span(index).start..DenylistPage::wallet_span(count).start,
+            DenylistPage::wallet_span(index + 1).start,
+        );
+        data[DenylistPage::wallet_span(index)].copy_from_slice(wallet.as_ref());
+        self.count += 1;
+        Ok(true)
+    }
+
+    /// Removes `wallet` from the page held in `data`, returning whether it was denied.
+    pub fn remove(&mut self, data: &mut [u8], wallet: &Pubkey) -> Result<bool, ProgramError> {
+        let index = match self.search(data, wallet)? {
+            Ok(index) => index,
+            Err(_) => return Ok(false),
+        };
+        let count = self.count as usize;
+        data.copy_within(
+            DenylistPage::wallet_span(index + 1).start..DenylistPage::wallet_span(count).start,
+            DenylistPage::wallet_span(index).start,
+        );
+        data[DenylistPage::wallet_span(count - 1)].fill(0);
+        self.count -= 1;
+        Ok(true)
+    }
+}
+
+impl Sealed for DenylistPage {}
+
+impl IsInitialized for DenylistPage {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for DenylistPage {
+    const LEN: usize = 5;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, DenylistPage::LEN];
+        let (is_initialized_dst, count_dst) = mut_array_refs![dst, 1, 4];
+
+        let DenylistPage {
+            is_initialized,
+            count,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        *count_dst = count.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, DenylistPage::LEN];
+        let (is_initialized, count) = array_refs![src, 1, 4];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(DenylistPage {
+            is_initialized,
+            count: u32::from_le_bytes(*count),
+        })
+    }
+}
+
+/// Age after which a price feed is considered stale
+pub const MAX_PRICE_AGE_SEC: i64 = 5 * 60;
+
+/// Price of a mint in reference units, stored in the PDA derived from `b"price_feed"` and the mint
+pub struct PriceFeed {
+    pub is_initialized: bool,
+    /// Mint the price is quoted for
+    pub mint_pubkey: Pubkey,
+    /// Reference units per base unit of the mint, in `FIXED_POINT_ONE` units
+    pub price: u64,
+    /// Time of the last price update
+    pub updated_at: i64,
+}
+
+impl Sealed for PriceFeed {}
+
+impl IsInitialized for PriceFeed {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for PriceFeed {
+    const LEN: usize = 49;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, PriceFeed::LEN];
+        let (is_initialized_dst, mint_pubkey_dst, price_dst, updated_at_dst) =
+            mut_array_refs![dst, 1, 32, 8, 8];
+
+        let PriceFeed {
+            is_initialized,
+            mint_pubkey,
+            price,
+            updated_at,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        mint_pubkey_dst.copy_from_slice(mint_pubkey.as_ref());
+        *price_dst = price.to_le_bytes();
+        *updated_at_dst = updated_at.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, PriceFeed::LEN];
+        let (is_initialized, mint_pubkey, price, updated_at) = array_refs![src, 1, 32, 8, 8];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(PriceFeed {
+            is_initialized,
+            mint_pubkey: Pubkey::new_from_array(*mint_pubkey),
+            price: u64::from_le_bytes(*price),
+            updated_at: i64::from_le_bytes(*updated_at),
+        })
+    }
+}
+
+/// Period after the end of a bonded auction the winner has to settle in,
+/// after which the exhibitor can take the winner's bond and reclaim the NFT
+pub const SETTLEMENT_GRACE_PERIOD_SEC: i64 = 3 * 24 * 60 * 60;
+
+/// Period the runner-up offered a second chance has to settle in
+pub const SECOND_CHANCE_WINDOW_SEC: i64 = 24 * 60 * 60;
+
+/// Final period of an auction in which bids can no longer be retracted
+pub const RETRACTION_CUTOFF_SEC: i64 = 60 * 60;
+
+/// Bond of a bidder, stored in the PDA derived from `b"bond"`, the escrow account and the bidder
+/// The bonded lamports are held by the account on top of its rent
+pub struct Bond {
+    pub is_initialized: bool,
+    /// Escrow pubkey of the auction the bond is posted for
+    pub auction_pubkey: Pubkey,
+    /// Wallet pubkey of the bidder
+    pub bidder_pubkey: Pubkey,
+    /// Bonded lamports
+    pub amount: u64,
+}
+
+impl Sealed for Bond {}
+
+impl IsInitialized for Bond {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+impl Pack for Bond {
+    const LEN: usize = 73;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, Bond::LEN];
+        let (is_initialized_dst, auction_pubkey_dst, bidder_pubkey_dst, amount_dst) =
+            mut_array_refs![dst, 1, 32, 32, 8];
+
+        let Bond {
+            is_initialized,
+            auction_pubkey,
+            bidder_pubkey,
+            amount,
+        } = self;
+
+        is_initialized_dst[0] = *is_initialized as u8;
+        auction_pubkey_dst.copy_from_slice(auction_pubkey.as_ref());
+        bidder_pubkey_dst.copy_from_slice(bidder_pubkey.as_ref());
+        *amount_dst = amount.to_le_bytes();
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, Bond::LEN];
+        let (is_initialized, auction_pubkey, bidder_pubkey, amount) =
+            array_refs![src, 1, 32, 32, 8];
+        let is_initialized = match is_initialized {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(Bond {
+            is_initialized,
+            auction_pubkey: Pubkey::new_from_array(*auction_pubkey),
+            bidder_pubkey: Pubkey::new_from_array(*bidder_pubkey),
+            amount: u64::from_le_bytes(*amount),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wallet(first_byte: u8, last_byte: u8) -> Pubkey {
+        let mut bytes = [first_byte; 32];
+        bytes[31] = last_byte;
+        Pubkey::new_from_array(bytes)
+    }
+
+    fn auction() -> Auction {
+        let mut auction = Auction::unpack_unchecked(&[0; Auction::LEN]).unwrap();
+        auction.is_initialized = true;
+        auction.exhibitor_pubkey = wallet(1, 1);
+        auction.price = 1_000;
+        auction.end_at = 10_000;
+        auction.highest_bidder_pubkey = wallet(2, 2);
+        auction.kind = AuctionKind::Barter;
+        auction.payee_count = 2;
+        auction.payee_receiving_pubkeys[1] = wallet(3, 3);
+        auction.payee_shares_bps = [6_000, 4_000, 0, 0, 0];
+        auction.dust_payee_index = 1;
+        auction.runner_up_price = 900;
+        auction.second_chance_until = -1;
+        auction.anti_snipe_window_sec = 300;
+        auction.anti_snipe_extension_sec = 120;
+        auction.bidder_allowlist_root = [7; 32];
+        auction.duration_sec = 86_400;
+        auction.auto_relist_cycles = 3;
+        auction.bids_in_vault = true;
+        auction.fee_bps = 250;
+        auction.treasury_pubkey = wallet(4, 4);
+        auction.cancel_penalty_bps = 500;
+        auction
+    }
+
+    #[test]
+    fn auction_round_trips() {
+        let mut packed = [0; Auction::LEN];
+        Auction::pack(auction(), &mut packed).unwrap();
+        let unpacked = Auction::unpack(&packed).unwrap();
+
+        assert_eq!(unpacked.exhibitor_pubkey, wallet(1, 1));
+        assert_eq!(unpacked.end_at, 10_000);
+        assert_eq!(unpacked.kind, AuctionKind::Barter);
+        assert_eq!(unpacked.payee_receiving_pubkeys[1], wallet(3, 3));
+        assert_eq!(unpacked.payee_shares_bps, [6_000, 4_000, 0, 0, 0]);
+        assert_eq!(unpacked.second_chance_until, -1);
+        assert_eq!(unpacked.bidder_allowlist_root, [7; 32]);
+        assert_eq!(unpacked.treasury_pubkey, wallet(4, 4));
+        assert_eq!(unpacked.cancel_penalty_bps, 500);
+
+        let mut repacked = [0; Auction::LEN];
+        Auction::pack(unpacked, &mut repacked).unwrap();
+        assert_eq!(packed, repacked);
+    }
+
+    #[test]
+    fn auction_rejects_invalid_initialized_flag() {
+        let mut packed = [0; Auction::LEN];
+        Auction::pack(auction(), &mut packed).unwrap();
+        packed[0] = 2;
+        assert_eq!(
+            Auction::unpack_unchecked(&packed).err(),
+            Some(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn legacy_auction_unpacks_as_english_auction_in_ft() {
+        let mut legacy = [0; LEGACY_AUCTION_LEN];
+        legacy[0] = 1;
+        legacy[1..33].copy_from_slice(wallet(1, 1).as_ref());
+        legacy[97..105].copy_from_slice(&1_000u64.to_le_bytes());
+        legacy[105..113].copy_from_slice(&10_000i64.to_le_bytes());
+        let auction = Auction::unpack_legacy(&legacy).unwrap();
+
+        assert!(auction.is_initialized);
+        assert_eq!(auction.exhibitor_pubkey, wallet(1, 1));
+        assert_eq!(auction.price, 1_000);
+        assert_eq!(auction.end_at, 10_000);
+        assert_eq!(auction.kind, AuctionKind::English);
+        assert!(!auction.pays_in_lamports);
+        assert!(!auction.holds_ft_in_vault());
+
+        assert!(Auction::unpack_legacy(&legacy[1..]).is_err());
+        legacy[0] = 0;
+        assert!(Auction::unpack_legacy(&legacy).is_err());
+    }
+
+    fn denylist_page(capacity: usize) -> (DenylistPage, Vec<u8>) {
+        let mut data = vec![0; DenylistPage::wallet_span(capacity).start];
+        let page = DenylistPage {
+            is_initialized: true,
+            count: 0,
+        };
+        DenylistPage::pack_header(page, &mut data).unwrap();
+        (DenylistPage::unpack_header(&data).unwrap(), data)
+    }
+
+    #[test]
+    fn denylist_page_keeps_wallets_sorted() {
+        let (mut page, mut data) = denylist_page(3);
+        for last_byte in [5, 1, 3] {
+            assert!(page.insert(&mut data, &wallet(9, last_byte)).unwrap());
+        }
+        assert_eq!(page.count, 3);
+        for (index, last_byte) in [1, 3, 5].into_iter().enumerate() {
+            assert_eq!(
+                data[DenylistPage::wallet_span(index)],
+                *wallet(9, last_byte).as_ref()
+            );
+        }
+        assert!(page.contains(&data, &wallet(9, 3)).unwrap());
+        assert!(!page.contains(&data, &wallet(9, 4)).unwrap());
+    }
+
+    #[test]
+    fn denylist_page_insert_needs_room() {
+        let (mut page, mut data) = denylist_page(1);
+        assert!(page.insert(&mut data, &wallet(9, 1)).unwrap());
+        // A wallet already denied needs no room.
+        assert!(!page.insert(&mut data, &wallet(9, 1)).unwrap());
+        assert_eq!(
+            page.insert(&mut data, &wallet(9, 2)),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+        assert_eq!(page.count, 1);
+    }
+
+    #[test]
+    fn denylist_page_removes_wallets() {
+        let (mut page, mut data) = denylist_page(3);
+        for last_byte in [1, 3, 5] {
+            page.insert(&mut data, &wallet(9, last_byte)).unwrap();
+        }
+        assert!(page.remove(&mut data, &wallet(9, 1)).unwrap());
+        assert!(!page.remove(&mut data, &wallet(9, 1)).unwrap());
+        assert_eq!(page.count, 2);
+        assert_eq!(data[DenylistPage::wallet_span(0)], *wallet(9, 3).as_ref());
+        assert_eq!(data[DenylistPage::wallet_span(1)], *wallet(9, 5).as_ref());
+        assert_eq!(data[DenylistPage::wallet_span(2)], [0; 32]);
+        assert!(page.contains(&data, &wallet(9, 5)).unwrap());
+    }
+
+    #[test]
+    fn denylist_page_rejects_truncated_data() {
+        let (mut page, mut data) = denylist_page(2);
+        page.insert(&mut data, &wallet(9, 1)).unwrap();
+        page.insert(&mut data, &wallet(9, 2)).unwrap();
+        let truncated = &data[..DenylistPage::wallet_span(1).start];
+        assert_eq!(
+            page.contains(truncated, &wallet(9, 1)),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn late_bid_extends_auction() {
+        let mut auction = auction();
+        auction.extend_for_late_bid(9_900).unwrap();
+        assert_eq!(auction.end_at, 10_020);
+
+        // A bid outside the window leaves the end untouched.
+        auction.extend_for_late_bid(9_000).unwrap();
+        assert_eq!(auction.end_at, 10_020);
+
+        // The extension never brings the end forward.
+        auction.anti_snipe_extension_sec = 10;
+        auction.extend_for_late_bid(10_000).unwrap();
+        assert_eq!(auction.end_at, 10_020);
+    }
+
+    #[test]
+    fn late_bid_extension_overflow_is_an_error() {
+        let mut auction = auction();
+        auction.end_at = i64::MIN;
+        assert_eq!(
+            auction.extend_for_late_bid(1),
+            Err(AuctionError::AmountOverflow.into())
+        );
+
+        auction.end_at = i64::MAX;
+        assert_eq!(
+            auction.extend_for_late_bid(i64::MAX),
+            Err(AuctionError::AmountOverflow.into())
+        );
+    }
+}